@@ -0,0 +1,217 @@
+use cursive::{
+    theme::{BaseColor, Color, ColorType, Effect, Style},
+    utils::markup::StyledString,
+};
+
+/// Converts text with ANSI escape sequences, like colored compiler output, into styled text
+///
+/// Colors and the bold and underline effects of SGR sequences like `\x1b[1;31m` are kept,
+/// all other escape sequences are removed, also operating system commands like hyperlinks. The style is reset at the end of the text.
+pub fn parse(text: &str) -> StyledString {
+    let mut styled = StyledString::new();
+    let mut style = Style::none();
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        styled.append_styled(&rest[..start], style);
+        rest = &rest[start + 1..];
+
+        if let Some(command) = rest.strip_prefix(']') {
+            // an operating system command like a hyperlink, ended by BEL or `\x1b\\`
+            rest = match command.find(['\x07', '\x1b']) {
+                Some(end) if command[end..].starts_with('\x07') => &command[end + 1..],
+                Some(end) => command[end + 1..]
+                    .strip_prefix('\\')
+                    .unwrap_or(&command[end..]),
+                None => "",
+            };
+            continue;
+        }
+        let Some(sequence) = rest.strip_prefix('[') else {
+            // a short sequence like `\x1b(B`, intermediates from ` ` to `/` and a final char
+            let end = rest
+                .find(|c: char| !(' '..='/').contains(&c))
+                .unwrap_or(rest.len());
+            let mut chars = rest[end..].chars();
+            chars.next();
+            rest = chars.as_str();
+            continue;
+        };
+        // parameters and intermediates are followed by a final byte from `@` to `~`
+        let Some(end) = sequence.find(|c: char| ('@'..='~').contains(&c)) else {
+            rest = "";
+            break;
+        };
+        if sequence[end..].starts_with('m') {
+            apply_sgr(&mut style, &sequence[..end]);
+        }
+        rest = &sequence[end + 1..];
+    }
+    styled.append_styled(rest, style);
+    styled
+}
+
+/// Updates the style by the parameters of an SGR sequence, like `1;31`
+fn apply_sgr(style: &mut Style, parameters: &str) {
+    let mut codes = parameters
+        .split(';')
+        .map(|code| code.parse::<u8>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        match code {
+            0 => *style = Style::none(),
+            1 | 3 | 4 | 7 => {
+                style.effects.insert(effect(code));
+            }
+            22 | 23 | 24 | 27 => {
+                // the codes resetting an effect are `20` higher
+                style.effects.remove(effect(code - 20));
+            }
+            30..=37 => style.color.front = base_color(code - 30, false),
+            90..=97 => style.color.front = base_color(code - 90, true),
+            40..=47 => style.color.back = base_color(code - 40, false),
+            100..=107 => style.color.back = base_color(code - 100, true),
+            39 => style.color.front = Style::none().color.front,
+            49 => style.color.back = Style::none().color.back,
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => codes.next().map(Color::from_256colors),
+                    Some(2) => match (codes.next(), codes.next(), codes.next()) {
+                        (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(color) = color {
+                    if code == 38 {
+                        style.color.front = color.into();
+                    } else {
+                        style.color.back = color.into();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the effect of an SGR code like `1` for bold, `2` is the dimmed text reset together
+/// with bold
+fn effect(code: u8) -> Effect {
+    match code {
+        1 | 2 => Effect::Bold,
+        3 => Effect::Italic,
+        4 => Effect::Underline,
+        _ => Effect::Reverse,
+    }
+}
+
+/// Returns one of the 8 basic colors, like `1` for red
+fn base_color(index: u8, light: bool) -> ColorType {
+    let base = BaseColor::from(index);
+    if light {
+        Color::Light(base).into()
+    } else {
+        Color::Dark(base).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the texts of the spans together with their styles
+    fn spans(text: &str) -> Vec<(String, Style)> {
+        parse(text)
+            .spans()
+            .filter(|span| !span.content.is_empty())
+            .map(|span| (span.content.to_string(), *span.attr))
+            .collect()
+    }
+
+    fn front(color: Color) -> Style {
+        let mut style = Style::none();
+        style.color.front = color.into();
+        style
+    }
+
+    fn back(color: Color) -> Style {
+        let mut style = Style::none();
+        style.color.back = color.into();
+        style
+    }
+
+    fn effects(effects: &[Effect]) -> Style {
+        let mut style = Style::none();
+        for effect in effects {
+            style.effects.insert(*effect);
+        }
+        style
+    }
+
+    #[test]
+    fn extended_colors_are_kept() {
+        assert_eq!(
+            spans("\x1b[38;5;196mred\x1b[0m \x1b[48;5;21mblue"),
+            [
+                ("red".to_string(), front(Color::from_256colors(196))),
+                (" ".to_string(), Style::none()),
+                ("blue".to_string(), back(Color::from_256colors(21))),
+            ]
+        );
+        assert_eq!(
+            spans("\x1b[38;2;255;128;0morange\x1b[39m\x1b[48;2;1;2;3mdark"),
+            [
+                ("orange".to_string(), front(Color::Rgb(255, 128, 0))),
+                ("dark".to_string(), back(Color::Rgb(1, 2, 3))),
+            ]
+        );
+        // incomplete colors are ignored
+        assert_eq!(
+            spans("\x1b[38;2;255mtext"),
+            [("text".to_string(), Style::none())]
+        );
+    }
+
+    #[test]
+    fn effects_are_reset_one_by_one() {
+        assert_eq!(
+            spans("\x1b[1;3;4;7mall\x1b[22mno bold\x1b[23;24mreversed\x1b[27mnone"),
+            [
+                (
+                    "all".to_string(),
+                    effects(&[
+                        Effect::Bold,
+                        Effect::Italic,
+                        Effect::Underline,
+                        Effect::Reverse
+                    ])
+                ),
+                (
+                    "no bold".to_string(),
+                    effects(&[Effect::Italic, Effect::Underline, Effect::Reverse])
+                ),
+                ("reversed".to_string(), effects(&[Effect::Reverse])),
+                ("none".to_string(), Style::none()),
+            ]
+        );
+    }
+
+    #[test]
+    fn other_sequences_are_removed() {
+        // clearing the line, moving and hiding the cursor, selecting a charset and a hyperlink
+        let text =
+            "\x1b[2Kcle\x1b[10;5Har\x1b(B\x1b[?25l \x1b]8;;https://a.rs\x1b\\link\x1b]8;;\x07\x1b=";
+        assert_eq!(parse(text).source(), "clear link");
+        assert!(spans(text).iter().all(|(_, style)| *style == Style::none()));
+    }
+
+    #[test]
+    fn truncated_sequences_are_dropped() {
+        assert_eq!(
+            spans("\x1b[31mred\x1b[1;3"),
+            [("red".to_string(), front(Color::Dark(BaseColor::Red)))]
+        );
+        assert_eq!(spans("text\x1b"), [("text".to_string(), Style::none())]);
+        assert_eq!(parse("text\x1b(").source(), "text");
+        assert_eq!(parse("text\x1b]8;;https://a.rs").source(), "text");
+    }
+}
@@ -1,25 +1,49 @@
 use std::{
-    collections::HashMap,
-    env,
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap, VecDeque},
+    env, fs,
+    hash::Hasher,
+    io::{self, Read},
+    mem,
+    ops::Range,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
-use crate::ui::edit_area::{Cursor, EditArea};
+use crate::ui::edit_area::{Cursor, Edit, EditArea};
 use cursive::{
+    align::HAlign,
+    backend::Backend,
     backends,
-    event::{Event, Key},
+    event::{Event, EventResult, EventTrigger, Key},
+    reexports::log::warn,
+    theme::{BaseColor, Color},
     view::{Nameable, Resizable},
-    views::{LinearLayout, NamedView, Panel, ResizedView, ScrollView},
+    views::{
+        FocusTracker, LinearLayout, NamedView, OnEventView, Panel, ResizedView, ScrollView,
+        TextView, ThemedView,
+    },
     Vec2,
 };
-use cursive_buffered_backend::BufferedBackend;
 use cursive_tree_view::TreeView;
-use syntect::highlighting::ThemeSet;
+use ropey::Rope;
+use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
 
 use crate::{
+    config::{Config, Settings},
+    cursor::{self, Library, TerminalCursor},
+    diff,
+    encoding::Format,
     error::ResultExt,
     events::{self, open_paths},
-    ui::file_tree::{self, TreeEntry},
+    paste::{self, BracketedPaste},
+    positions::{self, Position},
+    search::Search,
+    spell::Dictionary,
+    ui::{
+        empty_state,
+        file_tree::{self, TreeEntry},
+        notify, open_scratch, output, update_focus, update_status, update_title, update_tree_theme,
+    },
 };
 
 pub const PKG_NAME: &str = env!("CARGO_PKG_NAME");
@@ -29,29 +53,239 @@ pub const PKG_AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
 pub const PKG_DESCRIPTION: &str = env!("CARGO_PKG_DESCRIPTION");
 pub const PKG_LICENSE: &str = env!("CARGO_PKG_LICENSE");
 
+/// Maximal number of closed files which can be reopened
+const MAX_CLOSED_FILES: usize = 20;
+
+/// Name of the scratch buffer with the text piped in via `-`, shown inside of the title and goto
+pub const SCRATCH_NAME: &str = "stdin (scratch)";
+
 #[derive(Clone, Debug, Default)]
 pub struct State {
+    pub config: Config,
+    /// Settings changed at runtime, they are written back to the config file
+    pub settings: Settings,
+    /// Syntaxes for highlighting, loaded once and shared with the editor
+    pub syntax_set: Arc<SyntaxSet>,
+    /// Themes for highlighting, loaded once
+    pub theme_set: Arc<ThemeSet>,
     pub project_path: PathBuf,
     pub current_file: Option<PathBuf>,
     pub files: HashMap<PathBuf, FileData>,
+    /// Buffers without a file together with their names, like the text piped in via `-`, in
+    /// the order they were created
+    pub scratches: Vec<(String, FileData)>,
+    /// Name of the scratch buffer shown while there is no `current_file`
+    pub current_scratch: Option<String>,
     pub files_edited: HashMap<PathBuf, bool>,
+    /// Directory last used inside of a prompt, reset when switching projects
+    pub last_dir: Option<PathBuf>,
+    /// Recent notifications, the newest first
+    pub notifications: VecDeque<String>,
+    /// Dictionary for spell checking, shared with the editor
+    pub dictionary: Option<Arc<Dictionary>>,
+    /// File which is followed like `tail -f`, it can't be edited
+    pub following: Option<PathBuf>,
+    /// File with compiler output whose error locations are jumped through, together with the
+    /// index of the location jumped to last
+    pub error_output: Option<(PathBuf, usize)>,
+    /// Text searched last, it's kept after the highlighted matches were cleared
+    pub search: Option<String>,
+    /// Lines of the output of the run commands, kept across runs
+    pub output: Arc<VecDeque<String>>,
+    /// Search whose matches are highlighted inside of the output
+    pub output_search: Option<Search>,
+    /// Command run last, it's suggested for the next run
+    pub last_command: Option<String>,
+    /// Closed files with their positions, the one closed last at the end
+    pub closed_files: Vec<(PathBuf, Position)>,
 }
 
 #[derive(Clone, Debug, Default)]
 
 pub struct FileData {
-    pub str: String,
+    /// Content of the buffer, a rope so edits and line lookups don't copy the whole content
+    pub text: Rope,
     pub scroll_offset: Vec2,
     pub cursor: Cursor,
+    /// Only the beginning of a large file is loaded, so it can't be edited or saved
+    pub read_only: bool,
+    /// Hash of the content on disk when the file was loaded or saved the last time
+    pub saved_hash: u64,
+    /// Length in bytes of the content on disk, the content is only hashed if it's as long
+    pub saved_len: usize,
+    /// Files above the `highlight_limit` aren't highlighted, until it's turned on for them
+    pub highlighted: bool,
+    /// Notes attached to lines, like for a review, they aren't part of the content
+    pub notes: BTreeMap<usize, String>,
+    /// Encoding and line endings of the file on disk
+    pub format: Format,
+}
+
+impl FileData {
+    /// Returns the content as a string, it's copied out of the rope
+    pub fn str(&self) -> String {
+        self.text.to_string()
+    }
+
+    /// Hashes the content like [`content_hash`] without copying it
+    pub fn hash(&self) -> u64 {
+        chunks_hash(self.text.chunks())
+    }
+
+    /// Returns `true` if the content matches the one on disk, like after undoing all edits
+    ///
+    /// This is checked on every edit, so the content is only hashed if its length matches.
+    pub fn is_saved(&self) -> bool {
+        self.text.len_bytes() == self.saved_len && self.hash() == self.saved_hash
+    }
+
+    /// Remembers the current content as the one on disk
+    pub fn mark_saved(&mut self) {
+        self.saved_hash = self.hash();
+        self.saved_len = self.text.len_bytes();
+    }
+
+    /// Forgets the content on disk, like after the file was deleted, so the buffer is edited
+    pub fn mark_unsaved(&mut self) {
+        self.saved_hash = !self.hash();
+    }
+
+    /// Replaces the content, the notes move along with their lines
+    ///
+    /// Notes of removed lines are dropped, a line keeps the first note if several end up on it.
+    pub fn set_content(&mut self, content: &str) {
+        if !self.notes.is_empty() {
+            let notes = mem::take(&mut self.notes);
+            let moved = diff::moved_lines(&self.str(), content, notes.keys().copied());
+            for (line, note) in moved.into_iter().zip(notes.into_values()) {
+                if let Some(line) = line {
+                    self.notes.entry(line).or_insert(note);
+                }
+            }
+        }
+        self.text = Rope::from_str(content);
+    }
+
+    /// Applies an edit of the editor to the content, the notes move along with their lines
+    ///
+    /// Like [`set_content`](Self::set_content), but only the lines touched by the edit are
+    /// compared.
+    pub fn apply(&mut self, edit: &Edit) {
+        let Range { start, end } = edit.range;
+        if !self.notes.is_empty() {
+            let first = self.text.byte_to_line(start);
+            let last = self.text.byte_to_line(end);
+            let line_start = self.text.line_to_byte(first);
+            let line_end = if last + 1 < self.text.len_lines() {
+                self.text.line_to_byte(last + 1) - 1
+            } else {
+                self.text.len_bytes()
+            };
+            let old = self.text.byte_slice(line_start..line_end).to_string();
+            let new = [
+                &old[..start - line_start],
+                &edit.text,
+                &old[end - line_start..],
+            ]
+            .concat();
+            let shift = new.matches('\n').count() as isize - (last - first) as isize;
+
+            let notes = mem::take(&mut self.notes);
+            let touched = notes.range(first..=last).map(|(line, _)| line - first);
+            let mut moved = diff::moved_lines(&old, &new, touched).into_iter();
+            for (line, note) in notes {
+                let line = if line < first {
+                    Some(line)
+                } else if line <= last {
+                    moved.next().unwrap().map(|moved| moved + first)
+                } else {
+                    Some(line.saturating_add_signed(shift))
+                };
+                if let Some(line) = line {
+                    self.notes.entry(line).or_insert(note);
+                }
+            }
+        }
+        let (start, end) = (self.text.byte_to_char(start), self.text.byte_to_char(end));
+        self.text.remove(start..end);
+        self.text.insert(start, &edit.text);
+    }
+
+    /// Returns the position to remember after closing the file
+    pub fn position(&self) -> Position {
+        Position {
+            byte_offset: self.cursor.byte_offset,
+            scroll: (self.scroll_offset.x, self.scroll_offset.y),
+        }
+    }
+}
+
+/// Hashes a content, so it can be compared with the saved one without keeping a copy of it
+pub fn content_hash(content: &str) -> u64 {
+    chunks_hash([content])
+}
+
+/// Hashes a content split into chunks, the same as hashing them joined together
+fn chunks_hash<'a>(chunks: impl IntoIterator<Item = &'a str>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for chunk in chunks {
+        hasher.write(chunk.as_bytes());
+    }
+    hasher.finish()
 }
 
 impl State {
     pub fn is_file_edited(&self, path: &PathBuf) -> bool {
-        self.files_edited.get(path).is_some()
+        self.files_edited.contains_key(path)
     }
 
     pub fn is_current_file_edited(&self) -> bool {
-        self.is_file_edited(self.current_file.as_ref().unwrap_or(&PathBuf::default()))
+        match &self.current_file {
+            Some(current_file) => self.is_file_edited(current_file),
+            None => self
+                .current_scratch
+                .as_ref()
+                .is_some_and(|name| self.is_scratch_edited(name)),
+        }
+    }
+
+    /// Returns `true` if the scratch buffer was changed since it was created
+    pub fn is_scratch_edited(&self, name: &str) -> bool {
+        self.get_scratch(name)
+            .is_some_and(|scratch| !scratch.is_saved())
+    }
+
+    /// Returns the names of the scratch buffers which were changed since they were created
+    pub fn edited_scratches(&self) -> Vec<String> {
+        self.scratches
+            .iter()
+            .filter(|(_, scratch)| !scratch.is_saved())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn get_scratch(&self, name: &str) -> Option<&FileData> {
+        self.scratches
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, scratch)| scratch)
+    }
+
+    /// Returns the name for a new scratch buffer, like `scratch 2`, numbered by the lowest
+    /// unused number
+    pub fn new_scratch_name(&self) -> String {
+        (1..)
+            .map(|i| format!("scratch {i}"))
+            .find(|name| self.get_scratch(name).is_none())
+            .unwrap()
+    }
+
+    /// Removes a scratch buffer, the one created last is shown without a file afterwards
+    pub fn remove_scratch(&mut self, name: &str) {
+        self.scratches.retain(|(other, _)| other != name);
+        if self.current_scratch.as_deref() == Some(name) {
+            self.current_scratch = self.scratches.last().map(|(name, _)| name.clone());
+        }
     }
 
     pub fn get_file(&self, path: &PathBuf) -> Option<&FileData> {
@@ -59,7 +293,24 @@ impl State {
     }
 
     pub fn get_current_file(&self) -> Option<&FileData> {
-        self.get_file(self.current_file.as_ref().unwrap_or(&PathBuf::default()))
+        self.current_file
+            .as_ref()
+            .and_then(|current_file| self.get_file(current_file))
+    }
+
+    /// Returns the buffer shown inside of the editor, the current scratch buffer without a
+    /// current file
+    pub fn current_buffer_mut(&mut self) -> Option<&mut FileData> {
+        match &self.current_file {
+            Some(current_file) => self.files.get_mut(current_file),
+            None => {
+                let name = self.current_scratch.as_ref()?;
+                self.scratches
+                    .iter_mut()
+                    .find(|(other, _)| other == name)
+                    .map(|(_, scratch)| scratch)
+            }
+        }
     }
 
     pub fn remove_file(&mut self, path: &PathBuf) {
@@ -72,20 +323,73 @@ impl State {
         }
     }
 
+    /// Closes the buffer of a file, remembering it together with its position so it can be
+    /// reopened
+    pub fn close_file(&mut self, path: &PathBuf) {
+        if let Some(data) = self.get_file(path) {
+            let position = data.position();
+            self.closed_files.retain(|(closed, _)| closed != path);
+            self.closed_files.push((path.clone(), position));
+            if self.closed_files.len() > MAX_CLOSED_FILES {
+                self.closed_files.remove(0);
+            }
+        }
+        self.remove_file(path);
+    }
+
+    /// Returns the directory the prompts start in
+    ///
+    /// This is the last used directory if it still exists, otherwise the project directory.
+    pub fn prompt_dir(&self) -> &Path {
+        self.last_dir
+            .as_deref()
+            .filter(|dir| dir.is_dir())
+            .unwrap_or(&self.project_path)
+    }
+
+    /// Remembers the directory of a path used inside of a prompt
+    pub fn set_last_dir(&mut self, path: &Path) {
+        let dir = if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent()
+        };
+        self.last_dir = dir.and_then(|dir| dir.canonicalize().ok());
+    }
+
+    /// Switches to a new project directory
+    ///
+    /// The buffers of all opened files are kept, also if they're outside of the new project.
     pub fn open_new_project(
         &mut self,
         project_path: &Path,
         current_file: Option<&PathBuf>,
     ) -> Self {
-        self.project_path = project_path.canonicalize().unwrap_or_default();
-        let canonicalized_current_file =
-            current_file.map(|current_file| current_file.canonicalize().unwrap_or_default());
+        // paths which can't be canonicalized are kept, so they never become the empty path
+        let project_path = project_path
+            .canonicalize()
+            .unwrap_or_else(|_| project_path.to_path_buf());
+        if project_path != self.project_path {
+            self.last_dir = None;
+        }
+        self.project_path = project_path;
+        let canonicalized_current_file = current_file.map(|current_file| {
+            current_file
+                .canonicalize()
+                .unwrap_or_else(|_| current_file.clone())
+        });
         self.current_file = canonicalized_current_file;
         self.to_owned()
     }
 
+    /// Adds a file as a new buffer and makes it the current file
+    ///
+    /// All other buffers are kept inside of `files`, so they can be switched back to without
+    /// losing any unsaved changes.
     pub fn open_new_file(&mut self, current_file: PathBuf, content: FileData) -> Self {
-        let canonicalized_current_file = current_file.canonicalize().unwrap_or_default();
+        let canonicalized_current_file = current_file
+            .canonicalize()
+            .unwrap_or_else(|_| current_file.clone());
         self.files
             .insert(canonicalized_current_file.clone(), content);
         self.current_file = Some(canonicalized_current_file);
@@ -122,8 +426,12 @@ impl State {
 }
 
 // Helper types of the main/tree panel
-pub type EditorPanel = Panel<ResizedView<NamedView<EditArea>>>;
-pub type TreePanel = ResizedView<Panel<ScrollView<NamedView<TreeView<TreeEntry>>>>>;
+pub type EditorPanel = Panel<ThemedView<ResizedView<NamedView<EditArea>>>>;
+pub type TreePanel =
+    ResizedView<Panel<ThemedView<OnEventView<ScrollView<NamedView<TreeView<TreeEntry>>>>>>>;
+// The border themes of the panels, their content uses the general theme again
+pub type EditorBorder = ThemedView<NamedView<EditorPanel>>;
+pub type TreeBorder = ThemedView<NamedView<TreePanel>>;
 
 /// Starts the app && event loop
 pub fn start() {
@@ -131,19 +439,37 @@ pub fn start() {
 
     // gathering arguments
     let args: Vec<String> = env::args().collect();
-    let inc_path = if args.len() > 1 {
+    // `-` reads the piped text into a scratch buffer instead of opening a path
+    let stdin = (args.get(1).map(String::as_str) == Some("-")).then(read_stdin);
+    let inc_path = if args.len() > 1 && stdin.is_none() {
         Some(PathBuf::from(&args[1]))
     } else {
         None
     };
 
+    let config = Config::load();
+    let settings = config.clone().unwrap_or_default();
+
+    // without a path the configured project directory or else the working directory is used
+    let has_path = inc_path.is_some() || stdin.is_some();
     let mut file_path = None;
-    let mut project_path = PathBuf::from("/");
+    let mut project_path = settings
+        .default_project
+        .as_ref()
+        .map(PathBuf::from)
+        .filter(|path| path.is_dir())
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("/"));
 
     if let Some(inc_path) = inc_path {
         if inc_path.is_file() {
             file_path = Some(inc_path.clone());
-            project_path = PathBuf::from(inc_path.parent().unwrap_or(Path::new("/")));
+            // the parent of a bare file name like `main.rs` is empty, being the working directory
+            project_path = match inc_path.parent() {
+                Some(parent) if parent.as_os_str().is_empty() => PathBuf::from("."),
+                Some(parent) => parent.to_path_buf(),
+                None => PathBuf::from("/"),
+            };
         } else if inc_path.is_dir() {
             project_path = inc_path;
         } else {
@@ -160,85 +486,195 @@ pub fn start() {
     siv.clear_global_callbacks(Event::CtrlChar('q'));
     siv.clear_global_callbacks(Event::CtrlChar('g'));
     siv.clear_global_callbacks(Event::CtrlChar('o'));
+    siv.clear_global_callbacks(Event::CtrlChar('a'));
     siv.clear_global_callbacks(Event::CtrlChar('n'));
     siv.clear_global_callbacks(Event::CtrlChar('r'));
     siv.clear_global_callbacks(Event::CtrlChar('d'));
     siv.clear_global_callbacks(Event::CtrlChar('s'));
+    siv.clear_global_callbacks(Event::CtrlChar('b'));
+    siv.clear_global_callbacks(Event::CtrlChar('f'));
+    siv.clear_global_callbacks(Event::CtrlChar('w'));
+    siv.clear_global_callbacks(Event::CtrlChar('t'));
+    siv.clear_global_callbacks(Event::CtrlChar('u'));
+    siv.clear_global_callbacks(Key::F1);
+    siv.clear_global_callbacks(Key::F2);
+    siv.clear_global_callbacks(Key::F3);
+    siv.clear_global_callbacks(Key::F5);
+    siv.clear_global_callbacks(Key::F9);
+    siv.clear_global_callbacks(Key::F10);
+    siv.clear_global_callbacks(Key::F11);
+    siv.clear_global_callbacks(Key::F12);
 
     siv.add_global_callback(Key::Esc, |s| events::info(s).handle(s));
     siv.add_global_callback(Event::CtrlChar('p'), |s| s.toggle_debug_console());
     siv.add_global_callback(Event::CtrlChar('q'), |s| events::quit(s).handle(s));
     siv.add_global_callback(Event::CtrlChar('g'), |s| events::goto(s).handle(s));
     siv.add_global_callback(Event::CtrlChar('o'), |s| events::open(s).handle(s));
+    siv.add_global_callback(Event::CtrlChar('a'), |s| events::open_matching(s).handle(s));
     siv.add_global_callback(Event::CtrlChar('n'), |s| events::new(s).handle(s));
     siv.add_global_callback(Event::CtrlChar('r'), |s| events::rename(s).handle(s));
     siv.add_global_callback(Event::CtrlChar('d'), |s| events::delete(s).handle(s));
     siv.add_global_callback(Event::CtrlChar('s'), |s| events::save(s, None).handle(s));
+    siv.add_global_callback(Event::CtrlChar('w'), |s| events::save_all(s).handle(s));
+    siv.add_global_callback(Event::CtrlChar('b'), |s| events::switch_focus(s).handle(s));
+    siv.add_global_callback(Event::CtrlChar('f'), |s| events::search(s).handle(s));
+    siv.add_global_callback(Event::Alt(Key::Right), |s| events::find_next(s, true));
+    siv.add_global_callback(Event::Alt(Key::Left), |s| events::find_next(s, false));
+    siv.add_global_callback(Key::F1, |s| events::about(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F1), |s| events::new_scratch(s).handle(s));
+    siv.add_global_callback(Event::Ctrl(Key::F1), |s| events::statistics(s).handle(s));
+    siv.add_global_callback(Event::Ctrl(Key::F2), |s| events::compare(s).handle(s));
+    siv.add_global_callback(Event::Ctrl(Key::F3), |s| events::note(s).handle(s));
+    siv.add_global_callback(Event::Ctrl(Key::F4), |s| events::notes(s).handle(s));
+    siv.add_global_callback(Key::F2, |s| {
+        events::toggle_auto_pairs(s).notify(s);
+    });
+    siv.add_global_callback(Key::F3, |s| events::markers(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F3), |s| events::toggle_pin(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F8), |s| events::pinned(s).handle(s));
+    siv.add_global_callback(Key::F4, |s| events::align(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F4), |s| events::filter(s).handle(s));
+    siv.add_global_callback(Key::F5, |s| events::notifications(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F5), |s| events::follow(s).handle(s));
+    siv.add_global_callback(Key::F9, |s| events::open_externally(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F9), |s| events::run(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F2), output::toggle);
+    siv.add_global_callback(Event::Shift(Key::F6), |s| events::close(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F7), |s| events::reopen(s).handle(s));
+    siv.add_global_callback(Key::F10, |s| events::duplicate(s).handle(s));
+    siv.add_global_callback(Key::F11, |s| events::spelling(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F11), |s| {
+        events::toggle_highlighting(s).handle(s);
+    });
+    siv.add_global_callback(Key::F12, |s| events::copy_path(s).handle(s));
+    siv.add_global_callback(Event::Shift(Key::F12), |s| {
+        events::copy_path_as(s, true).handle(s);
+    });
+    siv.add_global_callback(Event::CtrlChar('t'), |s| events::templates(s).handle(s));
+    siv.add_global_callback(Event::CtrlChar('u'), |s| {
+        events::remove_duplicates(s).handle(s);
+    });
+    siv.add_global_callback(Event::Alt(Key::Down), |s| {
+        events::jump_to_error(s, true).handle(s);
+    });
+    siv.add_global_callback(Event::Alt(Key::Up), |s| {
+        events::jump_to_error(s, false).handle(s);
+    });
+    siv.add_global_callback(Event::Shift(Key::F10), |s| events::tree_menu(s, None));
+    siv.add_global_callback(' ', events::toggle_mark);
+    siv.add_global_callback(Event::Shift(Key::Up), |s| events::extend_marks(s, true));
+    siv.add_global_callback(Event::Shift(Key::Down), |s| events::extend_marks(s, false));
+    // views other than the editor take a bracketed paste char by char
+    siv.set_on_event_inner(
+        EventTrigger::from_fn(|event| paste::pasted_text(event).is_some()),
+        |event| {
+            let text = paste::pasted_text(event)?;
+            Some(EventResult::with_cb(move |s| paste::replay(s, &text)))
+        },
+    );
 
     // The current theme, needs to be passed on the general styling and the editor ui for fitting syntax highlighting style.
-    let theme = ThemeSet::load_defaults().themes["base16-eighties.dark"].clone();
+    // Loading these is slow, so they are only loaded once and shared.
+    let syntax_set = Arc::new(SyntaxSet::load_defaults_newlines());
+    let theme_set = Arc::new(ThemeSet::load_defaults());
+    let theme = theme_set.themes["base16-eighties.dark"].clone();
 
-    let mut raw_edit_area = EditArea::new(&theme).disabled();
+    let mut raw_edit_area = EditArea::new(syntax_set.clone(), &theme).disabled();
+    raw_edit_area.set_scroll_off(settings.scroll_off);
+    raw_edit_area.set_auto_pairs(settings.auto_pairs);
+    raw_edit_area.set_sticky_lines(if settings.sticky_scroll {
+        settings.sticky_scroll_lines
+    } else {
+        0
+    });
+    raw_edit_area.set_tab_size(settings.tab_size);
+    raw_edit_area.set_line_numbers(settings.line_numbers);
+    cursor::set_style(settings.cursor_shape, settings.cursor_blink);
+    let marker_color = Color::parse(&settings.marker_color).unwrap_or_else(|| {
+        warn!("Invalid marker color: {}", settings.marker_color);
+        Color::Dark(BaseColor::Yellow)
+    });
+    raw_edit_area.set_markers(settings.markers.clone(), marker_color);
+    raw_edit_area.set_placeholder(empty_state());
+    raw_edit_area.set_cb_sink(siv.cb_sink().clone());
+    raw_edit_area.set_on_large_paste(settings.large_paste_limit(), events::confirm_paste);
+    let dictionary = settings
+        .spell_check
+        .then(|| Dictionary::load(settings.dictionary.as_deref()).map(Arc::new))
+        .transpose();
+    if let Ok(dictionary) = &dictionary {
+        raw_edit_area.set_dictionary(dictionary.clone());
+    }
 
     // Detecting edits on `EditArea` and updating global state.
-    raw_edit_area.set_on_edit(|siv, content, scroll_offset, cursor| {
-        let mut state = siv
-            .with_user_data(|state: &mut State| state.clone())
-            .unwrap_or_default();
-        if let Some(current_file) = &state.current_file {
-            let contents = state.files.get_mut(current_file);
-            if let Some(contents) = contents {
-                contents.str = content.to_string();
+    //
+    // The state is mutated in place, cloning it would copy all opened files on every keystroke.
+    // Edits which lead back to the saved content, like undoing them, aren't counted as edited.
+    raw_edit_area.set_on_edit(|siv, edits, scroll_offset, cursor| {
+        let current_file = siv
+            .with_user_data(|state: &mut State| {
+                let contents = state.current_buffer_mut()?;
+                for edit in edits {
+                    contents.apply(edit);
+                }
                 contents.scroll_offset = scroll_offset;
                 contents.cursor = cursor;
-                state.files_edited.insert(current_file.clone(), true);
-
-                // Update title.
-                let title = state
-                    .clone()
-                    .current_file
-                    .unwrap_or_default()
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string()
-                    + " *";
-                siv.call_on_name("editor_title", |view: &mut EditorPanel| {
-                    view.set_title(title);
-                })
-                .unwrap();
-            }
+                let edited = !contents.is_saved();
+                let notes = contents.notes.keys().copied().collect();
+                // scratch buffers have no path, they're shown with an empty one
+                let Some(current_file) = state.current_file.clone() else {
+                    return Some((PathBuf::new(), edited, notes));
+                };
+                if edited {
+                    state.files_edited.insert(current_file.clone(), true);
+                } else {
+                    state.files_edited.remove(&current_file);
+                }
+                Some((current_file, edited, notes))
+            })
+            .flatten();
+
+        if let Some((current_file, edited, notes)) = current_file {
+            // Update title.
+            update_title(siv, edited, &current_file);
+            update_status(siv, Some(cursor));
+            siv.call_on_name("editor", |edit_area: &mut EditArea| {
+                edit_area.set_notes(notes);
+            });
         }
-        siv.set_user_data(state);
     });
 
     // Detecting cursor changes and updating global state.
-    raw_edit_area.set_on_interact(|siv, _, scroll_offset, cursor| {
-        let mut state = siv
-            .with_user_data(|state: &mut State| state.clone())
-            .unwrap_or_default();
-        if let Some(current_file) = &state.current_file {
-            let contents = state.files.get_mut(current_file);
-            if let Some(contents) = contents {
+    raw_edit_area.set_on_interact(|siv, scroll_offset, cursor| {
+        let updated = siv
+            .with_user_data(|state: &mut State| {
+                let contents = state.current_buffer_mut()?;
                 contents.scroll_offset = scroll_offset;
                 contents.cursor = cursor;
-            }
+                Some(())
+            })
+            .flatten();
+
+        if updated.is_some() {
+            update_status(siv, Some(cursor));
         }
-        siv.set_user_data(state);
     });
 
-    // Detecting scrolling and updating global state.
-    raw_edit_area.set_on_scroll(|siv, _, scroll_offset, _| {
-        let mut state = siv
-            .with_user_data(|state: &mut State| state.clone())
-            .unwrap_or_default();
-        if let Some(current_file) = &state.current_file {
-            let contents = state.files.get_mut(current_file);
-            if let Some(contents) = contents {
+    // Detecting scrolling and updating global state, paging moves the cursor along.
+    raw_edit_area.set_on_scroll(|siv, scroll_offset, cursor| {
+        let moved = siv
+            .with_user_data(|state: &mut State| {
+                let contents = state.current_buffer_mut()?;
                 contents.scroll_offset = scroll_offset;
-            }
+                let moved = contents.cursor.byte_offset != cursor.byte_offset;
+                contents.cursor = cursor;
+                Some(moved)
+            })
+            .flatten();
+
+        if moved == Some(true) {
+            update_status(siv, Some(cursor));
         }
-        siv.set_user_data(state);
     });
 
     // Setting general styling to theme
@@ -276,39 +712,370 @@ pub fn start() {
 
     let edit_area = raw_edit_area.with_name("editor").full_screen();
 
-    let editor_panel = Panel::new(edit_area).title("").with_name("editor_title");
-    let file_tree_panel = Panel::new(file_tree::new(&project_path))
+    let theme = siv.current_theme().clone();
+    let editor_panel = Panel::new(ThemedView::new(theme.clone(), edit_area))
         .title("")
-        .fixed_width(40)
-        .with_name("tree_title");
+        .with_name("editor_title");
+    let file_tree_panel = Panel::new(ThemedView::new(
+        theme.clone(),
+        file_tree::new(&project_path, settings.follow_symlinks),
+    ))
+    .title("")
+    .fixed_width(40)
+    .with_name("tree_title");
+    let editor_panel = ThemedView::new(theme.clone(), editor_panel).with_name("editor_border");
+    let file_tree_panel = ThemedView::new(theme, file_tree_panel).with_name("tree_border");
 
+    // Highlighting the title of the focused panel, also on mouse focus changes
     let layout = LinearLayout::horizontal()
-        .child(file_tree_panel)
-        .child(editor_panel);
+        .child(FocusTracker::new(file_tree_panel).on_focus(|_| EventResult::with_cb(update_focus)))
+        .child(FocusTracker::new(editor_panel).on_focus(|_| EventResult::with_cb(update_focus)))
+        .with_name("layout");
+
+    // Status bar below both panels showing notifications and the cursor position
+    let status = LinearLayout::horizontal()
+        .child(TextView::new("").with_name("notification").full_width())
+        .child(TextView::new("").h_align(HAlign::Right).with_name("status"));
 
-    siv.add_fullscreen_layer(layout);
+    siv.add_fullscreen_layer(
+        LinearLayout::vertical()
+            .child(layout)
+            .child(output::new())
+            .child(status),
+    );
 
+    let watch_tree = settings.watch_tree;
+    let backend_option = settings.backend.clone();
     // Set initial data.
+    siv.set_user_data(State {
+        config: settings,
+        syntax_set,
+        theme_set,
+        dictionary: dictionary.clone().unwrap_or_default(),
+        ..Default::default()
+    });
     open_paths(&mut siv, &project_path, file_path.as_ref()).unwrap();
+    if let Some(content) = stdin {
+        open_scratch(&mut siv, SCRATCH_NAME.to_string(), content);
+    }
+    update_tree_theme(&mut siv);
+    update_focus(&mut siv);
+    if watch_tree {
+        file_tree::watch(&mut siv);
+    }
+
+    if !has_path {
+        events::welcome(&mut siv);
+    }
+
+    // Show config errors after everything is set up.
+    config.handle(&mut siv);
+    dictionary.notify(&mut siv);
+
+    // The backend is initialized before the event loop, so a fallback can be shown.
+    let requested_backend = env::var("OMEGA_BACKEND").ok().or(backend_option);
+    let (backend, warning) = match backend(requested_backend.as_deref()) {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("Failed to initialize the terminal backend: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let Some(warning) = warning {
+        notify(&mut siv, warning);
+    }
 
     // Start event loop.
-    siv.run_with(|| backend());
+    siv.run_with(|| backend);
+
+    // Remember the positions inside of all opened files for reopening them later.
+    if let Some(state) = siv
+        .user_data::<State>()
+        .filter(|state| state.config.remember_positions)
+    {
+        let positions = state
+            .files
+            .iter()
+            .map(|(path, data)| (path.as_path(), data.position()));
+        if let Err(e) = positions::set(positions).and_then(|_| positions::prune()) {
+            eprintln!("The cursor positions couldn't be saved: {e}");
+        }
+    }
+}
+
+/// Reads the text piped into the editor, invalid UTF-8 is replaced
+///
+/// The terminal backends read the keys from the terminal itself, so stdin is free for it.
+fn read_stdin() -> String {
+    let mut bytes = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut bytes) {
+        eprintln!("Failed to read from stdin: {e}");
+        std::process::exit(1);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
 }
 
 /// Initiates a buffered Backend for improved visuals
 ///
-/// For windows it uses `crossterm`, for unix it uses `ncurses`
-fn backend() -> Box<BufferedBackend> {
+/// The requested backend is used if it's available, otherwise the default one is used and
+/// a warning is returned. By default `ncurses` with bracketed paste enabled is used on Unix
+/// and `crossterm` on Windows. Fails if the default backend can't be initialized either,
+/// like without a terminal.
+fn backend(requested: Option<&str>) -> io::Result<(Box<dyn Backend>, Option<String>)> {
+    let warning = match requested.map(|name| (name, init_backend(name))) {
+        Some((_, Ok(backend))) => return Ok((backend, None)),
+        Some((name, Err(e))) => {
+            let warning = format!(
+                "The backend {name} couldn't be used, the default one is used instead: {e}"
+            );
+            warn!("{warning}");
+            Some(warning)
+        }
+        None => None,
+    };
+
     #[cfg(unix)]
-    {
-        let ncurses_backend = backends::curses::n::Backend::init().unwrap();
-        let buffered_backend = cursive_buffered_backend::BufferedBackend::new(ncurses_backend);
-        Box::new(buffered_backend)
-    }
+    let backend = init_backend("ncurses")?;
     #[cfg(windows)]
-    {
-        let crossterm_backend = backends::crossterm::Backend::init().unwrap();
-        let buffered_backend = cursive_buffered_backend::BufferedBackend::new(crossterm_backend);
-        Box::new(buffered_backend)
+    let backend = init_backend("crossterm")?;
+    Ok((backend, warning))
+}
+
+/// Initiates the backend with the name, `ncurses` and `termion` are only available on Unix
+fn init_backend(name: &str) -> io::Result<Box<dyn Backend>> {
+    match name {
+        #[cfg(unix)]
+        "ncurses" => {
+            // the backend panics without a terminal instead of returning an error
+            fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/tty")?;
+            let ncurses_backend = backends::curses::n::Backend::init()?;
+            let buffered_backend = cursive_buffered_backend::BufferedBackend::new(ncurses_backend);
+            let cursor_backend = TerminalCursor::new(buffered_backend, Library::Ncurses);
+            Ok(Box::new(BracketedPaste::new(cursor_backend)))
+        }
+        "crossterm" => {
+            // the crossterm backend of cursive can't handle paste events yet
+            let crossterm_backend = backends::crossterm::Backend::init()
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            let buffered_backend =
+                cursive_buffered_backend::BufferedBackend::new(crossterm_backend);
+            Ok(Box::new(TerminalCursor::new(
+                buffered_backend,
+                Library::Other,
+            )))
+        }
+        #[cfg(unix)]
+        "termion" => {
+            // like crossterm, paste events aren't handled yet
+            let termion_backend = backends::termion::Backend::init()?;
+            let buffered_backend = cursive_buffered_backend::BufferedBackend::new(termion_backend);
+            Ok(Box::new(TerminalCursor::new(
+                buffered_backend,
+                Library::Other,
+            )))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the backend isn't available",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    /// Returns a buffer with the content
+    fn buffer(content: &str) -> FileData {
+        FileData {
+            text: Rope::from_str(content),
+            saved_hash: content_hash(content),
+            saved_len: content.len(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn edits_move_notes_like_replacing_the_content() {
+        let content = "a\nb\nc\nd\ne";
+        let edits = [
+            (2..2, "new\n"),
+            (1..5, ""),
+            (3..4, "x\ny"),
+            (0..content.len(), "all"),
+            (content.len()..content.len(), "\nf"),
+        ];
+        for (range, text) in edits {
+            let mut edited = buffer(content);
+            edited.notes = (0..5).map(|line| (line, line.to_string())).collect();
+            let mut replaced = edited.clone();
+
+            let edit = Edit {
+                range: range.clone(),
+                text: text.to_string(),
+            };
+            edited.apply(&edit);
+            let mut new = content.to_string();
+            new.replace_range(range, text);
+            replaced.set_content(&new);
+            assert_eq!(edited.str(), new);
+            assert_eq!(edited.notes, replaced.notes, "{edit:?}");
+        }
+    }
+
+    #[test]
+    fn undone_edits_are_saved() {
+        let mut edited = buffer("saved");
+        edited.apply(&Edit {
+            range: 0..5,
+            text: "edits".to_string(),
+        });
+        // as long as the saved content, so it's hashed
+        assert!(!edited.is_saved());
+        edited.apply(&Edit {
+            range: 5..5,
+            text: "!".to_string(),
+        });
+        assert!(!edited.is_saved());
+        edited.apply(&Edit {
+            range: 0..6,
+            text: "saved".to_string(),
+        });
+        assert!(edited.is_saved());
+    }
+
+    /// Returns a content of several MB with multi-byte chars, so the rope has many chunks
+    fn large_content() -> String {
+        (0..200_000)
+            .map(|i| format!("line {i} with ä and 😀\n"))
+            .collect()
+    }
+
+    #[test]
+    fn edits_keep_large_contents() {
+        let mut expected = large_content();
+        let mut edited = buffer(&expected);
+        assert_eq!(edited.hash(), content_hash(&expected));
+
+        for i in 0..1_000 {
+            // a char boundary somewhere inside of the content
+            let mut start = (i * 7_919 * 1_009) % expected.len();
+            while !expected.is_char_boundary(start) {
+                start -= 1;
+            }
+            let end = expected[start..]
+                .char_indices()
+                .nth(i % 3)
+                .map_or(expected.len(), |(len, _)| start + len);
+            let text = ["", "ö", "new\nline"][i % 3];
+            edited.apply(&Edit {
+                range: start..end,
+                text: text.to_string(),
+            });
+            expected.replace_range(start..end, text);
+        }
+        assert_eq!(edited.str(), expected);
+        assert_eq!(edited.hash(), content_hash(&expected));
+        assert!(!edited.is_saved());
+    }
+
+    /// Editing a large buffer via the rope is faster than replacing a range of a string, like
+    /// the buffers were edited before
+    #[test]
+    fn edits_of_large_files_dont_copy_the_content() {
+        const EDITS: usize = 2_000;
+        let content = large_content();
+        let middle = content.len() / 2;
+
+        let mut rope = buffer(&content);
+        let start = Instant::now();
+        for _ in 0..EDITS {
+            rope.apply(&Edit {
+                range: middle..middle,
+                text: "x".to_string(),
+            });
+        }
+        let rope_time = start.elapsed();
+
+        let mut string = content.clone();
+        let start = Instant::now();
+        for _ in 0..EDITS {
+            string.replace_range(middle..middle, "x");
+        }
+        let string_time = start.elapsed();
+
+        assert_eq!(rope.str(), string);
+        assert!(rope_time < string_time);
+    }
+
+    #[test]
+    fn opening_a_file_keeps_the_other_buffers() {
+        let mut state = State::default();
+        let (first, second) = (
+            PathBuf::from("/missing/first.rs"),
+            PathBuf::from("/missing/second.rs"),
+        );
+        state.open_new_file(first.clone(), buffer("first"));
+        state
+            .current_buffer_mut()
+            .unwrap()
+            .text
+            .insert(5, " edited");
+        state.open_new_file(second.clone(), buffer("second"));
+
+        assert_eq!(state.current_file.as_ref(), Some(&second));
+        assert_eq!(state.get_file(&first).unwrap().str(), "first edited");
+        assert_eq!(state.get_file(&second).unwrap().str(), "second");
+    }
+
+    #[test]
+    fn paths_which_cant_be_canonicalized_are_kept() {
+        let mut state = State::default();
+        let (project, file) = (
+            Path::new("/missing/project"),
+            PathBuf::from("/missing/project/file.rs"),
+        );
+        state.open_new_project(project, Some(&file));
+        assert_eq!(state.project_path, project);
+        assert_eq!(state.current_file.as_ref(), Some(&file));
+
+        let other = PathBuf::from("relative/missing.rs");
+        state.open_new_file(other.clone(), buffer("other"));
+        assert_eq!(state.current_file.as_ref(), Some(&other));
+        assert!(state.files.keys().all(|path| path != Path::new("")));
+    }
+
+    #[test]
+    fn existing_paths_are_canonicalized() {
+        let mut state = State::default();
+        let dir = env::temp_dir();
+        state.open_new_project(&dir.join("."), None);
+        assert_eq!(state.project_path, dir.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn no_current_file_is_not_the_empty_path() {
+        let mut state = State::default();
+        state.files.insert(PathBuf::new(), buffer("empty path"));
+        state.files_edited.insert(PathBuf::new(), true);
+        assert!(state.get_current_file().is_none());
+        assert!(state.current_buffer_mut().is_none());
+        assert!(!state.is_current_file_edited());
+    }
+
+    #[test]
+    fn opening_a_project_keeps_the_buffers() {
+        let mut state = State::default();
+        let file = PathBuf::from("/missing/file.rs");
+        state.open_new_file(file.clone(), buffer("content"));
+        state.open_new_project(Path::new("/missing/other"), None);
+
+        assert_eq!(state.current_file, None);
+        assert_eq!(state.get_file(&file).unwrap().str(), "content");
     }
 }
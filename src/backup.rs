@@ -0,0 +1,220 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::Config, ui::path_input::expand_home};
+
+/// The previous content of a file, written aside before the file is overwritten
+///
+/// It only replaces the existing backups via [`Pending::commit`] once the file was saved, so a
+/// failed save leaves them untouched.
+#[derive(Debug)]
+pub struct Pending {
+    temp: PathBuf,
+    paths: Backups,
+}
+
+impl Pending {
+    /// Moves the backup into place, the older backups are kept up to `backup_count`
+    pub fn commit(self) -> io::Result<()> {
+        let count = self.paths.count;
+        remove_if_exists(&self.paths.path(count - 1))?;
+        for i in (0..count - 1).rev() {
+            let path = self.paths.path(i);
+            if path.exists() {
+                fs::rename(path, self.paths.path(i + 1))?;
+            }
+        }
+        fs::rename(&self.temp, self.paths.path(0))?;
+
+        // backups left over from a higher backup_count
+        let mut i = count;
+        while self.paths.path(i).exists() {
+            fs::remove_file(self.paths.path(i))?;
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// Removes the written backup again, the existing backups stay as they are
+    pub fn discard(self) {
+        let _ = fs::remove_file(self.temp);
+    }
+}
+
+/// Writes the previous content of the file aside, if backups are enabled
+///
+/// The file shouldn't be overwritten if this fails, as there would be no backup of it.
+pub fn prepare(config: &Config, file: &Path, content: &[u8]) -> io::Result<Option<Pending>> {
+    let Some(paths) = Backups::new(config, file) else {
+        return Ok(None);
+    };
+    fs::create_dir_all(&paths.dir)?;
+
+    let mut temp = paths.path(0).into_os_string();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, content)?;
+    Ok(Some(Pending { temp, paths }))
+}
+
+/// The naming scheme of the backups of a file
+#[derive(Debug)]
+struct Backups {
+    dir: PathBuf,
+    name: String,
+    count: usize,
+}
+
+impl Backups {
+    /// Returns `None` if backups are disabled
+    ///
+    /// Inside of the `backup_dir` the whole path is part of the name, like `%home%main.rs~`, so
+    /// files with the same name don't share their backups.
+    fn new(config: &Config, file: &Path) -> Option<Self> {
+        if !config.backup || config.backup_count == 0 {
+            return None;
+        }
+        // without a suffix the backup would replace the file itself
+        let suffix = match config.backup_suffix.as_str() {
+            "" if config.backup_dir.is_none() => "~",
+            suffix => suffix,
+        };
+        let (dir, name) = match &config.backup_dir {
+            Some(dir) => (
+                expand_home(dir),
+                file.to_string_lossy().replace(['/', '\\', ':'], "%"),
+            ),
+            None => (
+                file.parent()?.to_path_buf(),
+                file.file_name()?.to_string_lossy().to_string(),
+            ),
+        };
+        Some(Self {
+            dir,
+            name: format!("{name}{suffix}"),
+            count: config.backup_count,
+        })
+    }
+
+    /// The path of the backup, `0` is the newest one, like `main.rs~`, older ones are numbered,
+    /// like `main.rs~1`
+    fn path(&self, i: usize) -> PathBuf {
+        match i {
+            0 => self.dir.join(&self.name),
+            i => self.dir.join(format!("{}{i}", self.name)),
+        }
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    /// Saves the content into the file like saving a buffer does, with a backup of its previous
+    /// content
+    fn save(config: &Config, file: &Path, content: &str) {
+        let pending = prepare(config, file, &fs::read(file).unwrap()).unwrap();
+        fs::write(file, content).unwrap();
+        pending.unwrap().commit().unwrap();
+    }
+
+    /// Returns the names and contents of the files inside of the directory
+    fn files(dir: &Path) -> Vec<(String, String)> {
+        let mut files: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                (name, fs::read_to_string(path).unwrap())
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn file(name: &str, content: &str) -> (String, String) {
+        (name.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn backups_are_rotated() {
+        let dir = temp_dir("backups");
+        let path = dir.join("main.rs");
+        fs::write(&path, "0").unwrap();
+        let mut config = Config {
+            backup: true,
+            backup_count: 3,
+            ..Default::default()
+        };
+
+        for content in ["1", "2", "3", "4"] {
+            save(&config, &path, content);
+        }
+        assert_eq!(
+            files(&dir),
+            [
+                file("main.rs", "4"),
+                file("main.rs~", "3"),
+                file("main.rs~1", "2"),
+                file("main.rs~2", "1"),
+            ]
+        );
+
+        // the ones behind a lowered count are removed
+        config.backup_count = 1;
+        save(&config, &path, "5");
+        assert_eq!(files(&dir), [file("main.rs", "5"), file("main.rs~", "4")]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_saves_keep_the_backups() {
+        let dir = temp_dir("backups-discarded");
+        let path = dir.join("main.rs");
+        fs::write(&path, "0").unwrap();
+        let config = Config {
+            backup: true,
+            backup_count: 2,
+            ..Default::default()
+        };
+        save(&config, &path, "1");
+
+        let pending = prepare(&config, &path, b"1").unwrap().unwrap();
+        pending.discard();
+        assert_eq!(files(&dir), [file("main.rs", "1"), file("main.rs~", "0")]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn backups_inside_of_the_backup_dir_are_named_by_the_whole_path() {
+        let dir = temp_dir("backup-dir");
+        fs::create_dir(dir.join("src")).unwrap();
+        let path = dir.join("src/main.rs");
+        fs::write(&path, "0").unwrap();
+        let config = Config {
+            backup: true,
+            backup_dir: Some(dir.join("backups").to_string_lossy().to_string()),
+            backup_suffix: String::new(),
+            ..Default::default()
+        };
+        save(&config, &path, "1");
+
+        let name = path.to_string_lossy().replace(['/', '\\', ':'], "%");
+        assert_eq!(files(&dir.join("backups")), [file(&name, "0")]);
+        assert_eq!(fs::read_to_string(path).unwrap(), "1");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
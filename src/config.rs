@@ -0,0 +1,603 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use cursive::reexports::log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use syntect::parsing::Regex;
+
+use crate::{app::PKG_NAME, error::Result};
+
+/// Date format used if none or an invalid one is configured
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// The user configuration, stored as `config.json` inside of the config directory
+///
+/// Missing options are set to their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Lines kept visible above and below the cursor, `0` disables the margin
+    pub scroll_off: usize,
+    /// Closing brackets and quotes automatically
+    pub auto_pairs: bool,
+    /// Auto-paired chars for languages by their syntax name, like `["()", "<>"]` for `"HTML"`,
+    /// other languages pair brackets and quotes
+    pub language_pairs: HashMap<String, Vec<String>>,
+    /// Pinning the headers of the blocks enclosing the first visible line to the top
+    pub sticky_scroll: bool,
+    /// Maximal number of pinned headers
+    pub sticky_scroll_lines: usize,
+    /// Width of an indentation level and of a tab character
+    pub tab_size: usize,
+    /// Indenting new lines like the line before them, adjusted by the `indent_rules`
+    pub auto_indent: bool,
+    /// Rules adjusting the indentation of new and typed lines
+    pub indent_rules: IndentRules,
+    /// Indent rules for languages by their syntax name, overriding `indent_rules`
+    pub language_indent_rules: HashMap<String, IndentRules>,
+    /// Chars besides letters and digits which are part of words, like the `_` of identifiers
+    pub word_chars: String,
+    /// Word chars for languages by their syntax name, overriding `word_chars`
+    pub language_word_chars: HashMap<String, String>,
+    /// Numbering of the lines, absolute ones or relative to the cursor
+    pub line_numbers: LineNumbers,
+    /// Shape of the cursor inside of the editor
+    pub cursor_shape: CursorShape,
+    /// Blinking the cursor inside of the editor
+    pub cursor_blink: bool,
+    /// Matching the case of searches, by default only searches with uppercase letters do
+    pub search_case: SearchCase,
+    /// Words which are highlighted inside of comments
+    pub markers: Vec<String>,
+    /// Color of the highlighted markers, like `"yellow"`, `"light red"` or `"#ffcc00"`
+    pub marker_color: String,
+    /// Format of the inserted date/time, see `strftime` for the specifiers
+    pub date_format: String,
+    /// Snippets by syntax name, like `"Rust"`, mapping trigger words to their bodies, the ones of
+    /// `"*"` are available in all languages
+    pub snippets: HashMap<String, HashMap<String, String>>,
+    /// Columns at which vertical rulers are drawn, like `[72, 80]`
+    pub rulers: Vec<usize>,
+    /// Rulers for files with a specific extension, overriding `rulers`
+    pub language_rulers: HashMap<String, Vec<usize>>,
+    /// Syntax names for files with a specific extension, like `"INI"` for `"conf"`, used
+    /// instead of detecting them
+    pub language_syntaxes: HashMap<String, String>,
+    /// Highlighting the text behind the last ruler
+    pub highlight_overflow: bool,
+    /// Underlining misspelled words inside of comments and strings, or everywhere in prose
+    pub spell_check: bool,
+    /// Hunspell dictionary used for spell checking, its `.dic` file, the English one of the system if not set
+    pub dictionary: Option<String>,
+    /// Expanding symlinked directories inside of the tree and searching them for markers
+    pub follow_symlinks: bool,
+    /// Terminal backend like `"crossterm"`, `"ncurses"` and `"termion"` are only available on Unix
+    pub backend: Option<String>,
+    /// Deprecated, `false` is moved into `overwrite` of `confirmations` as `never` when loading
+    #[serde(skip_serializing)]
+    pub confirm_overwrite: Option<bool>,
+    /// Which actions ask for a confirmation first
+    pub confirmations: Confirmations,
+    /// Refreshing the tree when files are changed outside of the editor, which may be slow on
+    /// network file systems
+    pub watch_tree: bool,
+    /// Writing a backup of the previous content of a file when saving it
+    pub backup: bool,
+    /// Appended to the name of a backup, like `main.rs~`
+    pub backup_suffix: String,
+    /// Directory for all backups, next to the saved files if not set
+    pub backup_dir: Option<String>,
+    /// Number of backups kept per file, older ones are numbered like `main.rs~1`
+    pub backup_count: usize,
+    /// Saving the edited current file when switching to another one
+    pub autosave_on_switch: bool,
+    /// Restoring the cursor and scroll position of reopened files, also in later sessions
+    pub remember_positions: bool,
+    /// Size in MB above which opening a file asks first, `0` disables the warning
+    pub large_file_warning: u64,
+    /// Number of lines above which files aren't highlighted, `0` highlights all of them
+    pub highlight_limit: usize,
+    /// Size in KB above which pasting asks first, `0` disables the warning
+    pub large_paste_warning: usize,
+    /// Shell commands run in the background when a file is opened or saved
+    pub hooks: Hooks,
+    /// Project directory opened when no path is given, the working directory if not set
+    pub default_project: Option<String>,
+    /// Border color of the focused panel, the highlight color of the theme if not set
+    pub border_color: Option<String>,
+    /// Title color of the focused panel, the highlight color of the theme if not set
+    pub title_color: Option<String>,
+    /// Title color of the other panel, the text color of the theme if not set
+    pub inactive_title_color: Option<String>,
+    /// Color of the selected entry of the tree, the highlight color of the theme if not set
+    pub selection_color: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            scroll_off: 0,
+            auto_pairs: true,
+            language_pairs: [
+                // lifetimes like `'a` aren't closed
+                ("Rust", vec!["()", "[]", "{}", "\"\""]),
+                ("Markdown", vec!["()", "[]", "{}", "\"\"", "``"]),
+                ("HTML", vec!["()", "[]", "{}", "\"\"", "''", "<>"]),
+                ("XML", vec!["\"\"", "''", "<>"]),
+            ]
+            .into_iter()
+            .map(|(language, pairs)| {
+                (
+                    language.to_string(),
+                    pairs.into_iter().map(String::from).collect(),
+                )
+            })
+            .collect(),
+            sticky_scroll: true,
+            sticky_scroll_lines: 5,
+            tab_size: 4,
+            auto_indent: true,
+            indent_rules: IndentRules::new(r"[{(\[]\s*$", r"^\s*[}\])]"),
+            language_indent_rules: [
+                (
+                    "Python",
+                    IndentRules::new(
+                        r"(:|[{(\[])\s*(#.*)?$",
+                        r"^\s*([}\])]|(elif|else|except|finally)\b.*:\s*$)",
+                    ),
+                ),
+                // lists don't need a deeper indentation inside of YAML
+                ("YAML", IndentRules::new(r":\s*(#.*)?$", "")),
+            ]
+            .into_iter()
+            .map(|(language, rules)| (language.to_string(), rules))
+            .collect(),
+            word_chars: "_".to_string(),
+            // names like `font-size` or `string->symbol` are single words
+            language_word_chars: [("CSS", "_-"), ("Lisp", "_-?!*<>"), ("Clojure", "_-?!*<>")]
+                .into_iter()
+                .map(|(language, chars)| (language.to_string(), chars.to_string()))
+                .collect(),
+            line_numbers: LineNumbers::Absolute,
+            cursor_shape: CursorShape::Block,
+            cursor_blink: false,
+            search_case: SearchCase::Smart,
+            markers: ["TODO", "FIXME", "HACK", "XXX"].map(String::from).to_vec(),
+            marker_color: "yellow".to_string(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            snippets: [
+                ("*", vec![("date", "$DATE")]),
+                (
+                    "Rust",
+                    vec![
+                        ("fn", "fn ${1:name}($2) {\n    $0\n}"),
+                        ("test", "#[test]\nfn ${1:name}() {\n    $0\n}"),
+                    ],
+                ),
+            ]
+            .into_iter()
+            .map(|(language, snippets)| {
+                (
+                    language.to_string(),
+                    snippets
+                        .into_iter()
+                        .map(|(trigger, body)| (trigger.to_string(), body.to_string()))
+                        .collect(),
+                )
+            })
+            .collect(),
+            rulers: Vec::new(),
+            language_rulers: HashMap::new(),
+            language_syntaxes: HashMap::new(),
+            highlight_overflow: false,
+            spell_check: false,
+            dictionary: None,
+            follow_symlinks: true,
+            backup: false,
+            backup_suffix: "~".to_string(),
+            backup_dir: None,
+            backup_count: 1,
+            autosave_on_switch: false,
+            remember_positions: true,
+            large_file_warning: 50,
+            highlight_limit: 50_000,
+            large_paste_warning: 512,
+            hooks: Hooks::default(),
+            default_project: None,
+            watch_tree: true,
+            confirm_overwrite: None,
+            confirmations: Confirmations::default(),
+            backend: None,
+            border_color: None,
+            title_color: None,
+            inactive_title_color: None,
+            selection_color: None,
+        }
+    }
+}
+
+/// How the lines are numbered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineNumbers {
+    /// Numbering the lines from the start of the file
+    Absolute,
+    /// Showing the distance to the line of the cursor, which is numbered `0`
+    Relative,
+    /// Like `Relative`, but the line of the cursor shows its absolute number
+    Hybrid,
+}
+
+/// Shape of the terminal cursor inside of the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorShape {
+    /// Covering the whole cell
+    Block,
+    /// A line below the char
+    Underline,
+    /// A thin bar at the left side of the cell
+    Bar,
+}
+
+/// How the case of a searched text is matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchCase {
+    /// Matching the case exactly
+    Sensitive,
+    /// Ignoring the case
+    Insensitive,
+    /// Ignoring the case unless the searched text contains an uppercase letter
+    Smart,
+}
+
+/// When an action asks for a confirmation first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confirm {
+    /// Asking every time
+    Always,
+    /// Never asking, the action is done right away
+    Never,
+    /// Only asking if a directory is affected
+    Directories,
+}
+
+impl Confirm {
+    /// Returns `true` if the action asks first, `is_dir` is set if it affects a directory
+    pub fn asks(self, is_dir: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Directories => is_dir,
+        }
+    }
+}
+
+/// Regexes adjusting the indentation, an empty one never matches
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndentRules {
+    /// Indenting the line after a matching line by one more level, like after a `{`
+    pub increase: String,
+    /// Indenting a line by one level less once it starts to match while typing, like a `}`
+    pub decrease: String,
+}
+
+impl IndentRules {
+    fn new(increase: &str, decrease: &str) -> Self {
+        Self {
+            increase: increase.to_string(),
+            decrease: decrease.to_string(),
+        }
+    }
+
+    /// Returns the regex of lines increasing the indentation, `None` if it's empty or invalid
+    pub fn increase_regex(&self) -> Option<Regex> {
+        compile_rule(&self.increase)
+    }
+
+    /// Returns the regex of lines decreasing the indentation, `None` if it's empty or invalid
+    pub fn decrease_regex(&self) -> Option<Regex> {
+        compile_rule(&self.decrease)
+    }
+}
+
+/// Compiles an indent rule, invalid ones are logged
+fn compile_rule(rule: &str) -> Option<Regex> {
+    if rule.is_empty() {
+        return None;
+    }
+    match Regex::try_compile(rule) {
+        Some(e) => {
+            warn!("Invalid indent rule {rule:?}: {e}");
+            None
+        }
+        None => Some(Regex::new(rule.to_string())),
+    }
+}
+
+/// Which actions ask for a confirmation first, all of them do by default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Confirmations {
+    /// Deleting a file or directory of the tree
+    pub delete: Confirm,
+    /// Creating a new file in place of an existing one
+    pub overwrite: Confirm,
+    /// Dropping unsaved changes of a file by deleting or overwriting it, even if these don't ask
+    pub discard_unsaved: Confirm,
+    /// Quitting with unsaved changes, without asking the files are saved first
+    pub quit_unsaved: Confirm,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self {
+            delete: Confirm::Always,
+            overwrite: Confirm::Always,
+            discard_unsaved: Confirm::Always,
+            quit_unsaved: Confirm::Always,
+        }
+    }
+}
+
+/// Shell commands run on the events of a file, they get its path via the `FILE` environment
+/// variable
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Run after a file was loaded into a new buffer
+    pub open: Vec<String>,
+    /// Run right before a file is written, the save doesn't wait for them
+    pub before_save: Vec<String>,
+    /// Run after a file was written
+    pub after_save: Vec<String>,
+}
+
+impl Config {
+    /// Returns the rulers for files with the given extension
+    pub fn rulers_for(&self, extension: &str) -> Vec<usize> {
+        self.language_rulers
+            .get(extension)
+            .unwrap_or(&self.rulers)
+            .clone()
+    }
+
+    /// Returns the chars besides letters and digits which are part of words in the syntax
+    pub fn word_chars_for(&self, syntax_name: &str) -> String {
+        self.language_word_chars
+            .iter()
+            .find(|(language, _)| language.eq_ignore_ascii_case(syntax_name))
+            .map_or(&self.word_chars, |(_, chars)| chars)
+            .clone()
+    }
+
+    /// Returns the indent rules of the syntax, `None` if auto-indentation is disabled
+    pub fn indent_rules_for(&self, syntax_name: &str) -> Option<&IndentRules> {
+        if !self.auto_indent {
+            return None;
+        }
+        self.language_indent_rules
+            .iter()
+            .find(|(language, _)| language.eq_ignore_ascii_case(syntax_name))
+            .map_or(Some(&self.indent_rules), |(_, rules)| Some(rules))
+    }
+
+    /// Returns the auto-paired chars configured for the syntax, `None` if there are none
+    ///
+    /// Entries which don't consist of exactly two chars are skipped.
+    pub fn pairs_for(&self, syntax_name: &str) -> Option<Vec<(char, char)>> {
+        let (_, pairs) = self
+            .language_pairs
+            .iter()
+            .find(|(language, _)| language.eq_ignore_ascii_case(syntax_name))?;
+        Some(
+            pairs
+                .iter()
+                .filter_map(|pair| {
+                    let mut chars = pair.chars();
+                    match (chars.next(), chars.next(), chars.next()) {
+                        (Some(open), Some(close), None) => Some((open, close)),
+                        _ => {
+                            warn!("Invalid auto-pair {pair:?} for {syntax_name}");
+                            None
+                        }
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the snippets available for the syntax, the ones of `"*"` included
+    pub fn snippets_for(&self, syntax_name: &str) -> HashMap<String, String> {
+        let mut snippets = self.snippets.get("*").cloned().unwrap_or_default();
+        for (language, language_snippets) in &self.snippets {
+            if language.eq_ignore_ascii_case(syntax_name) {
+                snippets.extend(language_snippets.clone());
+            }
+        }
+        snippets
+    }
+
+    /// Returns the configured syntax name for files with the given extension
+    ///
+    /// The extensions may be written with a leading dot, like `".tsx"`.
+    pub fn syntax_for(&self, extension: &str) -> Option<&str> {
+        self.language_syntaxes
+            .iter()
+            .find(|(configured, _)| configured.trim_start_matches('.') == extension)
+            .map(|(_, syntax)| syntax.as_str())
+    }
+
+    /// Returns the size in bytes above which opening a file asks first
+    pub fn large_file_limit(&self) -> Option<u64> {
+        (self.large_file_warning > 0).then_some(self.large_file_warning * 1024 * 1024)
+    }
+
+    /// Returns the size in bytes above which pasting asks first
+    pub fn large_paste_limit(&self) -> Option<usize> {
+        (self.large_paste_warning > 0).then_some(self.large_paste_warning * 1024)
+    }
+
+    /// Returns `true` if the content is short enough to be highlighted
+    pub fn highlights(&self, content: &str) -> bool {
+        self.highlight_limit == 0 || content.lines().nth(self.highlight_limit).is_none()
+    }
+
+    /// Loads the config file, using the defaults if it doesn't exist
+    pub fn load() -> Result<Self> {
+        let Some(path) = path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str::<Self>(&content)?.migrated()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Moves deprecated options into the ones replacing them
+    fn migrated(mut self) -> Self {
+        if self.confirm_overwrite.take() == Some(false) {
+            self.confirmations.overwrite = Confirm::Never;
+        }
+        self
+    }
+}
+
+/// Settings changed at runtime, like toggling the auto-pairs, which are kept for the next
+/// launches
+///
+/// Only the changed settings are written into the config file, the other options of the file are
+/// kept as they are and missing ones aren't filled with their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Settings {
+    /// Auto-pairing toggled inside of the editor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_pairs: Option<bool>,
+}
+
+impl Settings {
+    /// Writes the changed settings into the config file, creating it if it doesn't exist
+    pub fn save(&self) -> Result<()> {
+        match path() {
+            Some(path) => self.write(&path),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the changed settings into the config file at the path
+    ///
+    /// A config file which couldn't be parsed isn't overwritten, to not lose its content.
+    fn write(&self, path: &Path) -> Result<()> {
+        let mut options = match fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(Value::Object(options)) => options,
+                _ => {
+                    warn!("Config file couldn't be parsed, the changed settings aren't saved");
+                    return Ok(());
+                }
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => Map::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if let Value::Object(settings) = serde_json::to_value(self)? {
+            options.extend(settings);
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&options)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// The directory of all config files
+///
+/// For windows it's inside of `%APPDATA%`, for unix inside of `$XDG_CONFIG_HOME` or `~/.config`
+pub fn dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let base = env::var_os("APPDATA").map(PathBuf::from);
+    #[cfg(not(windows))]
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    base.map(|base| base.join(PKG_NAME))
+}
+
+/// The path of the config file
+pub fn path() -> Option<PathBuf> {
+    dir().map(|dir| dir.join("config.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn settings_only_change_their_keys() {
+        let dir = temp_dir("settings");
+        let path = dir.join("config.json");
+        let settings = Settings {
+            auto_pairs: Some(false),
+        };
+
+        // a missing file only gets the settings
+        settings.write(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"auto_pairs\": false\n}\n"
+        );
+
+        // the other options keep their order and aren't filled with defaults
+        fs::write(
+            &path,
+            r#"{"tab_size": 2, "auto_pairs": true, "rulers": [80]}"#,
+        )
+        .unwrap();
+        settings.write(&path).unwrap();
+        let options: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            options.as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["tab_size", "auto_pairs", "rulers"]
+        );
+        assert_eq!(options["auto_pairs"], false);
+        assert_eq!(options["tab_size"], 2);
+
+        // invalid files are kept
+        fs::write(&path, "{ invalid").unwrap();
+        settings.write(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ invalid");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn confirm_overwrite_is_moved_into_the_confirmations() {
+        let overwrite = |options: &str| {
+            let config: Config = serde_json::from_str(options).unwrap();
+            config.migrated().confirmations.overwrite
+        };
+        assert_eq!(overwrite(r#"{"confirm_overwrite": false}"#), Confirm::Never);
+        assert_eq!(overwrite(r#"{"confirm_overwrite": true}"#), Confirm::Always);
+        assert_eq!(
+            overwrite(r#"{"confirm_overwrite": true, "confirmations": {"overwrite": "never"}}"#),
+            Confirm::Never
+        );
+        assert_eq!(
+            overwrite(r#"{"confirm_overwrite": false, "confirmations": {"overwrite": "always"}}"#),
+            Confirm::Never
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+};
+
+use cursive::{backend::Backend, reexports::log::warn, theme, Printer, Vec2};
+
+use crate::config::CursorShape;
+
+/// Screen position of the terminal cursor in the next frame, set while the focused editor is
+/// drawn
+static POSITION: Mutex<Option<Vec2>> = Mutex::new(None);
+
+/// DECSCUSR parameter of the configured cursor shape, `0` is the default of the terminal
+static STYLE: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the shape of the terminal cursor and whether it blinks
+pub fn set_style(shape: CursorShape, blink: bool) {
+    let steady = match shape {
+        CursorShape::Block => 2,
+        CursorShape::Underline => 4,
+        CursorShape::Bar => 6,
+    };
+    STYLE.store(steady - u8::from(blink), Ordering::Relaxed);
+}
+
+/// Shows the terminal cursor at the position of the printer in the frame which is drawn
+///
+/// Positions outside of the visible area of the printer are ignored.
+pub fn show(printer: &Printer, pos: Vec2) {
+    if !pos.fits(printer.content_offset)
+        || !pos.strictly_lt(printer.content_offset + printer.output_size)
+    {
+        return;
+    }
+    *POSITION.lock().unwrap() = Some(printer.offset + pos - printer.content_offset);
+}
+
+/// The library of the wrapped backend, which decides how the cursor is moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Library {
+    /// ncurses keeps track of the cursor, so it has to be moved by ncurses itself
+    #[cfg(unix)]
+    Ncurses,
+    /// Libraries moving the cursor for every print like crossterm and termion, it's moved via
+    /// crossterm afterwards
+    Other,
+}
+
+/// Backend wrapper showing the terminal cursor at the position set by [`show`]
+///
+/// The terminal draws the cursor itself in the shape set by [`set_style`], so it blinks without
+/// redrawing the editor. Terminals which don't support setting the shape keep their own one.
+/// Frames without a position, like while a dialog is focused, hide the cursor.
+pub struct TerminalCursor<B: Backend> {
+    inner: B,
+    library: Library,
+    /// DECSCUSR parameter which was written last
+    style: u8,
+}
+
+impl<B: Backend> TerminalCursor<B> {
+    /// Wraps the backend, which has to be of the library
+    pub fn new(inner: B, library: Library) -> Self {
+        Self {
+            inner,
+            library,
+            style: 0,
+        }
+    }
+
+    /// Moves the cursor to the position and shows it, or hides it without one
+    fn place(&self, pos: Option<Vec2>) {
+        match self.library {
+            #[cfg(unix)]
+            Library::Ncurses => {
+                use cursive::backends::curses::n::ncurses;
+                if let Some(pos) = pos {
+                    ncurses::mv(pos.y as i32, pos.x as i32);
+                    ncurses::curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_VISIBLE);
+                    ncurses::refresh();
+                }
+            }
+            Library::Other => {
+                use cursive::backends::crossterm::crossterm::{
+                    cursor::{MoveTo, Show},
+                    execute,
+                };
+                if let Some(pos) = pos {
+                    if execute!(io::stdout(), MoveTo(pos.x as u16, pos.y as u16), Show).is_err() {
+                        warn!("The cursor couldn't be shown");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hides the cursor while the frame is drawn, so it doesn't jump around
+    fn hide(&self) {
+        match self.library {
+            #[cfg(unix)]
+            Library::Ncurses => {
+                use cursive::backends::curses::n::ncurses;
+                ncurses::curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+            }
+            Library::Other => {
+                use cursive::backends::crossterm::crossterm::{cursor::Hide, execute};
+                if execute!(io::stdout(), Hide).is_err() {
+                    warn!("The cursor couldn't be hidden");
+                }
+            }
+        }
+    }
+}
+
+impl<B: Backend> Drop for TerminalCursor<B> {
+    fn drop(&mut self) {
+        if self.style != 0 {
+            write_style(0);
+        }
+    }
+}
+
+impl<B: Backend> Backend for TerminalCursor<B> {
+    fn poll_event(&mut self) -> Option<cursive::event::Event> {
+        self.inner.poll_event()
+    }
+
+    fn set_title(&mut self, title: String) {
+        self.inner.set_title(title);
+    }
+
+    fn refresh(&mut self) {
+        let style = STYLE.load(Ordering::Relaxed);
+        if style != self.style {
+            write_style(style);
+            self.style = style;
+        }
+        self.hide();
+        self.inner.refresh();
+        self.place(POSITION.lock().unwrap().take());
+    }
+
+    fn has_colors(&self) -> bool {
+        self.inner.has_colors()
+    }
+
+    fn screen_size(&self) -> Vec2 {
+        self.inner.screen_size()
+    }
+
+    fn print_at(&self, pos: Vec2, text: &str) {
+        self.inner.print_at(pos, text);
+    }
+
+    fn print_at_rep(&self, pos: Vec2, repetitions: usize, text: &str) {
+        self.inner.print_at_rep(pos, repetitions, text);
+    }
+
+    fn clear(&self, color: theme::Color) {
+        self.inner.clear(color);
+    }
+
+    fn set_color(&self, colors: theme::ColorPair) -> theme::ColorPair {
+        self.inner.set_color(colors)
+    }
+
+    fn set_effect(&self, effect: theme::Effect) {
+        self.inner.set_effect(effect);
+    }
+
+    fn unset_effect(&self, effect: theme::Effect) {
+        self.inner.unset_effect(effect);
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// Sets the shape of the terminal cursor via DECSCUSR
+fn write_style(style: u8) {
+    let mut stdout = io::stdout();
+    if stdout
+        .write_all(format!("\x1b[{style} q").as_bytes())
+        .and_then(|_| stdout.flush())
+        .is_err()
+    {
+        warn!("The cursor shape couldn't be changed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_are_decscusr_parameters() {
+        let style = |shape, blink| {
+            set_style(shape, blink);
+            STYLE.load(Ordering::Relaxed)
+        };
+        assert_eq!(style(CursorShape::Block, true), 1);
+        assert_eq!(style(CursorShape::Block, false), 2);
+        assert_eq!(style(CursorShape::Underline, true), 3);
+        assert_eq!(style(CursorShape::Bar, false), 6);
+    }
+}
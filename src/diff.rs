@@ -0,0 +1,213 @@
+/// Maximal number of compared line pairs, larger changes are shown as replaced as a whole
+const MAX_COMPARISONS: usize = 1_000_000;
+
+/// A line of the difference between two texts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Line<'a> {
+    /// Inside of both texts
+    Same(&'a str),
+    /// Only inside of the old text
+    Removed(&'a str),
+    /// Only inside of the new text
+    Added(&'a str),
+}
+
+/// A row of two texts shown side by side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Row<'a> {
+    /// Inside of both texts
+    Same(&'a str),
+    /// A line of the old text replaced by one of the new text
+    Changed(&'a str, &'a str),
+    /// Only inside of the old text
+    Removed(&'a str),
+    /// Only inside of the new text
+    Added(&'a str),
+}
+
+/// Returns the lines of both texts, the ones which differ marked as removed or added
+///
+/// The longest common subsequence of lines is kept, like `diff` does.
+pub fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // the common start and end don't have to be compared
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut result = old[..prefix]
+        .iter()
+        .map(|line| Line::Same(line))
+        .collect::<Vec<_>>();
+    if old_middle.len() * new_middle.len() > MAX_COMPARISONS {
+        result.extend(old_middle.iter().map(|line| Line::Removed(line)));
+        result.extend(new_middle.iter().map(|line| Line::Added(line)));
+    } else {
+        result.extend(common_subsequence(old_middle, new_middle));
+    }
+    result.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| Line::Same(line)),
+    );
+    result
+}
+
+/// Compares all lines with each other, keeping the longest common subsequence
+fn common_subsequence<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    // lengths[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut result = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            result.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            result.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            result.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    result.extend(old[i..].iter().map(|line| Line::Removed(line)));
+    result.extend(new[j..].iter().map(|line| Line::Added(line)));
+    result
+}
+
+/// Pairs the lines of both texts into rows, removed lines followed by added ones are shown as
+/// changed next to each other
+pub fn rows<'a>(old: &'a str, new: &'a str) -> Vec<Row<'a>> {
+    let mut rows = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    for line in lines(old, new) {
+        match line {
+            Line::Same(line) => {
+                pair_changes(&mut rows, &mut removed, &mut added);
+                rows.push(Row::Same(line));
+            }
+            Line::Removed(line) => removed.push(line),
+            Line::Added(line) => added.push(line),
+        }
+    }
+    pair_changes(&mut rows, &mut removed, &mut added);
+    rows
+}
+
+/// Moves the removed and added lines of a change into the rows, pairwise as long as possible
+fn pair_changes<'a>(rows: &mut Vec<Row<'a>>, removed: &mut Vec<&'a str>, added: &mut Vec<&'a str>) {
+    let changed = removed.len().min(added.len());
+    rows.extend(
+        removed
+            .iter()
+            .zip(added.iter())
+            .map(|(old, new)| Row::Changed(old, new)),
+    );
+    rows.extend(removed.drain(..).skip(changed).map(Row::Removed));
+    rows.extend(added.drain(..).skip(changed).map(Row::Added));
+}
+
+/// Returns where the lines of the old text are inside of the new one, `None` for removed lines
+///
+/// Only the common lines at the start and at the end are compared, the lines of the changed
+/// part between them keep their place, or move to its last line if it shrank, like after
+/// joining lines.
+pub fn moved_lines(
+    old: &str,
+    new: &str,
+    lines: impl IntoIterator<Item = usize>,
+) -> Vec<Option<usize>> {
+    let old = old.split('\n').collect::<Vec<_>>();
+    let new = new.split('\n').collect::<Vec<_>>();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+
+    lines
+        .into_iter()
+        .map(|line| {
+            if line < prefix {
+                Some(line)
+            } else if line >= old_end {
+                Some(line - old_end + new_end)
+            } else if new_end > prefix {
+                Some(line.min(new_end - 1))
+            } else {
+                // the changed lines were removed as a whole
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_move_along_with_edits() {
+        let old = "a\nb\nc\nd";
+        // lines inserted in front of them
+        assert_eq!(
+            moved_lines(old, "new\na\nb\nc\nd", [0, 3]),
+            [Some(1), Some(4)]
+        );
+        // lines removed in front of them, the removed ones are gone
+        assert_eq!(
+            moved_lines(old, "a\nd", [0, 1, 2, 3]),
+            [Some(0), None, None, Some(1)]
+        );
+        // a changed line keeps its place
+        assert_eq!(
+            moved_lines(old, "a\nchanged\nc\nd", [1, 3]),
+            [Some(1), Some(3)]
+        );
+    }
+
+    #[test]
+    fn lines_of_a_shrunk_change_move_to_its_last_line() {
+        // joining the lines `b` and `c`
+        assert_eq!(
+            moved_lines("a\nb\nc\nd", "a\nbc\nd", [1, 2, 3]),
+            [Some(1), Some(1), Some(2)]
+        );
+        // splitting them again keeps the lines where they are
+        assert_eq!(
+            moved_lines("a\nbc\nd", "a\nb\nc\nd", [1, 2]),
+            [Some(1), Some(3)]
+        );
+    }
+
+    #[test]
+    fn trailing_newlines_are_lines() {
+        assert_eq!(moved_lines("a\n", "new\na\n", [0, 1]), [Some(1), Some(2)]);
+        assert_eq!(moved_lines("a", "a\n", [0]), [Some(0)]);
+    }
+}
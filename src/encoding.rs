@@ -0,0 +1,300 @@
+use std::{fs, io, path::Path};
+
+/// How the text of a file is stored on disk, saving writes it back the same way
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    pub encoding: Encoding,
+    /// All lines end with `\r\n`, inside of the buffer they end with `\n` instead
+    ///
+    /// Files with mixed line endings are kept as they are, with the `\r` inside of the buffer.
+    pub crlf: bool,
+}
+
+/// The encoding of a file, recognized by its byte order mark
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+    const UTF16_LE_BOM: [u8; 2] = [0xff, 0xfe];
+    const UTF16_BE_BOM: [u8; 2] = [0xfe, 0xff];
+
+    /// Returns the byte order mark of the encoding, which is empty for plain UTF-8
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Self::Utf8 => &[],
+            Self::Utf8Bom => &Self::UTF8_BOM,
+            Self::Utf16Le => &Self::UTF16_LE_BOM,
+            Self::Utf16Be => &Self::UTF16_BE_BOM,
+        }
+    }
+
+    /// Returns the encoding and the length of its byte order mark
+    fn detect(bytes: &[u8]) -> (Self, usize) {
+        if bytes.starts_with(&Self::UTF8_BOM) {
+            (Self::Utf8Bom, Self::UTF8_BOM.len())
+        } else if bytes.starts_with(&Self::UTF16_LE_BOM) {
+            (Self::Utf16Le, Self::UTF16_LE_BOM.len())
+        } else if bytes.starts_with(&Self::UTF16_BE_BOM) {
+            (Self::Utf16Be, Self::UTF16_BE_BOM.len())
+        } else {
+            (Self::Utf8, 0)
+        }
+    }
+}
+
+/// Returns the text of the file and how it's stored, `None` if it's binary like an image
+///
+/// Files which can't be decoded or contain a null byte are seen as binary. With `partial` the
+/// bytes may end in the middle of a char, like when only the beginning of the file was read,
+/// which is cut off.
+pub fn decode(bytes: &[u8], partial: bool) -> Option<(String, Format)> {
+    let (encoding, bom) = Encoding::detect(bytes);
+    let bytes = &bytes[bom..];
+    let text = match encoding {
+        Encoding::Utf8 | Encoding::Utf8Bom => {
+            let len = if partial {
+                crate::ui::valid_utf8_len(bytes)
+            } else {
+                bytes.len()
+            };
+            String::from_utf8(bytes[..len].to_vec()).ok()?
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            if bytes.len() % 2 != 0 && !partial {
+                return None;
+            }
+            let units = bytes.chunks_exact(2).map(|unit| match encoding {
+                Encoding::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]),
+                _ => u16::from_be_bytes([unit[0], unit[1]]),
+            });
+            let mut text = String::with_capacity(bytes.len() / 2);
+            for c in char::decode_utf16(units) {
+                match c {
+                    Ok(c) => text.push(c),
+                    // a surrogate pair cut in half
+                    Err(_) if partial => break,
+                    Err(_) => return None,
+                }
+            }
+            text
+        }
+    };
+    if text.contains('\0') {
+        return None;
+    }
+
+    let crlf = text.contains("\r\n") && text.matches('\n').count() == text.matches("\r\n").count();
+    let text = if crlf {
+        text.replace("\r\n", "\n")
+    } else {
+        text
+    };
+    Some((text, Format { encoding, crlf }))
+}
+
+/// Returns the text of the bytes appended to a file stored in the format, together with the
+/// number of bytes which were decoded
+///
+/// Like with [`decode`] of `partial` bytes, an incomplete char at the end is left for the next
+/// call, as is a `\r` which may be followed by a `\n`. At the `start` of the file its byte order
+/// mark is skipped. Invalid bytes are replaced instead of failing, as the file can't be
+/// rejected anymore.
+pub fn decode_appended(bytes: &[u8], format: Format, start: bool) -> (String, usize) {
+    let bom = format.encoding.bom();
+    let bom = if start && bytes.starts_with(bom) {
+        bom.len()
+    } else {
+        0
+    };
+    let bytes = &bytes[bom..];
+    let (mut text, mut len, unit) = match format.encoding {
+        Encoding::Utf8 | Encoding::Utf8Bom => {
+            let len = crate::ui::valid_utf8_len(bytes);
+            (String::from_utf8_lossy(&bytes[..len]).into_owned(), len, 1)
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|unit| match format.encoding {
+                    Encoding::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]),
+                    _ => u16::from_be_bytes([unit[0], unit[1]]),
+                })
+                .collect();
+            let mut text = String::with_capacity(units.len());
+            let mut len = 0;
+            for c in char::decode_utf16(units.iter().copied()) {
+                match c {
+                    Ok(c) => {
+                        text.push(c);
+                        len += c.len_utf16() * 2;
+                    }
+                    // the second half of a surrogate pair may not have been written yet
+                    Err(_) if len + 2 == units.len() * 2 => break,
+                    Err(_) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        len += 2;
+                    }
+                }
+            }
+            (text, len, 2)
+        }
+    };
+    if format.crlf {
+        if text.ends_with('\r') {
+            text.pop();
+            len -= unit;
+        }
+        text = text.replace("\r\n", "\n");
+    }
+    (text, bom + len)
+}
+
+/// Returns the bytes of the text stored in the format, the reverse of [`decode`]
+pub fn encode(text: &str, format: Format) -> Vec<u8> {
+    let text = if format.crlf {
+        text.replace('\n', "\r\n").into()
+    } else {
+        std::borrow::Cow::Borrowed(text)
+    };
+    match format.encoding {
+        Encoding::Utf8 => text.as_bytes().to_vec(),
+        Encoding::Utf8Bom => [&Encoding::UTF8_BOM, text.as_bytes()].concat(),
+        Encoding::Utf16Le => Encoding::UTF16_LE_BOM
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect(),
+        Encoding::Utf16Be => Encoding::UTF16_BE_BOM
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+            .collect(),
+    }
+}
+
+/// Reads the text of the file and how it's stored, like [`fs::read_to_string`]
+pub fn read(path: &Path) -> io::Result<(String, Format)> {
+    decode_text(&fs::read(path)?)
+}
+
+/// Like [`decode`] of a whole file, but binary files are an error
+pub fn decode_text(bytes: &[u8]) -> io::Result<(String, Format)> {
+    decode(bytes, false)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "The file isn't a text file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Files as other editors write them, saving them without edits must keep every byte
+    const FIXTURES: [(&str, &[u8]); 6] = [
+        ("utf8.txt", include_bytes!("../tests/fixtures/utf8.txt")),
+        (
+            "utf8_bom.txt",
+            include_bytes!("../tests/fixtures/utf8_bom.txt"),
+        ),
+        (
+            "utf16le.txt",
+            include_bytes!("../tests/fixtures/utf16le.txt"),
+        ),
+        (
+            "utf16be.txt",
+            include_bytes!("../tests/fixtures/utf16be.txt"),
+        ),
+        ("crlf.txt", include_bytes!("../tests/fixtures/crlf.txt")),
+        ("mixed.txt", include_bytes!("../tests/fixtures/mixed.txt")),
+    ];
+
+    #[test]
+    fn fixtures_round_trip() {
+        for (name, bytes) in FIXTURES {
+            let (text, format) = decode(bytes, false).unwrap_or_else(|| panic!("{name}"));
+            assert_eq!(encode(&text, format), bytes, "{name}");
+        }
+    }
+
+    #[test]
+    fn detects_format() {
+        let format = |name: &str| {
+            let (_, bytes) = FIXTURES.iter().find(|(other, _)| *other == name).unwrap();
+            decode(bytes, false).unwrap().1
+        };
+        assert_eq!(format("utf8.txt"), Format::default());
+        assert_eq!(format("utf8_bom.txt").encoding, Encoding::Utf8Bom);
+        assert_eq!(format("utf16le.txt").encoding, Encoding::Utf16Le);
+        assert_eq!(format("utf16be.txt").encoding, Encoding::Utf16Be);
+        assert!(format("crlf.txt").crlf);
+        assert!(!format("mixed.txt").crlf);
+    }
+
+    #[test]
+    fn crlf_is_normalized() {
+        let (text, format) = decode(b"a\r\nb\r\n", false).unwrap();
+        assert_eq!(text, "a\nb\n");
+        // a new line typed into the buffer gets the line ending of the file
+        assert_eq!(encode("a\nnew\nb\n", format), b"a\r\nnew\r\nb\r\n");
+    }
+
+    #[test]
+    fn mixed_line_endings_are_kept() {
+        let (text, format) = decode(b"a\r\nb\n", false).unwrap();
+        assert_eq!(text, "a\r\nb\n");
+        assert_eq!(encode(&text, format), b"a\r\nb\n");
+    }
+
+    #[test]
+    fn appended_text_is_decoded_in_the_format() {
+        let crlf = Format {
+            encoding: Encoding::Utf8Bom,
+            crlf: true,
+        };
+        let (text, len) = decode_appended(b"\xef\xbb\xbfa\r\nb\r", crlf, true);
+        assert_eq!((text.as_str(), len), ("a\nb", 7));
+        // the held back `\r` is decoded together with its `\n`
+        let (text, len) = decode_appended(b"\r\nc\r\n", crlf, false);
+        assert_eq!((text.as_str(), len), ("\nc\n", 5));
+
+        let utf16 = Format {
+            encoding: Encoding::Utf16Le,
+            crlf: false,
+        };
+        let bytes = encode("a😀", utf16);
+        let (text, len) = decode_appended(&bytes[..bytes.len() - 2], utf16, true);
+        assert_eq!((text.as_str(), len), ("a", 4));
+        let (text, len) = decode_appended(&bytes[len..], utf16, false);
+        assert_eq!((text.as_str(), len), ("😀", 4));
+    }
+
+    #[test]
+    fn binary_is_rejected() {
+        assert_eq!(decode(b"a\0b", false), None);
+        assert_eq!(decode(&[0xc3, 0x28], false), None);
+        // UTF-16 without a byte order mark
+        assert_eq!(decode(&[b'a', 0, b'b', 0], false), None);
+        // an odd number of bytes
+        assert_eq!(decode(&[0xff, 0xfe, b'a'], false), None);
+    }
+
+    #[test]
+    fn partial_cuts_off_incomplete_chars() {
+        let (text, _) = decode("aä".as_bytes().split_last().unwrap().1, true).unwrap();
+        assert_eq!(text, "a");
+        let bytes = encode(
+            "a😀",
+            Format {
+                encoding: Encoding::Utf16Le,
+                crlf: false,
+            },
+        );
+        let (text, _) = decode(&bytes[..bytes.len() - 2], true).unwrap();
+        assert_eq!(text, "a");
+        let (text, _) = decode(&bytes[..bytes.len() - 1], true).unwrap();
+        assert_eq!(text, "a");
+    }
+}
@@ -2,6 +2,8 @@ use std::fmt::{self, Debug};
 
 use cursive::{reexports::log::error, view::Nameable, views::Dialog, Cursive};
 
+use crate::{events::add_dialog, ui::notify};
+
 /// The error type.
 #[repr(i64)]
 #[derive(Debug, Clone)]
@@ -12,6 +14,10 @@ pub enum Error {
     FileOpen(String),
     /// The Text could not be saved to the clipboard
     Clipboard(String),
+    /// The config file could not be parsed
+    Config(String),
+    /// An external command failed
+    Command(String),
 }
 
 impl std::error::Error for Error {}
@@ -22,6 +28,8 @@ impl fmt::Display for Error {
             Error::Arguments(e) => write!(f, "Arguments: {e}.\nForce quit via ctrl + f or toggle the goto via ctrl + d"),
             Error::FileOpen(e) => write!(f, "File System Error: {e}. Check the file path and permissions.\nForce quit via ctrl + f or toggle the goto via ctrl + o"),
             Error::Clipboard(e) => write!(f, "Clipboard: {e}. Ensure your clipboard manager is running.\nForce quit via ctrl + f or toggle the goto via ctrl + d"),
+            Error::Config(e) => write!(f, "Config: {e}. Check the syntax of your config file, the defaults are used instead."),
+            Error::Command(e) => write!(f, "Command: {e}"),
         }
     }
 }
@@ -47,6 +55,20 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<notify_debouncer_mini::notify::Error> for Error {
+    fn from(e: notify_debouncer_mini::notify::Error) -> Self {
+        error!("notify::Error: {e}");
+        Self::FileOpen(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        error!("serde_json::Error: {e}");
+        Self::Config(e.to_string())
+    }
+}
+
 impl Error {
     /// Converts this error into a UI element for a Cursive application.
     pub fn to_dialog(self, siv: &mut Cursive) {
@@ -54,7 +76,8 @@ impl Error {
             siv.screen_mut().remove_layer(pos);
         }
         let error_message = self.to_string();
-        siv.add_layer(
+        add_dialog(
+            siv,
             Dialog::text(error_message)
                 .title("Error")
                 .padding_lrtb(1, 1, 1, 0)
@@ -72,6 +95,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// Extension for handler function
 pub trait ResultExt<T> {
     fn handle(self, siv: &mut Cursive);
+    fn notify(self, siv: &mut Cursive);
 }
 
 impl<T> ResultExt<T> for Result<T> {
@@ -81,4 +105,11 @@ impl<T> ResultExt<T> for Result<T> {
             e.to_dialog(siv);
         }
     }
+
+    /// Result Handler for non-fatal errors, showing them as a notification instead of a dialog
+    fn notify(self, siv: &mut Cursive) {
+        if let Err(e) = self {
+            notify(siv, e.to_string());
+        }
+    }
 }
@@ -1,26 +1,63 @@
 use std::{
-    fs::{self, OpenOptions},
-    io::{self, ErrorKind},
+    collections::{BTreeSet, HashSet},
+    ffi::OsStr,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
+    process::{Command, Stdio},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
 };
 
 use cursive::{
-    view::{Nameable, Resizable, Scrollable},
-    views::{Dialog, EditView, LinearLayout, ListView, ScrollView, SelectView, TextView},
-    Cursive, Vec2,
+    event::Key,
+    menu,
+    reexports::log::warn,
+    theme::{BaseColor, Color, Effect},
+    utils::markup::StyledString,
+    view::{IntoBoxedView, Nameable, Position, Resizable, Scrollable, ViewWrapper},
+    views::{
+        BoxedView, Checkbox, Dialog, EditView, LinearLayout, ListView, MenuPopup, OnEventView,
+        ScrollView, SelectView, TextView,
+    },
+    CbSink, Cursive, Vec2,
 };
 use cursive_tree_view::TreeView;
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+use ropey::Rope;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
+    ansi,
     app::{
-        EditorPanel, FileData, State, TreePanel, PKG_AUTHORS, PKG_DESCRIPTION, PKG_LICENSE,
-        PKG_NAME, PKG_REPOSITORY, PKG_VERSION,
+        content_hash, FileData, State, PKG_AUTHORS, PKG_DESCRIPTION, PKG_LICENSE, PKG_NAME,
+        PKG_REPOSITORY, PKG_VERSION,
     },
+    backup,
+    config::{Config, DEFAULT_DATE_FORMAT},
+    diff, encoding,
     error::{Error, Result, ResultExt},
+    glob,
+    hooks::{self, Hook},
+    locations, pinned, positions, recent,
+    search::Search,
+    snippet::format_date,
     ui::{
-        edit_area::{Cursor, EditArea},
-        file_tree::{load_parent, TreeEntry},
-        open_file, path_input, update_title,
+        edit_area::{Cursor, EditArea, MAX_COUNTED_MATCHES},
+        empty_state,
+        file_tree::{self, load_parent, TreeEntry},
+        is_editor_focused, notify, open_file, open_file_with, open_scratch, output, path_input,
+        read_text, show_scratch, update_focus, update_status, update_title, update_tree_title,
+        OpenMode,
     },
 };
 
@@ -29,10 +66,15 @@ pub fn info(siv: &mut Cursive) -> Result<()> {
     if let Some(pos) = siv.screen_mut().find_layer_from_name("info") {
         siv.screen_mut().remove_layer(pos);
     } else {
-        siv.add_layer(
+        add_dialog(
+            siv,
             Dialog::new()
                 .title(format!("{PKG_NAME} - Info"))
                 .padding_lrtb(1, 1, 1, 0)
+                .button("About", |siv| {
+                    siv.pop_layer();
+                    about(siv).handle(siv);
+                })
                 .dismiss_button("Close")
                 .content(
                     ListView::new()
@@ -42,33 +84,87 @@ pub fn info(siv: &mut Cursive) -> Result<()> {
                             TextView::new("the current file has been edited"),
                         )
                         .delimiter()
-                        // pck info
-                        .child("Version", TextView::new(PKG_VERSION))
-                        .child("Repository", TextView::new(PKG_REPOSITORY))
-                        .child("Authors", TextView::new(PKG_AUTHORS))
-                        .child("Description", TextView::new(PKG_DESCRIPTION))
-                        .child("License", TextView::new(PKG_LICENSE))
-                        .delimiter()
                         // shortcuts
                         // global
                         .child("Infos", TextView::new("Esc"))
+                        .child("About", TextView::new("F1"))
                         .child("Debugger", TextView::new("Ctrl + p"))
                         .child("Quitting", TextView::new("Ctrl + q"))
                         .child("Goto an already opened File", TextView::new("Ctrl + g"))
                         .child("Opening a new File/Project", TextView::new("Ctrl + o"))
+                        .child("Opening all matching Files", TextView::new("Ctrl + a"))
                         .child("Creating a new File/Directory", TextView::new("Ctrl + n"))
                         .child("Renaming a File/Directory", TextView::new("Ctrl + r"))
                         .child("Deleting a File/Directory", TextView::new("Ctrl + d"))
+                        .child("Duplicating a File/Directory", TextView::new("F10"))
+                        .child("Marking Tree Entries", TextView::new("Space"))
+                        .child(
+                            "Marking a Range of Entries",
+                            TextView::new("Shift + Up/Down"),
+                        )
+                        .child("Tree Context Menu", TextView::new("Shift + F10"))
+                        .child("Collapsing/Expanding in Tree", TextView::new("Left/Right"))
+                        .child("Parent Directory in Tree", TextView::new("Backspace"))
+                        .child(
+                            "First/Last Entry in Directory",
+                            TextView::new("Ctrl + Up/Down"),
+                        )
+                        .child("Pinning a File/Directory", TextView::new("Shift + F3"))
+                        .child("Listing pinned Files", TextView::new("Shift + F8"))
                         .child("Saving File", TextView::new("Ctrl + s"))
+                        .child("Saving all Files", TextView::new("Ctrl + w"))
+                        .child("Switching Focus Tree/Editor", TextView::new("Ctrl + b"))
+                        .child("Listing Markers like TODO", TextView::new("F3"))
+                        .child("Showing Notifications", TextView::new("F5"))
+                        .child("Following a File like a Log", TextView::new("Shift + F5"))
+                        .child("Jumping to Error Locations", TextView::new("Alt + Down/Up"))
+                        .child("Opening Folder/Terminal", TextView::new("F9"))
+                        .child("Running a Command", TextView::new("Shift + F9"))
+                        .child("Toggle Output Panel", TextView::new("Shift + F2"))
+                        .child("Toggle Highlighting", TextView::new("Shift + F11"))
+                        .child("Copying the Path of a File", TextView::new("F12"))
+                        .child("Copying the Relative Path", TextView::new("Shift + F12"))
+                        .child("Closing the current File", TextView::new("Shift + F6"))
+                        .child("Reopening a closed File", TextView::new("Shift + F7"))
+                        .child("New Scratch Buffer", TextView::new("Shift + F1"))
+                        .child("Showing Word Count", TextView::new("Ctrl + F1"))
+                        .child("Comparing two Files", TextView::new("Ctrl + F2"))
+                        .child("Adding a Note to a Line", TextView::new("Ctrl + F3"))
+                        .child("Listing Notes", TextView::new("Ctrl + F4"))
                         .delimiter()
                         // editor
                         .child("Copying Line", TextView::new("Ctrl + c"))
                         .child("Paste Clipboard", TextView::new("Ctrl + v"))
                         .child("Cut Line", TextView::new("Ctrl + x"))
+                        .child("Join Lines", TextView::new("Ctrl + k"))
+                        .child("Transpose Chars", TextView::new("Ctrl + e"))
+                        .child("Transpose Words", TextView::new("Ctrl + y"))
+                        .child("Uppercase Word", TextView::new("F6"))
+                        .child("Lowercase Word", TextView::new("F7"))
+                        .child("Title-case Word", TextView::new("F8"))
+                        .child(
+                            "Increment/Decrement",
+                            TextView::new("Ctrl + PageUp/PageDown"),
+                        )
                         .child("Move Line", TextView::new("Shift + Up/Down"))
                         .child("Move Cursor to EoL", TextView::new("Shift + Left/Right"))
+                        .child("Move Cursor by Word", TextView::new("Ctrl + Left/Right"))
+                        .child("Add Column Cursors", TextView::new("Ctrl + Up/Down"))
+                        .child("Search in File", TextView::new("Ctrl + f"))
+                        .child("Next/Previous Match", TextView::new("Alt + Right/Left"))
+                        .child("Clear Search Highlights", TextView::new("Esc"))
+                        .child("Align Lines on Delimiter", TextView::new("F4"))
+                        .child("Filter through Command", TextView::new("Shift + F4"))
+                        .child("Remove Duplicate Lines", TextView::new("Ctrl + u"))
+                        .child("Insert Date/File Name", TextView::new("Ctrl + t"))
+                        .child("Scroll Page", TextView::new("PageUp/PageDown"))
+                        .child("Scroll Half Page", TextView::new("Shift + PageUp/PageDown"))
+                        .child("Center Cursor Line", TextView::new("Ctrl + l"))
                         .child("Ident", TextView::new("Tab"))
                         .child("Remove Ident", TextView::new("Shift + Tab"))
+                        .child("Expand Snippet", TextView::new("Tab after a trigger"))
+                        .child("Toggle Auto-Pairs", TextView::new("F2"))
+                        .child("Spelling Suggestions", TextView::new("F11"))
                         .scrollable()
                         .with_name("info"),
                 ),
@@ -78,21 +174,114 @@ pub fn info(siv: &mut Cursive) -> Result<()> {
     Ok(())
 }
 
+/// Shows the name, version and other infos of the package
+///
+/// The repository can be copied, so issues can be reported there.
+pub fn about(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("about") {
+        siv.screen_mut().remove_layer(pos);
+    } else {
+        add_dialog(
+            siv,
+            Dialog::new()
+                .title(format!("{PKG_NAME} - About"))
+                .padding_lrtb(1, 1, 1, 0)
+                .button(
+                    "Copy Repository",
+                    |siv| match crate::clipboard::set_content(PKG_REPOSITORY.to_string()) {
+                        Ok(()) => notify(siv, format!("Copied {PKG_REPOSITORY}")),
+                        Err(e) => notify(siv, format!("Couldn't copy {PKG_REPOSITORY}: {e}")),
+                    },
+                )
+                .dismiss_button("Close")
+                .content(
+                    LinearLayout::vertical()
+                        .child(TextView::new(PKG_DESCRIPTION))
+                        .child(TextView::new(" "))
+                        .child(
+                            ListView::new()
+                                .child("Version", TextView::new(PKG_VERSION))
+                                .child("Authors", TextView::new(PKG_AUTHORS))
+                                .child("License", TextView::new(PKG_LICENSE))
+                                .child("Repository", TextView::new(PKG_REPOSITORY)),
+                        )
+                        .scrollable(),
+                )
+                .with_name("about"),
+        );
+    }
+
+    Ok(())
+}
+
+/// Entries of the welcome screen
+#[derive(Clone)]
+enum WelcomeAction {
+    Open,
+    New,
+    Recent(PathBuf),
+}
+
+/// Shows the welcome screen with quick actions and the recently opened files
+///
+/// It disappears once a file is opened.
+pub fn welcome(siv: &mut Cursive) {
+    let mut select = SelectView::new()
+        .item("Open a File/Project (Ctrl + o)", WelcomeAction::Open)
+        .item("Create a File/Directory (Ctrl + n)", WelcomeAction::New);
+    for file in recent::load() {
+        let label = file.to_string_lossy().to_string();
+        select.add_item(label, WelcomeAction::Recent(file));
+    }
+
+    let select = select.on_submit(|siv, action: &WelcomeAction| {
+        siv.pop_layer();
+        match action {
+            WelcomeAction::Open => open(siv).handle(siv),
+            WelcomeAction::New => new(siv).handle(siv),
+            WelcomeAction::Recent(file) => {
+                let project_path = file.parent().unwrap_or(Path::new("/"));
+                open_paths(siv, project_path, Some(file)).handle(siv);
+            }
+        }
+    });
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title(format!("{PKG_NAME} {PKG_VERSION}"))
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                LinearLayout::vertical()
+                    .child(TextView::new(PKG_DESCRIPTION))
+                    .child(TextView::new(" "))
+                    .child(TextView::new("Start"))
+                    .child(ScrollView::new(select)),
+            )
+            .dismiss_button("Close")
+            .with_name("welcome"),
+    );
+}
+
 /// Quits safely the app
 pub fn quit(siv: &mut Cursive) -> Result<()> {
     let state = siv
         .with_user_data(|state: &mut State| state.clone())
         .unwrap();
 
-    let edited_files = state
+    let edited_scratches = state.edited_scratches();
+    let mut edited_files = state
         .files_edited
         .into_iter() // Note the change to into_iter to consume the map
         .filter(|(_, edited)| *edited)
         .map(|(path, _)| path)
         .collect::<Vec<PathBuf>>(); // Now owns PathBuf instead of &PathBuf
+    edited_files.extend(edited_scratches.into_iter().map(PathBuf::from));
 
     if edited_files.is_empty() {
         siv.quit();
+    } else if !state.config.confirmations.quit_unsaved.asks(true) {
+        save_and_quit(siv);
     } else {
         let mut layout =
             LinearLayout::vertical().child(TextView::new("You have unsaved changes in: "));
@@ -100,30 +289,47 @@ pub fn quit(siv: &mut Cursive) -> Result<()> {
             layout.add_child(TextView::new(i.to_string_lossy()));
         }
 
-        // Clone edited_files for use in the Save closure
-        let edited_files_for_save = edited_files.clone();
-        siv.add_layer(
+        add_dialog(
+            siv,
             Dialog::new()
                 .content(layout)
-                .button("Save", move |siv| {
-                    for i in &edited_files_for_save {
-                        let binding = &FileData::default();
-                        let content = &state.files.get(i).unwrap_or(binding).str;
-                        save(siv, Some((i, content))).handle(siv);
-                    }
-                    siv.quit();
-                })
+                .button("Save", save_and_quit)
                 .button("Dismiss", |siv| {
                     siv.pop_layer();
                     siv.quit();
                 })
-                .dismiss_button("Cancel Closing"),
+                .dismiss_button("Cancel Closing")
+                .with_name("quit"),
         );
     }
 
     Ok(())
 }
 
+/// Saves all files and quits
+///
+/// Files which couldn't be saved keep the app open, their errors are shown. An edited scratch
+/// buffer is shown together with the prompt for its path instead of quitting.
+fn save_and_quit(siv: &mut Cursive) {
+    save_all(siv).handle(siv);
+    let (saved, edited_scratch) = siv
+        .with_user_data(|state: &mut State| {
+            let edited_scratch = state.edited_scratches().into_iter().next();
+            (state.files_edited.is_empty(), edited_scratch)
+        })
+        .unwrap_or_default();
+    if let Some(name) = edited_scratch {
+        // without asking first there is no dialog to close
+        if let Some(pos) = siv.screen_mut().find_layer_from_name("quit") {
+            siv.screen_mut().remove_layer(pos);
+        }
+        show_scratch(siv, &name);
+        save_as(siv).handle(siv);
+    } else if saved {
+        siv.quit();
+    }
+}
+
 /// Goto an opened file of the current project or more precise a file in
 /// the state.files hashmap which is inside the current project directory
 ///
@@ -144,7 +350,7 @@ pub fn goto(siv: &mut Cursive) -> Result<()> {
             .collect::<Vec<_>>();
         filtered.sort_by(|a, b| b.0.cmp(a.0));
 
-        let opened = filtered
+        let mut opened = filtered
             .iter()
             .filter(|p| p.0.starts_with(&state.project_path))
             .map(|f| {
@@ -154,8 +360,16 @@ pub fn goto(siv: &mut Cursive) -> Result<()> {
                     .to_string()
             })
             .collect::<Vec<_>>();
+        // scratch buffers are listed by their names, they have no path
+        let scratches = state
+            .scratches
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        opened.splice(0..0, scratches.iter().cloned());
 
-        siv.add_layer(
+        add_dialog(
+            siv,
             Dialog::new()
                 .title("Goto")
                 .padding_lrtb(1, 1, 1, 0)
@@ -163,6 +377,22 @@ pub fn goto(siv: &mut Cursive) -> Result<()> {
                     SelectView::new()
                         .with_all_str(&opened)
                         .on_submit(move |siv, item: &String| {
+                            // the shown buffer keeps its cursor and column cursors
+                            let shown = siv
+                                .with_user_data(|state: &mut State| match &state.current_file {
+                                    Some(current_file) => current_file == Path::new(item),
+                                    None => state.current_scratch.as_ref() == Some(item),
+                                })
+                                .unwrap_or_default();
+                            if shown {
+                                siv.pop_layer();
+                                return;
+                            }
+                            if scratches.contains(item) {
+                                show_scratch(siv, item);
+                                siv.pop_layer();
+                                return;
+                            }
                             let goto_file = &PathBuf::from(item);
                             if let Err(e) = open_file(siv, goto_file) {
                                 Into::<Error>::into(e).to_dialog(siv);
@@ -173,15 +403,15 @@ pub fn goto(siv: &mut Cursive) -> Result<()> {
                         .selected(
                             opened
                                 .iter()
-                                .position(|p| {
-                                    p == &state
-                                        .clone()
-                                        .current_file
-                                        .unwrap_or_default()
-                                        .canonicalize()
-                                        .unwrap_or_default()
-                                        .to_string_lossy()
-                                        .to_string()
+                                .position(|p| match &state.current_file {
+                                    Some(current_file) => {
+                                        p == &current_file
+                                            .canonicalize()
+                                            .unwrap_or_default()
+                                            .to_string_lossy()
+                                            .to_string()
+                                    }
+                                    None => Some(p) == state.current_scratch.as_ref(),
                                 })
                                 .unwrap_or_default(),
                         ),
@@ -209,21 +439,23 @@ pub fn open(siv: &mut Cursive) -> Result<()> {
         let state = siv
             .with_user_data(|state: &mut State| state.clone())
             .unwrap();
-        siv.add_layer(
+        add_dialog(
+            siv,
             Dialog::new()
                 .title("Open")
                 .padding_lrtb(1, 1, 1, 0)
                 .content(path_input::new(
-                    &state.project_path,
+                    state.prompt_dir(),
                     "open_new_path".to_string(),
                     true,
                 )?)
                 .button("Open", move |siv| {
                     let inc_path = siv
                         .call_on_name("open_new_path_edit", |view: &mut EditView| {
-                            PathBuf::from(view.get_content().to_string())
+                            path_input::expand_home(&view.get_content())
                         })
                         .unwrap();
+                    let inc_path = resolve_path(&state, &inc_path);
 
                     let mut current_file = None;
                     let project_path = if inc_path.is_file() {
@@ -240,6 +472,7 @@ pub fn open(siv: &mut Cursive) -> Result<()> {
                         Into::<Error>::into(e).to_dialog(siv);
                         return;
                     }
+                    siv.with_user_data(|state: &mut State| state.set_last_dir(&project_path));
 
                     siv.pop_layer();
                 })
@@ -252,6 +485,18 @@ pub fn open(siv: &mut Cursive) -> Result<()> {
     }
 }
 
+/// Resolves a relative path against the directory of the current file
+///
+/// Without a current file the project directory is used, absolute paths are kept as they are.
+fn resolve_path(state: &State, path: &Path) -> PathBuf {
+    let base = state
+        .current_file
+        .as_ref()
+        .and_then(|file| file.parent())
+        .unwrap_or(&state.project_path);
+    base.join(path)
+}
+
 /// Updates the ui accordingly to the paths
 pub fn open_paths(
     siv: &mut Cursive,
@@ -262,36 +507,21 @@ pub fn open_paths(
     if let Some(current_file) = current_file {
         open_file(siv, current_file).handle(siv);
     } else if project_path.exists() {
-        siv.call_on_name("editor", |edit_area: &mut EditArea| {
-            edit_area.set_content(' ');
-            edit_area.set_cursor(Cursor::default());
-            edit_area.set_scroll(Vec2::zero());
-            edit_area.disable();
-        })
-        .unwrap();
-        siv.call_on_name("editor_title", |view: &mut EditorPanel| view.set_title(""))
-            .unwrap();
+        clear_editor(siv);
     }
     if project_path.exists() {
-        siv.call_on_name("tree_title", |view: &mut TreePanel| {
-            view.get_inner_mut().set_title(
-                project_path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy(),
-            );
-        })
-        .unwrap();
+        update_tree_title(siv, project_path);
 
         let mut state = siv
             .with_user_data(|state: &mut State| state.clone())
             .unwrap_or_default();
 
         siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
-            load_parent(tree, project_path);
+            load_parent(tree, project_path, state.config.follow_symlinks);
         });
 
         siv.set_user_data(state.open_new_project(project_path, current_file));
+        file_tree::update_watched(siv);
     } else {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
@@ -303,280 +533,4131 @@ pub fn open_paths(
     Ok(())
 }
 
-/// Creates a new file
-pub fn new(siv: &mut Cursive) -> Result<()> {
-    if let Some(pos) = siv.screen_mut().find_layer_from_name("new") {
-        siv.screen_mut().remove_layer(pos);
-    } else {
-        let state = siv
-            .with_user_data(|state: &mut State| state.clone())
-            .unwrap();
-        siv.add_layer(
-            Dialog::new()
-                .title("Create As")
-                .padding_lrtb(1, 1, 1, 0)
-                .content(path_input::new(
-                    &state.project_path,
-                    "new_path".to_string(),
-                    false,
-                )?)
-                .button("A File", {
-                    move |siv: &mut Cursive| {
-                        let state = siv
-                            .with_user_data(|state: &mut State| state.clone())
-                            .unwrap();
-                        let new_path = siv
-                            .call_on_name("new_path_edit", |view: &mut EditView| {
-                                PathBuf::from(view.get_content().to_string())
-                            })
-                            .unwrap();
-
-                        if let Err(e) = OpenOptions::new()
-                            .write(true)
-                            .create_new(true)
-                            .open(new_path)
-                        {
-                            Into::<Error>::into(e).to_dialog(siv);
-                            return;
-                        }
+/// Disables the editor, showing the bindings for opening a file instead
+///
+/// The current scratch buffer is shown instead, if there is one.
+fn clear_editor(siv: &mut Cursive) {
+    let scratch = siv
+        .with_user_data(|state: &mut State| state.current_scratch.clone())
+        .flatten();
+    if let Some(name) = scratch {
+        return show_scratch(siv, &name);
+    }
+    siv.call_on_name("editor", |edit_area: &mut EditArea| {
+        edit_area.set_content(' ');
+        edit_area.set_cursor(Cursor::default());
+        edit_area.set_scroll(Vec2::zero());
+        edit_area.set_placeholder(empty_state());
+        edit_area.disable();
+    })
+    .unwrap();
+    update_title(siv, false, Path::new(""));
+    update_status(siv, None);
+}
 
-                        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
-                            load_parent(tree, &state.project_path);
-                        });
+/// Closes the buffer of the current file, asking first if it has unsaved changes
+///
+/// The closed files are remembered, so they can be reopened via `Shift` + `F7`. Without a
+/// current file the shown scratch buffer is closed, it can't be reopened.
+pub fn close(siv: &mut Cursive) -> Result<()> {
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+    let Some(path) = state.current_file.clone() else {
+        if let Some(name) = state.current_scratch {
+            close_scratch(siv, name);
+        }
+        return Ok(());
+    };
+    if !state.is_file_edited(&path) || !state.config.confirmations.discard_unsaved.asks(false) {
+        close_file(siv, &path);
+        return Ok(());
+    }
 
-                        siv.pop_layer();
-                    }
-                })
-                .button("A Directory", {
-                    move |siv: &mut Cursive| {
-                        let state = siv
-                            .with_user_data(|state: &mut State| state.clone())
-                            .unwrap();
-                        let new_path = siv
-                            .call_on_name("new_path_edit", |view: &mut EditView| {
-                                PathBuf::from(view.get_content().to_string())
-                            })
-                            .unwrap();
+    let save_path = path.clone();
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "{} has unsaved changes, do you want to save them before closing it?",
+            path.to_string_lossy()
+        ))
+        .title("Close")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Save", move |siv| {
+            siv.pop_layer();
+            save_and_close(siv, &save_path);
+        })
+        .button("Discard", move |siv| {
+            siv.pop_layer();
+            close_file(siv, &path);
+        })
+        .dismiss_button("Cancel"),
+    );
+    Ok(())
+}
 
-                        if let Err(e) = fs::create_dir_all(new_path) {
-                            Into::<Error>::into(e).to_dialog(siv);
-                            return;
-                        }
+/// Closes a scratch buffer, asking first to save it if it was edited
+fn close_scratch(siv: &mut Cursive, name: String) {
+    let ask = siv
+        .with_user_data(|state: &mut State| {
+            state.is_scratch_edited(&name) && state.config.confirmations.discard_unsaved.asks(false)
+        })
+        .unwrap_or_default();
+    if !ask {
+        return remove_scratch(siv, &name);
+    }
 
-                        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
-                            load_parent(tree, &state.project_path);
-                        });
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "{name} has unsaved changes, do you want to save them before closing it?"
+        ))
+        .title("Close")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Save", |siv| {
+            siv.pop_layer();
+            save_as(siv).handle(siv);
+        })
+        .button("Discard", move |siv| {
+            siv.pop_layer();
+            remove_scratch(siv, &name);
+        })
+        .dismiss_button("Cancel"),
+    );
+}
 
-                        siv.pop_layer();
-                    }
-                })
-                .dismiss_button("Cancel")
-                .full_width()
-                .with_name("new"),
-        );
+/// Removes a scratch buffer without saving it, another one is shown if there is one
+fn remove_scratch(siv: &mut Cursive, name: &str) {
+    siv.with_user_data(|state: &mut State| state.remove_scratch(name));
+    clear_editor(siv);
+    notify(siv, format!("Closed {name}"));
+}
+
+/// Creates a new empty scratch buffer and focuses it, it isn't backed by a file until it's
+/// saved
+///
+/// It's named by a number, like `scratch 2`, to tell it apart from the other scratch buffers.
+pub fn new_scratch(siv: &mut Cursive) -> Result<()> {
+    let name = siv
+        .with_user_data(|state: &mut State| state.new_scratch_name())
+        .unwrap();
+    open_scratch(siv, name.clone(), String::new());
+    if let Ok(result) = siv.focus_name("editor") {
+        result.process(siv);
     }
+    update_focus(siv);
+    notify(siv, format!("Created {name}, save it via Ctrl + s"));
     Ok(())
 }
 
-/// Rename(+move) a file/directory
-pub fn rename(siv: &mut Cursive) -> Result<()> {
-    if let Some(pos) = siv.screen_mut().find_layer_from_name("rename") {
+/// Saves the current file and closes its buffer, the buffer is kept if it wasn't saved
+///
+/// Saving a file which was changed on disk asks first, so it's only closed by choosing again.
+fn save_and_close(siv: &mut Cursive, path: &PathBuf) {
+    if let Err(e) = save(siv, None) {
+        return e.to_dialog(siv);
+    }
+    let saved = siv
+        .with_user_data(|state: &mut State| !state.is_file_edited(path))
+        .unwrap_or_default();
+    if saved {
+        close_file(siv, path);
+    }
+}
+
+/// Closes the buffer of a file without saving it, remembering its position
+fn close_file(siv: &mut Cursive, path: &PathBuf) {
+    let position = siv
+        .with_user_data(|state: &mut State| {
+            let position = state.get_file(path).map(FileData::position);
+            state.close_file(path);
+            position.filter(|_| state.config.remember_positions)
+        })
+        .flatten();
+    if let Some(position) = position {
+        if let Err(e) = positions::set([(path.as_path(), position)]) {
+            warn!("The cursor position couldn't be saved: {e}");
+        }
+    }
+    clear_editor(siv);
+    notify(
+        siv,
+        format!(
+            "Closed {}, reopen it via Shift + F7",
+            path.to_string_lossy()
+        ),
+    );
+}
+
+/// Reopens the file closed last at the position it was closed at
+pub fn reopen(siv: &mut Cursive) -> Result<()> {
+    let closed = siv
+        .with_user_data(|state: &mut State| state.closed_files.pop())
+        .unwrap();
+    let Some((path, position)) = closed else {
+        notify(siv, "No closed files to reopen");
+        return Ok(());
+    };
+    open_file(siv, &path)?;
+    let callback = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            let callback = edit_area.set_cursor_byte_offset(position.byte_offset);
+            edit_area.set_scroll(position.scroll.into());
+            callback
+        })
+        .unwrap();
+    callback(siv);
+    Ok(())
+}
+
+/// Creates a new file or directory
+///
+/// Relative paths are resolved against the directory of the selected tree entry, or the
+/// project directory. Missing parent directories are created, the new file is opened and
+/// revealed inside of the tree.
+pub fn new(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("new") {
         siv.screen_mut().remove_layer(pos);
     } else {
         let state = siv
             .with_user_data(|state: &mut State| state.clone())
             .unwrap();
-        let layout = LinearLayout::vertical()
-            .child(TextView::new(
-                "Note the file will be autosaved before it'll be moved/renamed!",
-            ))
-            .child(TextView::new(" "))
-            .child(
-                LinearLayout::horizontal()
-                    .child(
-                        LinearLayout::vertical()
-                            .child(TextView::new("From"))
-                            .child(path_input::new(
-                                &state.project_path,
-                                "from_rename_path".to_string(),
-                                true,
-                            )?)
-                            .full_width(),
-                    )
-                    .child(TextView::new(" "))
-                    .child(
-                        LinearLayout::vertical()
-                            .child(TextView::new("To"))
-                            .child(path_input::new(
-                                &state.project_path,
-                                "to_rename_path".to_string(),
-                                false,
-                            )?)
-                            .full_width(),
-                    ),
-            );
-        siv.add_layer(
+        add_dialog(
+            siv,
             Dialog::new()
-                .title("Rename")
+                .title("Create As")
                 .padding_lrtb(1, 1, 1, 0)
-                .content(layout)
-                .button("Confirm", |siv| {
-                    let mut state = siv
-                        .with_user_data(|state: &mut State| state.clone())
-                        .unwrap();
-                    let from = siv
-                        .call_on_name("from_rename_path_edit", |view: &mut EditView| {
-                            PathBuf::from(view.get_content().to_string())
-                        })
-                        .unwrap();
-
-                    let to = siv
-                        .call_on_name("to_rename_path_edit", |view: &mut EditView| {
-                            PathBuf::from(view.get_content().to_string())
-                        })
-                        .unwrap();
-
-                    if !to.exists() {
-                        if let Err(e) = fs::rename(&from, &to) {
-                            Into::<Error>::into(e).to_dialog(siv);
-                            return;
-                        }
+                .content(path_input::new(
+                    state.prompt_dir(),
+                    "new_path".to_string(),
+                    false,
+                )?)
+                .button("A File", |siv| {
+                    let new_path = new_path(siv);
+                    let confirm = overwrite_asks(siv, &new_path);
+                    if new_path.is_dir() {
+                        Error::Arguments(format!(
+                            "{} is an existing directory, choose another name for the file",
+                            new_path.to_string_lossy()
+                        ))
+                        .to_dialog(siv);
+                    } else if new_path.is_file() && confirm {
+                        confirm_overwrite(siv, new_path.clone(), move |siv| {
+                            create_file(siv, &new_path, true)
+                        });
                     } else {
-                        Into::<Error>::into(io::Error::new(
-                            io::ErrorKind::AlreadyExists,
-                            "Destination already exists",
+                        create_file(siv, &new_path, new_path.is_file()).handle(siv);
+                    }
+                })
+                .button("A Directory", |siv| {
+                    let new_path = new_path(siv);
+                    if new_path.exists() {
+                        let kind = if new_path.is_dir() {
+                            "directory"
+                        } else {
+                            "file"
+                        };
+                        Error::Arguments(format!(
+                            "{} is an existing {kind}, choose another name for the directory",
+                            new_path.to_string_lossy()
                         ))
                         .to_dialog(siv);
                         return;
                     }
-
-                    state.update_paths_after_rename(&from, &to);
-                    siv.set_user_data(state.clone());
-
-                    if let Err(e) =
-                        open_paths(siv, &state.project_path, state.current_file.as_ref())
-                    {
+                    if let Err(e) = fs::create_dir_all(&new_path) {
                         Into::<Error>::into(e).to_dialog(siv);
                         return;
                     }
+                    siv.with_user_data(|state: &mut State| state.set_last_dir(&new_path));
+                    reveal_in_tree(siv, &new_path);
 
                     siv.pop_layer();
                 })
                 .dismiss_button("Cancel")
                 .full_width()
-                .with_name("rename"),
+                .with_name("new"),
         );
     }
     Ok(())
 }
 
-/// Delete a file/directory(recursively)
-pub fn delete(siv: &mut Cursive) -> Result<()> {
-    if let Some(pos) = siv.screen_mut().find_layer_from_name("delete") {
-        siv.screen_mut().remove_layer(pos);
+/// Returns the path entered inside of the new prompt, resolved against the selected directory
+fn new_path(siv: &mut Cursive) -> PathBuf {
+    let new_path = siv
+        .call_on_name("new_path_edit", |view: &mut EditView| {
+            path_input::expand_home(&view.get_content())
+        })
+        .unwrap();
+    selected_dir(siv).join(new_path)
+}
+
+/// Asks whether an existing file should be replaced, like by an empty one or a renamed file
+///
+/// Unsaved changes of the file are discarded too, which is pointed out.
+fn confirm_overwrite(
+    siv: &mut Cursive,
+    path: PathBuf,
+    overwrite: impl Fn(&mut Cursive) -> Result<()> + 'static,
+) {
+    let edited = siv
+        .with_user_data(|state: &mut State| {
+            let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            state.is_file_edited(&path)
+        })
+        .unwrap_or_default();
+    let unsaved = if edited {
+        " and has unsaved changes"
     } else {
-        let state = siv
-            .with_user_data(|state: &mut State| state.clone())
-            .unwrap();
-        siv.add_layer(
-            Dialog::new()
-                .title("Delete")
-                .padding_lrtb(1, 1, 1, 0)
-                .content(path_input::new(
-                    &state.project_path,
-                    "delete_path".to_string(),
-                    true,
-                )?)
-                .button("Confirm", |siv| {
-                    let mut state = siv
-                        .with_user_data(|state: &mut State| state.clone())
-                        .unwrap();
-                    let delete_path = siv
-                        .call_on_name("delete_path_edit", |view: &mut EditView| {
-                            PathBuf::from(view.get_content().to_string())
-                        })
-                        .unwrap();
+        ""
+    };
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "{} already exists{unsaved}, do you want to overwrite it?",
+            path.to_string_lossy()
+        ))
+        .title("Overwrite")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Overwrite", move |siv| {
+            siv.pop_layer();
+            overwrite(siv).handle(siv);
+        })
+        .dismiss_button("Cancel"),
+    );
+}
 
-                    if delete_path.is_dir() {
-                        if let Err(e) = fs::remove_dir_all(&delete_path) {
-                            Into::<Error>::into(e).to_dialog(siv);
-                            return;
-                        }
-                    } else if let Err(e) = fs::remove_file(&delete_path) {
-                        Into::<Error>::into(e).to_dialog(siv);
-                        return;
-                    }
+/// Creates a file including its missing parent directories and opens it
+///
+/// An existing file is only emptied if `overwrite` is set, its buffer is discarded.
+fn create_file(siv: &mut Cursive, path: &Path, overwrite: bool) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(!overwrite)
+        .truncate(overwrite)
+        .open(path)?;
 
-                    state.remove_file(&delete_path);
+    let path = path.canonicalize()?;
+    siv.with_user_data(|state: &mut State| {
+        state.remove_file(&path);
+        state.set_last_dir(&path);
+    });
+    reveal_in_tree(siv, &path);
 
-                    siv.set_user_data(state.clone());
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("new") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    open_file(siv, &path)
+}
 
-                    let current = if &delete_path
-                        != state.current_file.as_ref().unwrap_or(&PathBuf::default())
-                    {
-                        state.current_file
-                    } else {
-                        None
-                    };
+/// Reloads the tree and selects the path, expanding the directories leading to it
+fn reveal_in_tree(siv: &mut Cursive, path: &Path) {
+    let (project_path, follow_symlinks) = siv
+        .with_user_data(|state: &mut State| {
+            (state.project_path.clone(), state.config.follow_symlinks)
+        })
+        .unwrap_or_default();
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+        load_parent(tree, &project_path, follow_symlinks);
+        file_tree::reveal(tree, &path, follow_symlinks);
+    });
+    file_tree::update_watched(siv);
+}
 
-                    if let Err(e) = open_paths(siv, &state.project_path, current.as_ref()) {
-                        Into::<Error>::into(e).to_dialog(siv);
-                        return;
-                    }
+/// Returns the directory of the selected tree entry, the project directory without one
+fn selected_dir(siv: &mut Cursive) -> PathBuf {
+    let selected = siv
+        .call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            let item = tree.borrow_item(tree.row()?)?;
+            item.dir
+                .clone()
+                .or_else(|| item.path.parent().map(Path::to_path_buf))
+        })
+        .flatten();
+    match selected {
+        Some(dir) => dir,
+        None => siv
+            .with_user_data(|state: &mut State| state.project_path.clone())
+            .unwrap_or_default(),
+    }
+}
 
-                    if state.project_path == delete_path {
-                        siv.pop_layer();
-                        Into::<Error>::into(io::Error::new(
-                            ErrorKind::NotFound,
-                            "Couldn't find project. It got deleted",
-                        ))
-                        .to_dialog(siv);
-                        return;
-                    }
+/// Rename(+move) a file/directory
+///
+/// With marked tree entries, they're all moved into a directory instead.
+pub fn rename(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("rename") {
+        siv.screen_mut().remove_layer(pos);
+    } else if let Some(marked) = marked_entries(siv) {
+        move_marked(siv, marked)?;
+    } else {
+        let dir = siv
+            .with_user_data(|state: &mut State| state.prompt_dir().to_path_buf())
+            .unwrap();
+        rename_dialog(siv, &dir)?;
+    }
+    Ok(())
+}
 
-                    siv.pop_layer();
-                })
-                .dismiss_button("Cancel")
-                .full_width()
-                .with_name("delete"),
+/// Asks where the file or directory should be moved to, both paths starting at the path
+fn rename_dialog(siv: &mut Cursive, path: &Path) -> Result<()> {
+    let layout = LinearLayout::vertical()
+        .child(TextView::new(
+            "Note the file will be autosaved before it'll be moved/renamed!",
+        ))
+        .child(TextView::new(" "))
+        .child(
+            LinearLayout::horizontal()
+                .child(
+                    LinearLayout::vertical()
+                        .child(TextView::new("From"))
+                        .child(path_input::new(path, "from_rename_path".to_string(), true)?)
+                        .full_width(),
+                )
+                .child(TextView::new(" "))
+                .child(
+                    LinearLayout::vertical()
+                        .child(TextView::new("To"))
+                        .child(path_input::new(path, "to_rename_path".to_string(), false)?)
+                        .full_width(),
+                ),
         );
-    }
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Rename")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(layout)
+            .button("Confirm", |siv| {
+                let from = siv
+                    .call_on_name("from_rename_path_edit", |view: &mut EditView| {
+                        path_input::expand_home(&view.get_content())
+                    })
+                    .unwrap();
+
+                let to = siv
+                    .call_on_name("to_rename_path_edit", |view: &mut EditView| {
+                        path_input::expand_home(&view.get_content())
+                    })
+                    .unwrap();
+
+                if !to.exists() {
+                    return rename_path(siv, &from, &to).handle(siv);
+                }
+                let same = from.canonicalize().ok() == to.canonicalize().ok();
+                if same {
+                    siv.pop_layer();
+                } else if to.is_dir() || from.is_dir() {
+                    let kind = if to.is_dir() { "directory" } else { "file" };
+                    Error::Arguments(format!(
+                        "{} is an existing {kind}, choose another name",
+                        to.to_string_lossy()
+                    ))
+                    .to_dialog(siv);
+                } else if overwrite_asks(siv, &to) {
+                    confirm_overwrite(siv, to.clone(), move |siv| rename_path(siv, &from, &to));
+                } else {
+                    rename_path(siv, &from, &to).handle(siv);
+                }
+            })
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("rename"),
+    );
     Ok(())
 }
 
-/// Save current progress + Handling Title
-pub fn save(siv: &mut Cursive, other: Option<(&PathBuf, &String)>) -> Result<()> {
+/// Returns `true` if replacing the existing file asks first, like if it has unsaved changes
+fn overwrite_asks(siv: &mut Cursive, to: &Path) -> bool {
+    siv.with_user_data(|state: &mut State| {
+        let confirmations = &state.config.confirmations;
+        let to = to.canonicalize().unwrap_or_else(|_| to.to_path_buf());
+        confirmations.overwrite.asks(false)
+            || (state.is_file_edited(&to) && confirmations.discard_unsaved.asks(false))
+    })
+    .unwrap_or(true)
+}
+
+/// Moves the file or directory, the buffers of the moved files follow them
+///
+/// A file which is replaced loses its buffer together with its unsaved changes.
+fn rename_path(siv: &mut Cursive, from: &Path, to: &Path) -> Result<()> {
     let mut state = siv
         .with_user_data(|state: &mut State| state.clone())
         .unwrap();
+    let replaced = to.canonicalize().ok().filter(|to| to.is_file());
+    fs::rename(from, to)?;
 
-    let binding = FileData::default();
-    let content = &state.get_current_file().unwrap_or(&binding).str;
+    if let Some(replaced) = replaced {
+        state.remove_file(&replaced);
+    }
+    state.update_paths_after_rename(from, to);
+    state.set_last_dir(to);
+    siv.set_user_data(state.clone());
+    open_paths(siv, &state.project_path, state.current_file.as_ref())?;
 
-    let current_file = state
-        .current_file
-        .as_ref()
-        .map(|current_file| (current_file, content));
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("rename") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    Ok(())
+}
+
+/// Duplicates the selected file or directory(recursively) inside of the same directory
+///
+/// The copy is done in the background, so large directories don't block the ui. Afterwards
+/// the copy is selected in the tree and a copied file can be opened.
+pub fn duplicate(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("duplicate") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let Some(from) = siv
+        .call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            Some(tree.borrow_item(tree.row()?)?.path.clone())
+        })
+        .flatten()
+    else {
+        return Err(Error::FileOpen(
+            "No file or directory is selected".to_string(),
+        ));
+    };
+    let is_file = from.is_file();
+
+    let mut layout = LinearLayout::vertical()
+        .child(TextView::new(format!(
+            "Duplicating {}",
+            from.to_string_lossy()
+        )))
+        .child(TextView::new(" "))
+        .child(
+            EditView::new()
+                .content(copy_name(&from))
+                .with_name("duplicate_name"),
+        );
+    if is_file {
+        layout.add_child(
+            LinearLayout::horizontal()
+                .child(Checkbox::new().checked().with_name("duplicate_open"))
+                .child(TextView::new(" Open the copy")),
+        );
+    }
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Duplicate")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(layout)
+            .button("Confirm", move |siv| {
+                let name = siv
+                    .call_on_name("duplicate_name", |view: &mut EditView| {
+                        view.get_content().to_string()
+                    })
+                    .unwrap();
+                let open = siv
+                    .call_on_name("duplicate_open", |view: &mut Checkbox| view.is_checked())
+                    .unwrap_or_default();
+
+                // only a name is allowed, so the copy can't end up inside of itself
+                if Path::new(&name).file_name() != Some(OsStr::new(&name)) {
+                    Error::Arguments("The name can't contain a path".to_string()).to_dialog(siv);
+                    return;
+                }
+                let to = from.with_file_name(&name);
+                if to.exists() {
+                    Into::<Error>::into(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        "Destination already exists",
+                    ))
+                    .to_dialog(siv);
+                    return;
+                }
+
+                siv.pop_layer();
+                notify(siv, format!("Duplicating {name}..."));
+                let from = from.clone();
+                let cb_sink = siv.cb_sink().clone();
+                thread::spawn(move || {
+                    let result = copy_recursively(&from, &to);
+                    // the app may have been quit in the meantime
+                    let _ = cb_sink.send(Box::new(move |siv| match result {
+                        Ok(()) => {
+                            reveal_in_tree(siv, &to);
+                            notify(siv, format!("Duplicated {name}"));
+                            if open {
+                                open_file(siv, &to).handle(siv);
+                            }
+                        }
+                        Err(e) => Into::<Error>::into(e).to_dialog(siv),
+                    }));
+                });
+            })
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("duplicate"),
+    );
+    Ok(())
+}
+
+/// Returns a free name for a copy of the path inside of the same directory
+///
+/// The name gets a ` copy` suffix in front of the extension, followed by a number if needed.
+fn copy_name(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|i| match i {
+            1 => format!("{stem} copy{extension}"),
+            i => format!("{stem} copy {i}{extension}"),
+        })
+        .find(|name| !path.with_file_name(name).exists())
+        .unwrap()
+}
+
+/// Copies a file or recursively a directory with all of its content, symlinks stay links
+fn copy_recursively(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_symlink() {
+        copy_symlink(from, to)
+    } else if from.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Creates a symlink with the same target, instead of copying the content it points to
+///
+/// Copying the content could never end for links pointing to a parent directory.
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let target = fs::read_link(from)?;
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(target, to);
+    #[cfg(windows)]
+    return if from.is_dir() {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    };
+}
+
+/// Delete a file/directory(recursively)
+///
+/// With marked tree entries, they're all deleted after a single confirmation instead. If the
+/// configured confirmations don't ask for them, the marked entries or the selected one of the
+/// focused tree are deleted right away.
+pub fn delete(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("delete") {
+        siv.screen_mut().remove_layer(pos);
+    } else if let Some(marked) = marked_entries(siv) {
+        if delete_asks(siv, &marked) {
+            delete_marked(siv, marked);
+        } else {
+            delete_paths(siv, &marked);
+        }
+    } else if let Some(selected) = selected_entry(siv).filter(|path| !delete_asks(siv, &[path])) {
+        delete_paths(siv, &[selected]);
+    } else {
+        let dir = siv
+            .with_user_data(|state: &mut State| state.prompt_dir().to_path_buf())
+            .unwrap();
+        delete_dialog(siv, &dir)?;
+    }
+    Ok(())
+}
+
+/// Deletes the file or directory, asking first if the configured confirmations do
+fn delete_entry(siv: &mut Cursive, path: &Path) -> Result<()> {
+    if delete_asks(siv, &[path]) {
+        delete_dialog(siv, path)
+    } else {
+        delete_paths(siv, &[path.to_path_buf()]);
+        Ok(())
+    }
+}
+
+/// Asks for the file or directory to delete, starting at the path
+fn delete_dialog(siv: &mut Cursive, path: &Path) -> Result<()> {
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Delete")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(path_input::new(path, "delete_path".to_string(), true)?)
+            .button("Confirm", |siv| {
+                let mut state = siv
+                    .with_user_data(|state: &mut State| state.clone())
+                    .unwrap();
+                let delete_path = siv
+                    .call_on_name("delete_path_edit", |view: &mut EditView| {
+                        path_input::expand_home(&view.get_content())
+                    })
+                    .unwrap();
+
+                if delete_path.is_dir() {
+                    if let Err(e) = fs::remove_dir_all(&delete_path) {
+                        Into::<Error>::into(e).to_dialog(siv);
+                        return;
+                    }
+                } else if let Err(e) = fs::remove_file(&delete_path) {
+                    Into::<Error>::into(e).to_dialog(siv);
+                    return;
+                }
+
+                state.remove_file(&delete_path);
+                state.set_last_dir(&delete_path);
+
+                siv.set_user_data(state.clone());
+
+                let current = state
+                    .current_file
+                    .filter(|current_file| current_file != &delete_path);
+
+                if let Err(e) = open_paths(siv, &state.project_path, current.as_ref()) {
+                    Into::<Error>::into(e).to_dialog(siv);
+                    return;
+                }
+
+                if state.project_path == delete_path {
+                    siv.pop_layer();
+                    Into::<Error>::into(io::Error::new(
+                        ErrorKind::NotFound,
+                        "Couldn't find project. It got deleted",
+                    ))
+                    .to_dialog(siv);
+                    return;
+                }
+
+                siv.pop_layer();
+            })
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("delete"),
+    );
+    Ok(())
+}
+
+/// Returns the path of the selected entry while the tree is active
+fn selected_entry(siv: &mut Cursive) -> Option<PathBuf> {
+    if !is_tree_active(siv) {
+        return None;
+    }
+    siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+        let item = tree.borrow_item(tree.row()?)?;
+        item.error.is_none().then(|| item.path.clone())
+    })
+    .flatten()
+}
+
+/// Shows the actions for the selected entry of the tree inside of a menu
+///
+/// The menu is opened at the position of a right-click, or else in the center. Files and
+/// directories offer different actions, all of them are the ones of their bindings.
+pub fn tree_menu(siv: &mut Cursive, position: Option<Vec2>) {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("tree_menu") {
+        siv.screen_mut().remove_layer(pos);
+        return;
+    }
+    let Some(path) = selected_entry(siv) else {
+        return;
+    };
+    let marked = siv
+        .call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            Some(tree.borrow_item(tree.row()?)?.marked)
+        })
+        .flatten()
+        .unwrap_or_default();
+    let is_pinned = siv
+        .with_user_data(|state: &mut State| pinned::load(&state.project_path).contains(&path))
+        .unwrap_or_default();
+
+    let mut menu = menu::Tree::new();
+    if path.is_dir() {
+        menu.add_leaf("New File/Directory", |siv| new(siv).handle(siv));
+    } else {
+        let open_path = path.clone();
+        menu.add_leaf("Open", move |siv| open_file(siv, &open_path).handle(siv));
+    }
+    let (rename_path, delete_path) = (path.clone(), path);
+    menu.add_leaf("Rename", move |siv| {
+        rename_dialog(siv, &rename_path).handle(siv);
+    });
+    menu.add_leaf("Delete", move |siv| {
+        delete_entry(siv, &delete_path).handle(siv);
+    });
+    menu.add_leaf("Duplicate", |siv| duplicate(siv).handle(siv));
+    menu.add_delimiter();
+    menu.add_leaf("Copy Path", |siv| copy_path_as(siv, false).handle(siv));
+    menu.add_leaf("Copy Relative Path", |siv| {
+        copy_path_as(siv, true).handle(siv);
+    });
+    menu.add_leaf("Open Externally", |siv| open_externally(siv).handle(siv));
+    menu.add_leaf(if marked { "Unmark" } else { "Mark" }, toggle_mark);
+    menu.add_leaf(if is_pinned { "Unpin" } else { "Pin" }, |siv| {
+        toggle_pin(siv).handle(siv);
+    });
+
+    let popup = MenuPopup::new(Rc::new(menu)).with_name("tree_menu");
+    match position {
+        Some(position) => siv
+            .screen_mut()
+            .add_layer_at(Position::absolute(position), popup),
+        None => siv.add_layer(popup),
+    }
+}
+
+/// Returns `true` if deleting the paths asks first, like for unsaved changes inside of them
+fn delete_asks(siv: &mut Cursive, paths: &[impl AsRef<Path>]) -> bool {
+    siv.with_user_data(|state: &mut State| {
+        let confirmations = &state.config.confirmations;
+        paths.iter().any(|path| {
+            let path = path.as_ref();
+            let is_dir = path.is_dir();
+            confirmations.delete.asks(is_dir)
+                || (!unsaved_inside(state, path).is_empty()
+                    && confirmations.discard_unsaved.asks(is_dir))
+        })
+    })
+    .unwrap_or(true)
+}
+
+/// Returns the files with unsaved changes at or inside of the path
+fn unsaved_inside(state: &State, path: &Path) -> Vec<PathBuf> {
+    state
+        .files_edited
+        .iter()
+        .filter(|(file, edited)| **edited && file.starts_with(path))
+        .map(|(file, _)| file.clone())
+        .collect()
+}
+
+/// Returns `true` if the tree is focused and no dialog is shown above it
+fn is_tree_active(siv: &mut Cursive) -> bool {
+    siv.screen().len() == 1 && !is_editor_focused(siv)
+}
+
+/// Marks the selected tree entry or removes its mark
+pub fn toggle_mark(siv: &mut Cursive) {
+    if is_tree_active(siv) {
+        siv.call_on_name("tree", file_tree::toggle_mark);
+    }
+}
+
+/// Marks the selected tree entry and the one above or below it
+pub fn extend_marks(siv: &mut Cursive, up: bool) {
+    if is_tree_active(siv) {
+        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            file_tree::extend_marks(tree, up);
+        });
+    }
+}
+
+/// Pins the selected tree entry or, while the editor is focused, the current file, pinned
+/// paths are unpinned instead
+pub fn toggle_pin(siv: &mut Cursive) -> Result<()> {
+    let path = if is_editor_focused(siv) {
+        siv.with_user_data(|state: &mut State| state.current_file.clone())
+            .flatten()
+    } else {
+        selected_entry(siv)
+    };
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let project_path = siv
+        .with_user_data(|state: &mut State| state.project_path.clone())
+        .unwrap();
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if pinned::toggle(&project_path, &path)? {
+        notify(siv, format!("Pinned {name}"));
+    } else {
+        notify(siv, format!("Unpinned {name}"));
+    }
+    Ok(())
+}
+
+/// Lists the pinned files and directories of the project
+///
+/// Selecting a file opens it and selecting a directory reveals it inside of the tree. Paths
+/// which don't exist anymore are shown as missing, `Del` unpins the selected one.
+pub fn pinned(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("pinned") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let project_path = siv
+        .with_user_data(|state: &mut State| state.project_path.clone())
+        .unwrap();
+    let paths = pinned::load(&project_path);
+    if paths.is_empty() {
+        notify(siv, "Nothing is pinned, pin entries via Shift + F3");
+        return Ok(());
+    }
+
+    let mut select = SelectView::new();
+    for path in paths {
+        let relative = path.strip_prefix(&project_path).unwrap_or(&path);
+        let mut label = relative.to_string_lossy().to_string();
+        if path.is_dir() {
+            label.push('/');
+        } else if !path.exists() {
+            label.push_str(" (missing)");
+        }
+        select.add_item(label, path);
+    }
+
+    let select = OnEventView::new(
+        select
+            .on_submit(|siv, path: &PathBuf| open_pinned(siv, path))
+            .with_name("pinned_select"),
+    )
+    .on_event(Key::Del, move |siv| {
+        let removed = siv
+            .call_on_name("pinned_select", |select: &mut SelectView<PathBuf>| {
+                let id = select.selected_id()?;
+                let path = select.get_item(id)?.1.clone();
+                Some((id, path))
+            })
+            .flatten();
+        let Some((id, path)) = removed else {
+            return;
+        };
+        if let Err(e) = pinned::remove(&project_path, &path) {
+            return e.to_dialog(siv);
+        }
+        let (callback, empty) = siv
+            .call_on_name("pinned_select", |select: &mut SelectView<PathBuf>| {
+                (select.remove_item(id), select.is_empty())
+            })
+            .unwrap();
+        callback(siv);
+        if empty {
+            siv.pop_layer();
+        }
+    });
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Pinned")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(ScrollView::new(select))
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("pinned"),
+    );
+
+    Ok(())
+}
+
+/// Opens the pinned file or reveals the pinned directory inside of the tree
+fn open_pinned(siv: &mut Cursive, path: &Path) {
+    if !path.exists() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        return notify(siv, format!("{name} doesn't exist anymore, Del unpins it"));
+    }
+    siv.pop_layer();
+    let current_file = siv
+        .with_user_data(|state: &mut State| state.current_file.clone())
+        .flatten();
+    if path.is_dir() {
+        reveal_in_tree(siv, path);
+        if let Ok(result) = siv.focus_name("tree") {
+            result.process(siv);
+        }
+        update_focus(siv);
+    } else if current_file.as_deref() != Some(path) {
+        // the current file keeps its cursor and column cursors
+        open_file(siv, path).handle(siv);
+    }
+}
+
+/// Returns the marked tree entries, `None` without any
+fn marked_entries(siv: &mut Cursive) -> Option<Vec<PathBuf>> {
+    siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+        file_tree::marked(tree)
+    })
+    .filter(|marked| !marked.is_empty())
+}
+
+/// Lists the paths relative to the project, one per line
+fn list_paths(siv: &mut Cursive, paths: &[PathBuf]) -> String {
+    let project_path = siv
+        .with_user_data(|state: &mut State| state.project_path.clone())
+        .unwrap_or_default();
+    paths
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&project_path)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Shows the entries a bulk operation failed for, together with the reasons
+fn report_failures(siv: &mut Cursive, action: &str, total: usize, failures: Vec<String>) {
+    if !failures.is_empty() {
+        Error::FileOpen(format!(
+            "Couldn't {action} {} of {total} entries:\n{}",
+            failures.len(),
+            failures.join("\n")
+        ))
+        .to_dialog(siv);
+    }
+}
+
+/// Deletes the marked entries after a single confirmation
+///
+/// Files with unsaved changes among them are pointed out.
+fn delete_marked(siv: &mut Cursive, marked: Vec<PathBuf>) {
+    let list = list_paths(siv, &marked);
+    let unsaved = siv
+        .with_user_data(|state: &mut State| {
+            marked
+                .iter()
+                .map(|path| unsaved_inside(state, path).len())
+                .sum::<usize>()
+        })
+        .unwrap_or_default();
+    let mut question = format!("Delete {} marked entries?", marked.len());
+    if unsaved > 0 {
+        question.push_str(&format!(" {unsaved} of the files have unsaved changes."));
+    }
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Delete")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                LinearLayout::vertical()
+                    .child(TextView::new(question))
+                    .child(TextView::new(" "))
+                    .child(TextView::new(list).scrollable()),
+            )
+            .button("Confirm", move |siv| {
+                siv.pop_layer();
+                delete_paths(siv, &marked);
+            })
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("delete"),
+    );
+}
+
+/// Deletes the files and directories, closing their buffers
+///
+/// The tree is reloaded once at the end and the entries which couldn't be deleted are listed.
+fn delete_paths(siv: &mut Cursive, paths: &[PathBuf]) {
+    let mut state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+
+    let mut failures = Vec::new();
+    for path in paths {
+        let result = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+        match result {
+            Ok(()) => {
+                let buffers = state
+                    .files
+                    .keys()
+                    .filter(|file| file.starts_with(path))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for file in buffers {
+                    state.remove_file(&file);
+                }
+            }
+            Err(e) => failures.push(format!("{}: {e}", path.to_string_lossy())),
+        }
+    }
+    siv.set_user_data(state.clone());
+
+    if let Err(e) = open_paths(siv, &state.project_path, state.current_file.as_ref()) {
+        e.to_dialog(siv);
+        return;
+    }
+    report_failures(siv, "delete", paths.len(), failures);
+}
+
+/// Moves the marked entries into a directory
+///
+/// The paths of affected buffers are updated and the tree is reloaded once at the end.
+fn move_marked(siv: &mut Cursive, marked: Vec<PathBuf>) -> Result<()> {
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+    let list = list_paths(siv, &marked);
+    let layout = LinearLayout::vertical()
+        .child(TextView::new(list).scrollable())
+        .child(TextView::new(" "))
+        .child(TextView::new(format!(
+            "Move {} marked entries into",
+            marked.len()
+        )))
+        .child(path_input::new(
+            state.prompt_dir(),
+            "move_path".to_string(),
+            false,
+        )?);
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Move")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(layout)
+            .button("Confirm", move |siv| {
+                let mut state = siv
+                    .with_user_data(|state: &mut State| state.clone())
+                    .unwrap();
+                let target = siv
+                    .call_on_name("move_path_edit", |view: &mut EditView| {
+                        path_input::expand_home(&view.get_content())
+                    })
+                    .unwrap();
+                if !target.is_dir() {
+                    Error::FileOpen("The destination isn't a directory".to_string()).to_dialog(siv);
+                    return;
+                }
+
+                let mut failures = Vec::new();
+                for path in &marked {
+                    let to = target.join(path.file_name().unwrap_or_default());
+                    let result = if target.starts_with(path) {
+                        Err(io::Error::new(
+                            ErrorKind::InvalidInput,
+                            "Can't move a directory into itself",
+                        ))
+                    } else if to.exists() {
+                        Err(io::Error::new(
+                            ErrorKind::AlreadyExists,
+                            "Destination already exists",
+                        ))
+                    } else {
+                        fs::rename(path, &to)
+                    };
+                    match result {
+                        Ok(()) => state.update_paths_after_rename(path, &to),
+                        Err(e) => failures.push(format!("{}: {e}", path.to_string_lossy())),
+                    }
+                }
+                state.set_last_dir(&target);
+                siv.set_user_data(state.clone());
+                siv.pop_layer();
+
+                if let Err(e) = open_paths(siv, &state.project_path, state.current_file.as_ref()) {
+                    e.to_dialog(siv);
+                    return;
+                }
+                report_failures(siv, "move", marked.len(), failures);
+            })
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("rename"),
+    );
+    Ok(())
+}
+
+/// Save current progress + Handling Title
+///
+/// Without `other` the current file is saved, otherwise the given path with the given content.
+/// If the file was changed on disk since it was loaded or saved, it asks before overwriting it.
+pub fn save(siv: &mut Cursive, other: Option<(&PathBuf, &String)>) -> Result<()> {
+    // scratch buffers have no file yet, so their path is asked for
+    let scratch = siv
+        .with_user_data(|state: &mut State| {
+            state.current_file.is_none() && state.current_scratch.is_some()
+        })
+        .unwrap_or_default();
+    if other.is_none() && scratch {
+        return save_as(siv);
+    }
+    // without an opened file there is nothing to save
+    let Some((path, content)) = siv
+        .with_user_data(|state: &mut State| match other {
+            Some((path, content)) => Some((path.clone(), content.clone())),
+            None => Some((state.current_file.clone()?, state.get_current_file()?.str())),
+        })
+        .flatten()
+    else {
+        return Ok(());
+    };
+    save_file(siv, &path, &content, false)
+}
+
+/// Asks for the path the current buffer is saved at, it becomes the buffer of that file
+///
+/// This is used for scratch buffers and files deleted on disk. Relative paths are resolved
+/// against the directory of the last prompt. Saving over an existing file asks first, unless
+/// it's disabled via the `confirmations` option.
+pub fn save_as(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("save_as") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let prompt_dir = siv
+        .with_user_data(|state: &mut State| state.prompt_dir().to_path_buf())
+        .unwrap();
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Save As")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(path_input::new(
+                &prompt_dir,
+                "save_as_path".to_string(),
+                false,
+            )?)
+            .button("Save", |siv| {
+                let path = siv
+                    .call_on_name("save_as_path_edit", |view: &mut EditView| {
+                        path_input::expand_home(&view.get_content())
+                    })
+                    .unwrap();
+                let (path, confirm) = siv
+                    .with_user_data(|state: &mut State| {
+                        let confirmations = &state.config.confirmations;
+                        (
+                            state.prompt_dir().join(path),
+                            confirmations.overwrite.asks(false),
+                        )
+                    })
+                    .unwrap();
+                if path.is_dir() {
+                    Error::Arguments(format!(
+                        "{} is an existing directory, choose another name for the file",
+                        path.to_string_lossy()
+                    ))
+                    .to_dialog(siv);
+                } else if path.is_file() && confirm {
+                    add_dialog(
+                        siv,
+                        Dialog::text(format!(
+                            "{} already exists, do you want to overwrite it?",
+                            path.to_string_lossy()
+                        ))
+                        .title("Overwrite")
+                        .padding_lrtb(1, 1, 1, 0)
+                        .button("Overwrite", move |siv| {
+                            siv.pop_layer();
+                            save_as_to(siv, &path).handle(siv);
+                        })
+                        .dismiss_button("Cancel"),
+                    );
+                } else {
+                    save_as_to(siv, &path).handle(siv);
+                }
+            })
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("save_as"),
+    );
+    Ok(())
+}
+
+/// Writes the current buffer to the path, it's replaced by the buffer of the new file
+fn save_as_to(siv: &mut Cursive, path: &Path) -> Result<()> {
+    let Some((old_path, buffer)) = siv
+        .with_user_data(|state: &mut State| {
+            Some((
+                state.current_file.clone(),
+                state.current_buffer_mut()?.clone(),
+            ))
+        })
+        .flatten()
+    else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    hooks::run(siv, Hook::BeforeSave, path);
+    write_atomically(path, &encoding::encode(&buffer.str(), buffer.format))?;
+
+    let path = path.canonicalize()?;
+    siv.with_user_data(|state: &mut State| {
+        match &old_path {
+            Some(old_path) => state.remove_file(old_path),
+            None => {
+                if let Some(name) = state.current_scratch.clone() {
+                    state.remove_scratch(&name);
+                }
+            }
+        }
+        state.set_last_dir(&path);
+        state.files_edited.remove(&path);
+        state.files.insert(
+            path.clone(),
+            FileData {
+                saved_hash: buffer.hash(),
+                saved_len: buffer.text.len_bytes(),
+                highlighted: state.config.highlights(&buffer.str()),
+                ..buffer
+            },
+        );
+    });
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("save_as") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    hooks::run(siv, Hook::AfterSave, &path);
+    reveal_in_tree(siv, &path);
+    open_file(siv, &path)?;
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    notify(siv, format!("Saved {file_name}"));
+    Ok(())
+}
+
+/// Saves the content to the path, changes on disk are only overwritten with `overwrite`
+fn save_file(siv: &mut Cursive, path: &Path, content: &str, overwrite: bool) -> Result<()> {
+    let mut state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+    let path = path.to_path_buf();
+    let content = content.to_string();
+    // saving a partially loaded file would cut it off
+    if state.get_file(&path).is_some_and(|file| file.read_only) {
+        return Err(Error::Arguments(
+            "Only the beginning of the large file is loaded, it can't be saved".to_string(),
+        ));
+    }
+
+    // a file which doesn't exist on disk anymore is created again, so its buffer isn't lost
+    let old_bytes = match fs::read(&path) {
+        Ok(old_bytes) => Some(old_bytes),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            None
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let old_content = old_bytes
+        .as_deref()
+        .map(encoding::decode_text)
+        .transpose()?;
+
+    // the content on disk differs from the one the buffer was loaded with
+    let changed_on_disk = old_content.as_ref().is_some_and(|(old_content, _)| {
+        state.get_file(&path).is_some_and(|file| {
+            *old_content != content && content_hash(old_content) != file.saved_hash
+        })
+    });
+    if changed_on_disk && !overwrite {
+        confirm_external_changes(siv, &path);
+        return Ok(());
+    }
+
+    hooks::run(siv, Hook::BeforeSave, &path);
+
+    // written back like it was loaded, otherwise like the file on disk is
+    let format = state
+        .get_file(&path)
+        .map(|file| file.format)
+        .or_else(|| old_content.map(|(_, format)| format))
+        .unwrap_or_default();
+    let bytes = encoding::encode(&content, format);
+    let mut backup_error = None;
+    if old_bytes.as_ref() != Some(&bytes) {
+        // the previous content is written aside first, the backups are only replaced once the
+        // file was saved
+        let backup = match &old_bytes {
+            Some(old_bytes) => backup::prepare(&state.config, &path, old_bytes)?,
+            None => None,
+        };
+        // just write when something really changed
+        if let Err(e) = write_atomically(&path, &bytes) {
+            if let Some(backup) = backup {
+                backup.discard();
+            }
+            return Err(e.into());
+        }
+        if let Some(Err(e)) = backup.map(backup::Pending::commit) {
+            backup_error = Some(format!("Couldn't save the backup: {e}"));
+        }
+    }
+
+    if state.current_file.as_ref() == Some(&path) {
+        update_title(siv, false, &path);
+    }
+
+    state.files_edited.remove(&path);
+    if let Some(file) = state.files.get_mut(&path) {
+        file.saved_hash = content_hash(&content);
+        file.saved_len = content.len();
+    }
+
+    // notifications are part of the state, so they're only shown once it's stored
+    siv.set_user_data(state);
+    if let Some(backup_error) = backup_error {
+        notify(siv, backup_error);
+    }
+    hooks::run(siv, Hook::AfterSave, &path);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    notify(siv, format!("Saved {file_name}"));
+    Ok(())
+}
+
+/// Asks how to continue saving a file which was changed on disk in the meantime
+///
+/// Showing the differences is the default, so nothing is lost by accident.
+fn confirm_external_changes(siv: &mut Cursive, path: &Path) {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("external_changes") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    let (diff_path, reload_path, overwrite_path) =
+        (path.to_path_buf(), path.to_path_buf(), path.to_path_buf());
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "{} was changed on disk since it was opened or saved.\nSaving overwrites these changes.",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ))
+        .title("Changed on Disk")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Show Diff", move |siv| {
+            show_external_diff(siv, &diff_path).handle(siv);
+        })
+        .button("Reload", move |siv| {
+            siv.pop_layer();
+            reload(siv, &reload_path).handle(siv);
+        })
+        .button("Overwrite", move |siv| {
+            siv.pop_layer();
+            let content = siv
+                .with_user_data(|state: &mut State| {
+                    Some(state.get_file(&overwrite_path)?.str())
+                })
+                .flatten();
+            if let Some(content) = content {
+                save_file(siv, &overwrite_path, &content, true).handle(siv);
+            }
+        })
+        .dismiss_button("Cancel")
+        .with_name("external_changes"),
+    );
+}
+
+/// Asks how to continue with the buffer of a current file which was deleted on disk
+///
+/// The buffer counts as edited from then on, so it isn't lost by accident. Keeping it and
+/// saving it later creates the file again.
+pub fn confirm_deleted(siv: &mut Cursive, path: &Path) {
+    let path = path.to_path_buf();
+    siv.with_user_data(|state: &mut State| {
+        if let Some(file) = state.files.get_mut(&path) {
+            file.mark_unsaved();
+            state.files_edited.insert(path.clone(), true);
+        }
+    });
+    update_title(siv, true, &path);
+
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("deleted") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "{} was deleted on disk.\nIts buffer can be kept and saved again, saved at another path or closed.",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ))
+        .title("Deleted on Disk")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Keep", |siv| {
+            siv.pop_layer();
+        })
+        .button("Save As", |siv| {
+            siv.pop_layer();
+            save_as(siv).handle(siv);
+        })
+        .button("Close", move |siv| {
+            siv.pop_layer();
+            close_file(siv, &path);
+        })
+        .with_name("deleted"),
+    );
+}
+
+/// Shows the differences between the file on disk and its buffer
+fn show_external_diff(siv: &mut Cursive, path: &Path) -> Result<()> {
+    /// Unchanged lines shown around the changed ones
+    const CONTEXT: usize = 2;
+
+    let (disk, _) = encoding::read(path)?;
+    let buffer = siv
+        .with_user_data(|state: &mut State| Some(state.files.get(path)?.str()))
+        .flatten()
+        .unwrap_or_default();
+
+    let lines = diff::lines(&disk, &buffer);
+    let is_near_change = |i: usize| {
+        lines[i.saturating_sub(CONTEXT)..(i + CONTEXT + 1).min(lines.len())]
+            .iter()
+            .any(|line| !matches!(line, diff::Line::Same(_)))
+    };
+    let mut text = StyledString::plain("- on disk, + in the editor\n\n");
+    let mut skipped = false;
+    for (i, line) in lines.iter().enumerate() {
+        if !is_near_change(i) {
+            skipped = true;
+            continue;
+        }
+        if skipped {
+            text.append_styled("...\n", Effect::Dim);
+            skipped = false;
+        }
+        match line {
+            diff::Line::Same(line) => text.append_plain(format!("  {line}\n")),
+            diff::Line::Removed(line) => {
+                text.append_styled(format!("- {line}\n"), Color::Dark(BaseColor::Red));
+            }
+            diff::Line::Added(line) => {
+                text.append_styled(format!("+ {line}\n"), Color::Dark(BaseColor::Green));
+            }
+        }
+    }
+    if skipped {
+        text.append_styled("...\n", Effect::Dim);
+    }
+
+    add_dialog(
+        siv,
+        Dialog::around(TextView::new(text).scrollable())
+            .title(format!(
+                "Diff of {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ))
+            .padding_lrtb(1, 1, 1, 0)
+            .dismiss_button("Close")
+            .full_screen(),
+    );
+    Ok(())
+}
+
+/// Replaces the buffer of the file with its content on disk, dropping the unsaved changes
+fn reload(siv: &mut Cursive, path: &Path) -> Result<()> {
+    let (content, format) = encoding::read(path)?;
+    let (is_current, notes) = siv
+        .with_user_data(|state: &mut State| {
+            let mut notes = BTreeSet::new();
+            if let Some(file) = state.files.get_mut(path) {
+                file.set_content(&content);
+                file.format = format;
+                file.mark_saved();
+                notes = file.notes.keys().copied().collect();
+            }
+            state.files_edited.remove(path);
+            (state.current_file.as_deref() == Some(path), notes)
+        })
+        .unwrap_or_default();
+
+    if is_current {
+        let callback = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                let byte_offset = edit_area.cursor().byte_offset;
+                edit_area.set_content(content);
+                edit_area.set_notes(notes);
+                edit_area.set_cursor_byte_offset(byte_offset)
+            })
+            .unwrap();
+        callback(siv);
+        update_title(siv, false, path);
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    notify(siv, format!("Reloaded {file_name}"));
+    Ok(())
+}
+
+/// Writes the file via a temporary file inside of the same directory, which replaces it at once
+///
+/// So the file isn't left truncated if writing fails halfway. Its permissions are kept. Files
+/// which can't be replaced like that are written directly instead, like links, hard linked
+/// files, files owned by another user or ones inside of a directory which isn't writable.
+/// Failing to write the temporary file, like with a full disk, is an error instead, as writing
+/// the file directly would most likely fail the same way and truncate it.
+fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => Some(metadata),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return fs::write(path, content);
+    };
+    if metadata
+        .as_ref()
+        .is_some_and(|metadata| !is_replaceable(metadata))
+    {
+        return fs::write(path, content);
+    }
+
+    let temp = dir.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    match replace_with_temp(path, &temp, content, metadata.as_ref()) {
+        Ok(true) => Ok(()),
+        Ok(false) => fs::write(path, content),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
+
+/// Writes the content into the temporary file and renames it to the path
+///
+/// Returns `false` if the file can't be replaced that way, like without write access to the
+/// directory, across file systems or if the owner can't be kept, the temporary file is removed
+/// again then.
+fn replace_with_temp(
+    path: &Path,
+    temp: &Path,
+    content: &[u8],
+    metadata: Option<&fs::Metadata>,
+) -> io::Result<bool> {
+    let mut file = match File::create(temp) {
+        Ok(file) => file,
+        Err(e) if is_not_replaceable_error(&e) => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    file.write_all(content)?;
+    file.sync_all()?;
+    if let Some(metadata) = metadata {
+        fs::set_permissions(temp, metadata.permissions())?;
+        // the temporary file belongs to the current user
+        if !has_same_owner(metadata, &file.metadata()?) {
+            fs::remove_file(temp)?;
+            return Ok(false);
+        }
+    }
+    match fs::rename(temp, path) {
+        Ok(()) => Ok(true),
+        Err(e) if is_not_replaceable_error(&e) => {
+            fs::remove_file(temp)?;
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns `true` for errors of creating or renaming the temporary file, after which the file
+/// may still be written directly
+fn is_not_replaceable_error(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    let other = matches!(e.raw_os_error(), Some(libc::EROFS | libc::EXDEV));
+    #[cfg(not(unix))]
+    let other = false;
+    e.kind() == ErrorKind::PermissionDenied || other
+}
+
+/// Returns `false` for files which would lose something when replaced by another file
+fn is_replaceable(metadata: &fs::Metadata) -> bool {
+    #[cfg(unix)]
+    let single_link = std::os::unix::fs::MetadataExt::nlink(metadata) == 1;
+    #[cfg(not(unix))]
+    let single_link = true;
+    metadata.is_file() && single_link
+}
+
+/// Returns `true` if both files belong to the same user and group
+fn has_same_owner(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        a.uid() == b.uid() && a.gid() == b.gid()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (a, b);
+        true
+    }
+}
+
+/// Saves all edited files, also the ones which aren't currently shown
+pub fn save_all(siv: &mut Cursive) -> Result<()> {
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+
+    for path in state.files_edited.keys() {
+        if let Some(file) = state.get_file(path) {
+            save(siv, Some((path, &file.str()))).handle(siv);
+        }
+    }
+    Ok(())
+}
+
+/// Toggles the auto-pairing of brackets and quotes inside the editor
+///
+/// The setting is saved to the config file.
+pub fn toggle_auto_pairs(siv: &mut Cursive) -> Result<()> {
+    let (auto_pairs, settings) = siv
+        .with_user_data(|state: &mut State| {
+            state.config.auto_pairs = !state.config.auto_pairs;
+            state.settings.auto_pairs = Some(state.config.auto_pairs);
+            (state.config.auto_pairs, state.settings.clone())
+        })
+        .unwrap();
+    notify(
+        siv,
+        if auto_pairs {
+            "Auto-pairs enabled"
+        } else {
+            "Auto-pairs disabled"
+        },
+    );
+
+    siv.call_on_name("editor", |edit_area: &mut EditArea| {
+        edit_area.set_auto_pairs(auto_pairs);
+    })
+    .unwrap();
+
+    settings.save()
+}
+
+/// Turns the syntax highlighting of the current file on or off
+///
+/// Files above the `highlight_limit` are opened without it, this turns it on for them anyway.
+pub fn toggle_highlighting(siv: &mut Cursive) -> Result<()> {
+    let highlighted = siv
+        .with_user_data(|state: &mut State| {
+            let current_file = state.current_file.clone()?;
+            let file = state.files.get_mut(&current_file)?;
+            file.highlighted = !file.highlighted;
+            Some(file.highlighted)
+        })
+        .flatten();
+    let Some(highlighted) = highlighted else {
+        return Ok(());
+    };
+    let cursor = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.set_highlighted(highlighted);
+            edit_area.cursor()
+        })
+        .unwrap();
+    update_status(siv, Some(cursor));
+    notify(
+        siv,
+        if highlighted {
+            "Highlighting enabled"
+        } else {
+            "Highlighting disabled"
+        },
+    );
+    Ok(())
+}
+
+/// Shows the number of lines, words and characters of the current buffer, also of the lines of
+/// the column cursors if there are any
+///
+/// Words are split by the Unicode rules, so punctuation isn't counted as a word, and characters
+/// are counted as graphemes, so `é` made up of two code points is a single one.
+pub fn statistics(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("statistics") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let Some(content) = siv
+        .with_user_data(|state: &mut State| Some(state.current_buffer_mut()?.str()))
+        .flatten()
+    else {
+        return Ok(());
+    };
+    let selection = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.selection().map(count_text)
+        })
+        .flatten();
+
+    let mut list = ListView::new();
+    for (i, (name, count)) in ["Lines", "Words", "Characters"]
+        .into_iter()
+        .zip(count_text(&content))
+        .enumerate()
+    {
+        let text = match selection {
+            Some(selected) => format!("{count} ({} selected)", selected[i]),
+            None => count.to_string(),
+        };
+        list.add_child(name, TextView::new(text));
+    }
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Statistics")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(list)
+            .dismiss_button("Close")
+            .with_name("statistics"),
+    );
+    Ok(())
+}
+
+/// Counts the lines, words and graphemes of the text
+fn count_text(text: &str) -> [usize; 3] {
+    [
+        text.lines().count(),
+        text.unicode_words().count(),
+        text.graphemes(true).count(),
+    ]
+}
+
+/// Compares the current buffer side by side with another open file or scratch buffer
+///
+/// The differences are computed in the background, so large files don't block the editor.
+pub fn compare(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("compare") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let Some((current, others)) = siv
+        .with_user_data(|state: &mut State| {
+            let current = match &state.current_file {
+                Some(current_file) => current_file.to_string_lossy().to_string(),
+                None => state.current_scratch.clone()?,
+            };
+            // scratch buffers are listed by their names, they have no path
+            let mut others = state
+                .scratches
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            let mut files = state
+                .files
+                .keys()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            files.sort();
+            others.extend(files);
+            others.retain(|other| *other != current);
+            Some((current, others))
+        })
+        .flatten()
+    else {
+        notify(siv, "Open a file to compare it with another one");
+        return Ok(());
+    };
+    if others.is_empty() {
+        notify(siv, "Open another file to compare the current one with");
+        return Ok(());
+    }
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title(format!("Compare {} with", buffer_name(&current)))
+            .padding_lrtb(1, 1, 1, 0)
+            .content(ScrollView::new(
+                SelectView::new()
+                    .with_all_str(others)
+                    .on_submit(move |siv, other: &String| {
+                        siv.pop_layer();
+                        show_comparison(siv, &current, other);
+                    }),
+            ))
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("compare"),
+    );
+    Ok(())
+}
+
+/// Returns the file name of an open file or the name of a scratch buffer
+fn buffer_name(buffer: &str) -> String {
+    Path::new(buffer)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Shows the differences of two buffers, the old one on the left and the new one on the right
+///
+/// Both sides are inside of the same scroll view, so they're scrolled together.
+fn show_comparison(siv: &mut Cursive, old: &str, new: &str) {
+    let Some((old_text, new_text, tab_size)) = siv
+        .with_user_data(|state: &mut State| {
+            let text = |buffer: &str| match state.get_scratch(buffer) {
+                Some(scratch) => Some(scratch.str()),
+                None => Some(state.files.get(Path::new(buffer))?.str()),
+            };
+            Some((text(old)?, text(new)?, state.config.tab_size))
+        })
+        .flatten()
+    else {
+        return;
+    };
+
+    let title = format!("Diff of {} and {}", buffer_name(old), buffer_name(new));
+    notify(
+        siv,
+        format!("Comparing {} and {}...", buffer_name(old), buffer_name(new)),
+    );
+    let cb_sink = siv.cb_sink().clone();
+    thread::spawn(move || {
+        let [left, separator, right] = side_by_side(&old_text, &new_text, tab_size);
+        // the app may have been quit in the meantime
+        let _ = cb_sink.send(Box::new(move |siv| {
+            add_dialog(
+                siv,
+                Dialog::around(
+                    LinearLayout::horizontal()
+                        .child(TextView::new(left).no_wrap().full_width())
+                        .child(TextView::new(separator))
+                        .child(TextView::new(right).no_wrap().full_width())
+                        .scrollable(),
+                )
+                .title(title)
+                .padding_lrtb(1, 1, 1, 0)
+                .dismiss_button("Close")
+                .full_screen(),
+            );
+        }));
+    });
+}
+
+/// Returns the numbered lines of both texts next to each other and the column between them
+///
+/// Like `diff -y` the column marks changed lines with `|`, removed ones with `<` and added ones
+/// with `>`, tabs are expanded so both sides stay aligned.
+fn side_by_side(old: &str, new: &str, tab_size: usize) -> [StyledString; 3] {
+    let width = old
+        .lines()
+        .count()
+        .max(new.lines().count())
+        .to_string()
+        .len();
+    let tab = " ".repeat(tab_size);
+    let mut sides = [
+        StyledString::new(),
+        StyledString::new(),
+        StyledString::new(),
+    ];
+    let mut numbers = [0, 0];
+    for row in diff::rows(old, new) {
+        let (lines, marker, color) = match row {
+            diff::Row::Same(line) => ([Some(line), Some(line)], " ", None),
+            diff::Row::Changed(old, new) => ([Some(old), Some(new)], "|", Some(BaseColor::Yellow)),
+            diff::Row::Removed(old) => ([Some(old), None], "<", Some(BaseColor::Red)),
+            diff::Row::Added(new) => ([None, Some(new)], ">", Some(BaseColor::Green)),
+        };
+        sides[1].append_plain(format!(" {marker} \n"));
+        for (side, (line, number)) in [0, 2].into_iter().zip(lines.into_iter().zip(&mut numbers)) {
+            let Some(line) = line else {
+                sides[side].append_plain("\n");
+                continue;
+            };
+            *number += 1;
+            let text = format!("{number:>width$} {}\n", line.replace('\t', &tab));
+            match color {
+                Some(color) => sides[side].append_styled(text, Color::Dark(color)),
+                None => sides[side].append_plain(text),
+            }
+        }
+    }
+    sides
+}
+
+/// Shows the note of the cursor's line for editing it, or adds a new one
+///
+/// Notes don't change the content, so they can also be added to read-only files.
+pub fn note(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("note") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let Some(row) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.is_enabled().then(|| edit_area.selected_row())
+        })
+        .flatten()
+    else {
+        return Ok(());
+    };
+    let Some(note) = siv
+        .with_user_data(|state: &mut State| {
+            Some(state.current_buffer_mut()?.notes.get(&row).cloned())
+        })
+        .flatten()
+    else {
+        return Ok(());
+    };
+
+    let has_note = note.is_some();
+    let mut dialog = Dialog::new()
+        .title(format!("Note on Line {}", row + 1))
+        .padding_lrtb(1, 1, 1, 0)
+        .content(
+            EditView::new()
+                .content(note.unwrap_or_default())
+                .on_submit(move |siv, text| {
+                    siv.pop_layer();
+                    set_note(siv, row, text);
+                })
+                .with_name("note_text")
+                .min_width(40),
+        )
+        .button("Save", move |siv| {
+            let text = siv
+                .call_on_name("note_text", |view: &mut EditView| view.get_content())
+                .unwrap();
+            siv.pop_layer();
+            set_note(siv, row, &text);
+        });
+    if has_note {
+        dialog.add_button("Remove", move |siv| {
+            siv.pop_layer();
+            set_note(siv, row, "");
+        });
+    }
+    add_dialog(siv, dialog.dismiss_button("Cancel").with_name("note"));
+    Ok(())
+}
+
+/// Attaches the note to the row of the current buffer, an empty note removes it
+fn set_note(siv: &mut Cursive, row: usize, text: &str) {
+    let text = text.trim();
+    let Some(notes) = siv
+        .with_user_data(|state: &mut State| {
+            let buffer = state.current_buffer_mut()?;
+            if text.is_empty() {
+                buffer.notes.remove(&row)?;
+            } else {
+                buffer.notes.insert(row, text.to_string());
+            }
+            Some(buffer.notes.keys().copied().collect())
+        })
+        .flatten()
+    else {
+        return;
+    };
+    siv.call_on_name("editor", |edit_area: &mut EditArea| {
+        edit_area.set_notes(notes);
+    });
+    if text.is_empty() {
+        notify(siv, format!("Removed the note of line {}", row + 1));
+    }
+}
+
+/// Lists the notes of the current buffer, selecting one moves the cursor to its line
+pub fn notes(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("notes") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let notes = siv
+        .with_user_data(|state: &mut State| Some(state.current_buffer_mut()?.notes.clone()))
+        .flatten()
+        .unwrap_or_default();
+    if notes.is_empty() {
+        notify(siv, "No notes in this buffer, add one via Ctrl + F3");
+        return Ok(());
+    }
+
+    let mut select = SelectView::new();
+    for (row, note) in notes {
+        select.add_item(format!("{}: {note}", row + 1), row);
+    }
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Notes")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(ScrollView::new(select.on_submit(|siv, row: &usize| {
+                siv.pop_layer();
+                let callback = siv
+                    .call_on_name("editor", |edit_area: &mut EditArea| {
+                        edit_area.set_cursor_row(*row)
+                    })
+                    .unwrap();
+                callback(siv);
+            })))
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("notes"),
+    );
+    Ok(())
+}
+
+/// Aligns the lines of the column cursors or around the cursor on a delimiter
+pub fn align(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("align") {
+        siv.screen_mut().remove_layer(pos);
+    } else {
+        let enabled = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| edit_area.is_enabled())
+            .unwrap();
+        if !enabled {
+            return Ok(());
+        }
+        check_editable(siv)?;
+
+        let submit = |siv: &mut Cursive, delimiter: &str| {
+            siv.pop_layer();
+            let callback = siv
+                .call_on_name("editor", |edit_area: &mut EditArea| {
+                    edit_area.align_rows(delimiter)
+                })
+                .unwrap();
+            callback(siv);
+        };
+        add_dialog(
+            siv,
+            Dialog::new()
+                .title("Align on Delimiter")
+                .padding_lrtb(1, 1, 1, 0)
+                .content(
+                    EditView::new()
+                        .content("=")
+                        .on_submit(submit)
+                        .with_name("align_delimiter")
+                        .fixed_width(20),
+                )
+                .button("Align", move |siv| {
+                    let delimiter = siv
+                        .call_on_name("align_delimiter", |view: &mut EditView| view.get_content())
+                        .unwrap();
+                    submit(siv, &delimiter);
+                })
+                .dismiss_button("Cancel")
+                .with_name("align"),
+        );
+    }
+
+    Ok(())
+}
+
+/// Removes duplicate adjacent lines of the column cursors or of the whole file
+///
+/// The number of removed lines is shown as a notification.
+pub fn remove_duplicates(siv: &mut Cursive) -> Result<()> {
+    check_editable(siv)?;
+    let Some((removed, callback)) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area
+                .is_enabled()
+                .then(|| edit_area.remove_duplicate_rows())
+        })
+        .unwrap()
+    else {
+        return Ok(());
+    };
+    callback(siv);
+
+    notify(
+        siv,
+        match removed {
+            0 => "No duplicate lines found".to_string(),
+            1 => "Removed 1 duplicate line".to_string(),
+            n => format!("Removed {n} duplicate lines"),
+        },
+    );
+    Ok(())
+}
+
+/// Pipes the lines of the column cursors or the whole file through a shell command
+///
+/// The text is replaced by the output of the command, like `sort` or `jq .`. A failing command
+/// leaves it as it is and shows its error output instead.
+pub fn filter(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("filter") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let enabled = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| edit_area.is_enabled())
+        .unwrap();
+    if !enabled {
+        return Ok(());
+    }
+    check_editable(siv)?;
+
+    let submit = |siv: &mut Cursive, command: &str| {
+        siv.pop_layer();
+        if !command.trim().is_empty() {
+            run_filter(siv, command.to_string());
+        }
+    };
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Filter through Command")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                EditView::new()
+                    .on_submit(submit)
+                    .with_name("filter_command")
+                    .fixed_width(40),
+            )
+            .button("Run", move |siv| {
+                let command = siv
+                    .call_on_name("filter_command", |view: &mut EditView| view.get_content())
+                    .unwrap();
+                submit(siv, &command);
+            })
+            .dismiss_button("Cancel")
+            .with_name("filter"),
+    );
+    Ok(())
+}
+
+/// Returns a command running the given one inside of the system's shell
+pub fn shell(command: &str) -> Command {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+/// Asks for a shell command like `cargo build` and runs it inside of the project directory
+///
+/// Its output is streamed into the output panel, which keeps the output of former runs.
+pub fn run(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("run") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let last = siv
+        .with_user_data(|state: &mut State| state.last_command.clone())
+        .unwrap()
+        .unwrap_or_default();
+
+    let submit = |siv: &mut Cursive, command: &str| {
+        siv.pop_layer();
+        if !command.trim().is_empty() {
+            start_run(siv, command.to_string()).handle(siv);
+        }
+    };
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Run Command")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                EditView::new()
+                    .content(last)
+                    .on_submit(submit)
+                    .with_name("run_command")
+                    .fixed_width(40),
+            )
+            .button("Run", move |siv| {
+                let command = siv
+                    .call_on_name("run_command", |view: &mut EditView| view.get_content())
+                    .unwrap();
+                submit(siv, &command);
+            })
+            .dismiss_button("Cancel")
+            .with_name("run"),
+    );
+    Ok(())
+}
+
+/// Starts the command, its stdout and stderr are appended to the output line by line
+fn start_run(siv: &mut Cursive, command: String) -> Result<()> {
+    let project_path = siv
+        .with_user_data(|state: &mut State| {
+            state.last_command = Some(command.clone());
+            state.project_path.clone()
+        })
+        .unwrap();
+    let mut child = shell(&command)
+        .current_dir(project_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    output::show(siv);
+    output::append(siv, format!("\x1b[1m$ {command}\x1b[0m"));
+    let cb_sink = siv.cb_sink().clone();
+    let readers = [
+        stream_lines(child.stdout.take().unwrap(), cb_sink.clone()),
+        stream_lines(child.stderr.take().unwrap(), cb_sink.clone()),
+    ];
+    thread::spawn(move || {
+        for reader in readers {
+            let _ = reader.join();
+        }
+        let message = match child.wait() {
+            Ok(status) if status.success() => format!("{command} finished"),
+            Ok(status) => format!("{command} failed with {status}"),
+            Err(e) => format!("{command} failed: {e}"),
+        };
+        let _ = cb_sink.send(Box::new(move |siv| {
+            output::append(siv, format!("\x1b[90m{message}\x1b[0m"));
+            notify(siv, message);
+        }));
+    });
+    Ok(())
+}
+
+/// Sends the lines of a stream to the output panel until it ends
+///
+/// Like in a terminal, only the text behind the last carriage return of a line is kept, so
+/// progress bars show their final state.
+fn stream_lines<R: Read + Send + 'static>(stream: R, cb_sink: CbSink) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut buffer = Vec::new();
+        while reader
+            .read_until(b'\n', &mut buffer)
+            .is_ok_and(|read| read > 0)
+        {
+            let line = String::from_utf8_lossy(&buffer);
+            let line = line.trim_end_matches(['\n', '\r']);
+            let line = line.rsplit('\r').next().unwrap_or_default().to_string();
+            buffer.clear();
+            // the app may have been quit in the meantime
+            if cb_sink
+                .send(Box::new(move |siv| output::append(siv, line)))
+                .is_err()
+            {
+                break;
+            }
+        }
+    })
+}
+
+/// Searches the output panel, the matches are highlighted until they're cleared via `Esc`
+///
+/// The output is scrolled to the last matching line, as the newest output is the most relevant.
+pub fn search_output(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("search") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let last = siv
+        .with_user_data(|state: &mut State| {
+            state
+                .output_search
+                .as_ref()
+                .map(|search| search.text.clone())
+        })
+        .unwrap()
+        .unwrap_or_default();
+
+    let submit = |siv: &mut Cursive, text: &str| {
+        siv.pop_layer();
+        let (search, output) = siv
+            .with_user_data(|state: &mut State| {
+                state.output_search = Search::new(text, state.config.search_case);
+                (state.output_search.clone(), state.output.clone())
+            })
+            .unwrap();
+        output::render(siv);
+        let Some(search) = search else {
+            return;
+        };
+        let text = &search.text;
+        let matching = output
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| search.matches(ansi::parse(line).source()).next().is_some())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        match matching.last() {
+            Some(&last) => {
+                output::scroll_to_line(siv, last);
+                notify(siv, format!("{} matching lines", matching.len()));
+            }
+            None => notify(siv, format!("No matches for \"{text}\"")),
+        }
+    };
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Search Output")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                EditView::new()
+                    .content(last)
+                    .on_submit(submit)
+                    .with_name("search_text")
+                    .fixed_width(40),
+            )
+            .button("Find", move |siv| {
+                let text = siv
+                    .call_on_name("search_text", |view: &mut EditView| view.get_content())
+                    .unwrap();
+                submit(siv, &text);
+            })
+            .dismiss_button("Cancel")
+            .with_name("search"),
+    );
+    Ok(())
+}
+
+/// Searches the current file, all matches are highlighted until they're cleared via `Esc`
+///
+/// The cursor is moved to the next match, searching the same text again continues behind it.
+pub fn search(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("search") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+    let enabled = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| edit_area.is_enabled())
+        .unwrap();
+    if !enabled {
+        return Ok(());
+    }
+    let last = siv
+        .with_user_data(|state: &mut State| state.search.clone())
+        .unwrap()
+        .unwrap_or_default();
+
+    let submit = |siv: &mut Cursive, text: &str| {
+        siv.pop_layer();
+        let text = (!text.is_empty()).then(|| text.to_string());
+        let case = siv
+            .with_user_data(|state: &mut State| {
+                state.search = text.clone();
+                state.config.search_case
+            })
+            .unwrap();
+        let search = text.as_deref().and_then(|text| Search::new(text, case));
+        let repeated = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                let repeated = edit_area.search() == search.as_ref();
+                edit_area.set_search(search);
+                repeated
+            })
+            .unwrap();
+        siv.focus_name("editor").unwrap();
+        if text.is_some() {
+            move_to_match(siv, true, repeated);
+        }
+    };
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Search")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                EditView::new()
+                    .content(last)
+                    .on_submit(submit)
+                    .with_name("search_text")
+                    .fixed_width(40),
+            )
+            .button("Find", move |siv| {
+                let text = siv
+                    .call_on_name("search_text", |view: &mut EditView| view.get_content())
+                    .unwrap();
+                submit(siv, &text);
+            })
+            .dismiss_button("Cancel")
+            .with_name("search"),
+    );
+    Ok(())
+}
+
+/// Moves the cursor to the next or previous match of the last search
+///
+/// Matches which were cleared via `Esc` are highlighted again.
+pub fn find_next(siv: &mut Cursive, forward: bool) {
+    let Some(search) = siv
+        .with_user_data(|state: &mut State| {
+            Search::new(state.search.as_deref()?, state.config.search_case)
+        })
+        .unwrap()
+    else {
+        return notify(siv, "Nothing searched yet, search via Ctrl + f");
+    };
+    let enabled = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            if edit_area.search().is_none() {
+                edit_area.set_search(Some(search));
+            }
+            edit_area.is_enabled()
+        })
+        .unwrap();
+    if enabled {
+        move_to_match(siv, forward, true);
+    }
+}
+
+/// Moves the cursor of the editor to the next or previous match of its search, showing its
+/// number like `Match 3 of 17`
+fn move_to_match(siv: &mut Cursive, forward: bool, skip_current: bool) {
+    let (search, callback) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            let search = edit_area
+                .search()
+                .map(|search| search.text.clone())
+                .unwrap_or_default();
+            let found = edit_area.find_match(forward, skip_current);
+            (
+                search,
+                found.map(|offset| edit_area.set_cursor_byte_offset(offset)),
+            )
+        })
+        .unwrap();
+    let Some(callback) = callback else {
+        return notify(siv, format!("No matches for \"{search}\""));
+    };
+    callback(siv);
+
+    let (current, total) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| edit_area.match_count())
+        .unwrap();
+    let total = if total == MAX_COUNTED_MATCHES {
+        format!("{total}+")
+    } else {
+        total.to_string()
+    };
+    notify(
+        siv,
+        match current {
+            Some(current) => format!("Match {current} of {total} for \"{search}\""),
+            None => format!("More than {MAX_COUNTED_MATCHES} matches for \"{search}\""),
+        },
+    );
+}
+
+/// Runs the command in the background and replaces the text with its output afterwards
+///
+/// The output is dropped if the file was edited or switched in the meantime.
+fn run_filter(siv: &mut Cursive, command: String) {
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+    let Some(path) = state.current_file.clone() else {
+        return;
+    };
+    let (range, text, hash) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            let range = edit_area.lines_range();
+            let content = edit_area.get_content();
+            (
+                range.clone(),
+                content[range].to_string(),
+                content_hash(content),
+            )
+        })
+        .unwrap();
+    let dir = path
+        .parent()
+        .map_or(state.project_path.clone(), Path::to_path_buf);
+
+    notify(siv, format!("Running {command}"));
+    let cb_sink = siv.cb_sink().clone();
+    thread::spawn(move || {
+        let result = run_command(&command, &text, &dir);
+        let _ = cb_sink.send(Box::new(move |siv| {
+            let output = match result {
+                Ok(output) => output,
+                Err(e) => return e.to_dialog(siv),
+            };
+            let current_file = siv
+                .with_user_data(|state: &mut State| state.current_file.clone())
+                .flatten();
+            let unchanged = siv
+                .call_on_name("editor", |edit_area: &mut EditArea| {
+                    content_hash(edit_area.get_content()) == hash
+                })
+                .unwrap_or_default();
+            if current_file != Some(path) || !unchanged {
+                let message = format!("The file was changed while {command} ran, it's kept");
+                notify(siv, message);
+                return;
+            }
+
+            // a missing newline at the end stays missing
+            let output = match output.strip_suffix('\n') {
+                Some(stripped) if !text.ends_with('\n') => stripped,
+                _ => &output,
+            };
+            let callback = siv
+                .call_on_name("editor", |edit_area: &mut EditArea| {
+                    edit_area.replace_lines(range, output)
+                })
+                .unwrap();
+            callback(siv);
+            notify(siv, format!("Filtered through {command}"));
+        }));
+    });
+}
+
+/// Runs the command inside of a shell with the input on stdin and returns its output
+///
+/// A nonzero exit status is an error containing the error output of the command.
+fn run_command(command: &str, input: &str, dir: &Path) -> Result<String> {
+    let mut child = shell(command)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // written separately, so a command writing before it read everything doesn't block
+    let mut stdin = child.stdin.take().unwrap();
+    let mut input = input.to_string();
+    if !input.ends_with('\n') {
+        input.push('\n');
+    }
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(Error::Command(format!(
+            "{command} failed with {}\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| Error::Command(format!("{command} didn't output valid UTF-8")))
+}
+
+/// Lists templates like the current date/time or the file name, selecting one inserts it at the cursor
+///
+/// An invalid date format shows an error, the default format is used instead.
+pub fn templates(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("templates") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+    let Some(current_file) = state.current_file.clone() else {
+        return Ok(());
+    };
+    check_editable(siv)?;
+
+    let (date, valid) = match format_date(&state.config.date_format) {
+        Some(date) => (date, true),
+        None => (format_date(DEFAULT_DATE_FORMAT).unwrap_or_default(), false),
+    };
+    let file_name = current_file
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let relative_path = current_file
+        .strip_prefix(&state.project_path)
+        .unwrap_or(&current_file)
+        .to_string_lossy()
+        .to_string();
+
+    let mut select = SelectView::new();
+    for (name, text) in [
+        ("Date/Time", date),
+        ("File Name", file_name),
+        ("Relative Path", relative_path),
+    ] {
+        select.add_item(format!("{name}: {text}"), text);
+    }
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Insert Template")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(select.on_submit(|siv, text: &String| {
+                siv.pop_layer();
+                let callback = siv
+                    .call_on_name("editor", |edit_area: &mut EditArea| {
+                        edit_area.insert_str(text)
+                    })
+                    .unwrap();
+                callback(siv);
+            }))
+            .dismiss_button("Cancel")
+            .with_name("templates"),
+    );
+
+    if !valid {
+        return Err(Error::Config(format!(
+            "Invalid date format `{}`",
+            state.config.date_format
+        )));
+    }
+    Ok(())
+}
+
+/// Shows the directory of the selected tree entry with buttons to open it in the file
+/// manager or a terminal, a selected file can also be opened with its default application
+///
+/// Without a selection the project directory is used. If no file manager or terminal
+/// can be started, like on a headless system, the path can still be read from the dialog.
+pub fn open_externally(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("open_externally") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let file = siv
+        .call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            Some(tree.borrow_item(tree.row()?)?.path.clone())
+        })
+        .flatten()
+        .filter(|path| path.is_file());
+    let dir = selected_dir(siv);
+    let manager_dir = dir.clone();
+    let terminal_dir = dir.clone();
+    let mut dialog = Dialog::text(file.as_ref().unwrap_or(&dir).to_string_lossy())
+        .title("Open Externally")
+        .padding_lrtb(1, 1, 1, 0);
+    if let Some(file) = file {
+        dialog.add_button("Application", move |siv| open_default_app(siv, &file));
+    }
+    add_dialog(
+        siv,
+        dialog
+            .button("File Manager", move |siv| {
+                open_default_app(siv, &manager_dir);
+            })
+            .button("Terminal", move |siv| match spawn_terminal(&terminal_dir) {
+                Ok(()) => {
+                    siv.pop_layer();
+                }
+                Err(e) => notify(siv, format!("Couldn't open a terminal: {e}")),
+            })
+            .dismiss_button("Cancel")
+            .with_name("open_externally"),
+    );
+    Ok(())
+}
+
+/// Asks to open a file which isn't text, like an image, with its default application instead
+pub fn open_unsupported(siv: &mut Cursive, path: &Path) {
+    let file = path.to_path_buf();
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "{} isn't a text file, it can't be edited",
+            path.to_string_lossy()
+        ))
+        .title("Unsupported File")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Open with Application", move |siv| {
+            open_default_app(siv, &file);
+        })
+        .dismiss_button("Cancel"),
+    );
+}
+
+/// Asks before reading a large file, which may block the editor for a while
+///
+/// Opening only the beginning of it read-only is the safe choice, so it's focused.
+pub fn confirm_large_file(siv: &mut Cursive, path: &Path, size: u64) {
+    let limit = siv
+        .with_user_data(|state: &mut State| state.config.large_file_warning)
+        .unwrap_or_default();
+    let (read_only_file, full_file) = (path.to_path_buf(), path.to_path_buf());
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "{} is {}MB, open anyway?\nOpened read-only, only its first {limit}MB are loaded.",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            size / 1024 / 1024
+        ))
+        .title("Large File")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Read-only", move |siv| {
+            siv.pop_layer();
+            open_file_with(siv, &read_only_file, OpenMode::ReadOnly).handle(siv);
+        })
+        .button("Open", move |siv| {
+            siv.pop_layer();
+            open_file_with(siv, &full_file, OpenMode::Full).handle(siv);
+        })
+        .dismiss_button("Cancel"),
+    );
+}
+
+/// Asks before pasting a large text into the editor, which may block it for a while
+pub fn confirm_paste(siv: &mut Cursive, text: String) {
+    add_dialog(
+        siv,
+        Dialog::text(format!(
+            "The pasted text is {}KB with {} lines, paste it anyway?",
+            text.len() / 1024,
+            text.lines().count()
+        ))
+        .title("Large Paste")
+        .padding_lrtb(1, 1, 1, 0)
+        .button("Paste", move |siv| {
+            siv.pop_layer();
+            let callback = siv
+                .call_on_name("editor", |edit_area: &mut EditArea| {
+                    edit_area.insert_str(&text)
+                })
+                .unwrap();
+            callback(siv);
+        })
+        .dismiss_button("Cancel"),
+    );
+}
+
+/// Opens the path with its default application and closes the dialog, a directory inside of
+/// the file manager
+///
+/// The application is started in the background, so the editor isn't blocked by it.
+fn open_default_app(siv: &mut Cursive, path: &Path) {
+    match spawn_default_app(path) {
+        Ok(()) => {
+            siv.pop_layer();
+        }
+        Err(e) => notify(
+            siv,
+            format!("Couldn't open {}: {e}", path.to_string_lossy()),
+        ),
+    }
+}
+
+/// Returns an error if there is no graphical session to show windows in
+fn check_graphical_session() -> io::Result<()> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            "no graphical session found",
+        ));
+    }
+    Ok(())
+}
+
+/// Opens the path with the default application of the system, directories inside of the
+/// file manager
+fn spawn_default_app(path: &Path) -> io::Result<()> {
+    check_graphical_session()?;
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(windows)]
+    let opener = "explorer";
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let opener = "xdg-open";
+
+    detached(Command::new(opener).arg(path))
+}
+
+/// Opens a new terminal window inside of the directory
+///
+/// On Linux the terminal from `$TERMINAL` is used, falling back to `x-terminal-emulator`.
+fn spawn_terminal(dir: &Path) -> io::Result<()> {
+    check_graphical_session()?;
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("open");
+        command.args(["-a", "Terminal"]).arg(dir);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "cmd"]);
+        command
+    };
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let mut command =
+        Command::new(std::env::var_os("TERMINAL").unwrap_or_else(|| "x-terminal-emulator".into()));
+
+    detached(command.current_dir(dir))
+}
+
+/// Starts the command without connecting it to the terminal of the editor
+///
+/// It's waited for in the background, so it doesn't stay a zombie once it exits.
+fn detached(command: &mut Command) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    thread::spawn(move || {
+        if let Err(e) = child.wait() {
+            warn!("The started command couldn't be waited for: {e}");
+        }
+    });
+    Ok(())
+}
+
+/// Copies the absolute or project relative path of the current file to the clipboard
+///
+/// While the tree is focused its selected entry is used instead. Without clipboard access,
+/// like on a headless system, the path is shown as a notification.
+pub fn copy_path(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("copy_path") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let path = path_to_copy(siv)?;
+    let absolute = path.to_string_lossy().to_string();
+    let relative = relative_path(siv, &path);
+
+    let mut select = SelectView::new();
+    select.add_item(format!("Absolute: {absolute}"), absolute);
+    if let Some(relative) = relative {
+        select.add_item(format!("Relative: {relative}"), relative);
+    }
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Copy Path")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(select.on_submit(|siv, path: &String| {
+                siv.pop_layer();
+                copy_to_clipboard(siv, path);
+            }))
+            .dismiss_button("Cancel")
+            .with_name("copy_path"),
+    );
+    Ok(())
+}
+
+/// Copies the absolute or project relative path of the current file without asking which one
+///
+/// Like for [`copy_path`], the selected entry is used while the tree is focused.
+pub fn copy_path_as(siv: &mut Cursive, relative: bool) -> Result<()> {
+    let path = path_to_copy(siv)?;
+    let text = if relative {
+        relative_path(siv, &path).ok_or_else(|| {
+            Error::FileOpen(format!(
+                "{} isn't inside of the project",
+                path.to_string_lossy()
+            ))
+        })?
+    } else {
+        path.to_string_lossy().to_string()
+    };
+    copy_to_clipboard(siv, &text);
+    Ok(())
+}
+
+/// Returns the current file or, while the tree is focused, its selected entry
+fn path_to_copy(siv: &mut Cursive) -> Result<PathBuf> {
+    let selected = siv
+        .call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            Some(tree.borrow_item(tree.row()?)?.path.clone())
+        })
+        .flatten();
+    let current_file = siv
+        .with_user_data(|state: &mut State| state.current_file.clone())
+        .flatten();
+    let path = if is_editor_focused(siv) {
+        current_file.or(selected)
+    } else {
+        selected.or(current_file)
+    };
+    path.ok_or_else(|| Error::FileOpen("No file or directory is selected".to_string()))
+}
+
+/// Returns the path relative to the project, `None` if it's outside of it or the project itself
+fn relative_path(siv: &mut Cursive, path: &Path) -> Option<String> {
+    let project_path = siv
+        .with_user_data(|state: &mut State| state.project_path.clone())
+        .unwrap_or_default();
+    path.strip_prefix(project_path)
+        .ok()
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .map(|relative| relative.to_string_lossy().to_string())
+}
+
+/// Copies the path to the clipboard, showing it inside of the status bar
+fn copy_to_clipboard(siv: &mut Cursive, path: &str) {
+    match crate::clipboard::set_content(path.to_string()) {
+        Ok(()) => notify(siv, format!("Copied {path}")),
+        Err(e) => notify(siv, format!("Couldn't copy the path {path}: {e}")),
+    }
+}
+
+/// Shows the recent notifications, the newest first
+pub fn notifications(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("notifications") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let notifications = siv
+        .with_user_data(|state: &mut State| state.notifications.clone())
+        .unwrap_or_default();
+    let text = if notifications.is_empty() {
+        "No notifications yet".to_string()
+    } else {
+        Vec::from(notifications).join("\n")
+    };
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Notifications")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(ScrollView::new(TextView::new(text)))
+            .dismiss_button("Close")
+            .with_name("notifications"),
+    );
+    Ok(())
+}
+
+/// Lists suggestions for the misspelled word at the cursor, selecting one replaces the word
+///
+/// The word can also be added to the user dictionary, so it isn't underlined anymore.
+pub fn spelling(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("spelling") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let Some(dictionary) = siv
+        .with_user_data(|state: &mut State| state.dictionary.clone())
+        .flatten()
+    else {
+        notify(siv, "Spell checking is disabled");
+        return Ok(());
+    };
+    check_editable(siv)?;
+    let Some((range, word)) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area
+                .is_enabled()
+                .then(|| edit_area.misspelled_at_cursor())
+                .flatten()
+        })
+        .unwrap()
+    else {
+        notify(siv, "No misspelled word at the cursor");
+        return Ok(());
+    };
+
+    let mut select = SelectView::new();
+    for suggestion in dictionary.suggestions(&word) {
+        select.add_item(suggestion.clone(), Some(suggestion));
+    }
+    select.add_item(format!("Add `{word}` to the Dictionary"), None);
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title(format!("Spelling of {word}"))
+            .padding_lrtb(1, 1, 1, 0)
+            .content(ScrollView::new(select.on_submit(
+                move |siv, suggestion: &Option<String>| {
+                    siv.pop_layer();
+                    match suggestion {
+                        Some(suggestion) => {
+                            let callback = siv
+                                .call_on_name("editor", |edit_area: &mut EditArea| {
+                                    edit_area.replace_text(range.clone(), suggestion)
+                                })
+                                .unwrap();
+                            callback(siv);
+                        }
+                        None => add_to_dictionary(siv, &word).handle(siv),
+                    }
+                },
+            )))
+            .dismiss_button("Cancel")
+            .with_name("spelling"),
+    );
+    Ok(())
+}
+
+/// Adds the word to the user dictionary and updates the spell checking of the editor
+fn add_to_dictionary(siv: &mut Cursive, word: &str) -> Result<()> {
+    let Some(mut dictionary) = siv
+        .with_user_data(|state: &mut State| state.dictionary.take())
+        .flatten()
+    else {
+        return Ok(());
+    };
+    let result = Arc::make_mut(&mut dictionary).add(word);
+
+    siv.call_on_name("editor", |edit_area: &mut EditArea| {
+        edit_area.set_dictionary(Some(dictionary.clone()));
+    });
+    siv.with_user_data(|state: &mut State| state.dictionary = Some(dictionary));
+    result?;
+    notify(siv, format!("Added {word} to the dictionary"));
+    Ok(())
+}
+
+/// Returns an error if the current file can't be edited, like while following it
+fn check_editable(siv: &mut Cursive) -> Result<()> {
+    let read_only = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.is_read_only()
+        })
+        .unwrap();
+    let following = siv
+        .with_user_data(|state: &mut State| {
+            state.following.is_some() && state.following == state.current_file
+        })
+        .unwrap_or_default();
+    match (read_only, following) {
+        (false, _) => Ok(()),
+        (true, true) => Err(Error::Arguments(
+            "Editing is disabled while following the file".to_string(),
+        )),
+        (true, false) => Err(Error::Arguments(
+            "Only the beginning of the large file is loaded, it's read-only".to_string(),
+        )),
+    }
+}
+
+/// Time the changes of a followed file are collected for, before the new content is read
+const FOLLOW_DEBOUNCE: Duration = Duration::from_millis(200);
+/// Id of the latest followed file, so the content of older ones is dropped
+static FOLLOW_ID: AtomicUsize = AtomicUsize::new(0);
+/// Watcher of the followed file, dropping it stops following
+static FOLLOWER: Mutex<Option<Debouncer<RecommendedWatcher>>> = Mutex::new(None);
+
+/// Follows the current file like `tail -f`, content appended on disk is appended to the
+/// buffer and scrolled to
+///
+/// Editing is disabled while following, which is stopped by toggling it again. If the file
+/// is truncated, like on log rotation, it's reloaded.
+pub fn follow(siv: &mut Cursive) -> Result<()> {
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+    let Some(current_file) = state.current_file.clone() else {
+        return Ok(());
+    };
+    let name = current_file
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    if state.following.as_ref() == Some(&current_file) {
+        stop_following(siv);
+        notify(siv, format!("Stopped following {name}"));
+        return Ok(());
+    }
+    if state.is_current_file_edited() {
+        return Err(Error::Arguments(
+            "Save the file before following it".to_string(),
+        ));
+    }
+    if state.get_current_file().is_some_and(|file| file.read_only) {
+        return Err(Error::Arguments(
+            "Only the beginning of the large file is loaded, it can't be followed".to_string(),
+        ));
+    }
+    stop_following(siv);
+
+    // appended content is decoded like the file on disk is stored
+    let bytes = fs::read(&current_file)?;
+    let Some((_, format)) = encoding::decode(&bytes, true) else {
+        return Err(Error::Arguments("The file isn't a text file".to_string()));
+    };
+    let (content, len) = encoding::decode_appended(&bytes, format, true);
+    siv.with_user_data(|state: &mut State| {
+        if let Some(data) = state.files.get_mut(&current_file) {
+            data.text = Rope::from_str(&content);
+            data.format = format;
+            data.mark_saved();
+        }
+        state.following = Some(current_file.clone());
+    });
+    let callback = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.set_content(content);
+            edit_area.set_read_only(true);
+            edit_area.set_cursor_byte_offset(usize::MAX)
+        })
+        .unwrap();
+    callback(siv);
+
+    let id = FOLLOW_ID.fetch_add(1, Ordering::Relaxed) + 1;
+    let cb_sink = siv.cb_sink().clone();
+    let path = current_file.clone();
+    let mut offset = len as u64;
+    let mut debouncer = new_debouncer(FOLLOW_DEBOUNCE, move |events: DebounceEventResult| {
+        let changed = match events {
+            Ok(events) => events.iter().any(|event| event.path == path),
+            Err(e) => {
+                warn!("The followed file couldn't be watched: {e}");
+                false
+            }
+        };
+        if !changed {
+            return;
+        }
+        let Ok(size) = fs::metadata(&path).map(|metadata| metadata.len()) else {
+            return;
+        };
+        if size == offset {
+            return;
+        }
+        // a truncated file is read again from the start
+        let reload = size < offset;
+        if reload {
+            offset = 0;
+        }
+        let Ok(appended) = read_from(&path, offset) else {
+            return;
+        };
+        let (text, len) = encoding::decode_appended(&appended, format, offset == 0);
+        offset += len as u64;
+
+        let path = path.clone();
+        // the app may have been quit in the meantime, which drops the watcher as well
+        let _ = cb_sink.send(Box::new(move |siv| {
+            append_followed(siv, &path, &text, reload, id);
+        }));
+    })?;
+    // the directory is watched, so a file replaced on log rotation is still followed
+    let dir = current_file.parent().unwrap_or(&current_file);
+    debouncer
+        .watcher()
+        .watch(dir, RecursiveMode::NonRecursive)?;
+    *FOLLOWER.lock().unwrap() = Some(debouncer);
+
+    notify(siv, format!("Following {name}, editing is disabled"));
+    Ok(())
+}
+
+/// Stops following a file, making it editable again
+fn stop_following(siv: &mut Cursive) {
+    FOLLOW_ID.fetch_add(1, Ordering::Relaxed);
+    FOLLOWER.lock().unwrap().take();
+    siv.with_user_data(|state: &mut State| state.following = None);
+    siv.call_on_name("editor", |edit_area: &mut EditArea| {
+        edit_area.set_read_only(false);
+    });
+}
+
+/// Appends new content of the followed file to its buffer, a reload replaces the buffer
+fn append_followed(siv: &mut Cursive, path: &Path, text: &str, reload: bool, id: usize) {
+    if FOLLOW_ID.load(Ordering::Relaxed) != id {
+        return;
+    }
+    let Some((content, is_current)) = siv
+        .with_user_data(|state: &mut State| {
+            let data = state.files.get_mut(path)?;
+            if reload {
+                data.text = Rope::new();
+            }
+            data.text.append(Rope::from_str(text));
+            data.mark_saved();
+            Some((
+                reload.then(|| data.str()),
+                state.current_file.as_deref() == Some(path),
+            ))
+        })
+        .flatten()
+    else {
+        return;
+    };
+
+    if is_current {
+        let callback = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                if let Some(content) = content {
+                    edit_area.set_content(content);
+                    edit_area.set_cursor_byte_offset(usize::MAX)
+                } else {
+                    edit_area.append(text)
+                }
+            })
+            .unwrap();
+        callback(siv);
+    }
+}
+
+/// Reads the content of a file behind the byte offset
+fn read_from(path: &Path, offset: u64) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Jumps to the next or previous error location mentioned by compiler output
+///
+/// Inside of a file mentioning locations, like pasted compiler output, the one behind or
+/// before the cursor is jumped to and the file is remembered. Inside of other files, like the
+/// one jumped to, the locations of the remembered file are continued. Locations of files which
+/// don't exist are skipped.
+pub fn jump_to_error(siv: &mut Cursive, forward: bool) -> Result<()> {
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+    let current = state
+        .current_file
+        .clone()
+        .zip(state.get_current_file())
+        .map(|(path, file)| (path, locations::parse(&file.str()), file.cursor.row))
+        .filter(|(_, locations, _)| !locations.is_empty());
+
+    let (output, locations, candidates) = if let Some((output, locations, row)) = current {
+        // the location jumped to last is at the cursor, so it's stepped over
+        let last = state
+            .error_output
+            .as_ref()
+            .filter(|(path, _)| *path == output)
+            .map(|&(_, index)| index)
+            .filter(|&index| {
+                locations.get(index).map(|location| location.output_line) == Some(row)
+            });
+        let candidates = match (last, forward) {
+            (Some(last), true) => (last + 1..locations.len()).collect::<Vec<_>>(),
+            (Some(last), false) => (0..last).rev().collect(),
+            (None, true) => (0..locations.len())
+                .filter(|&i| locations[i].output_line >= row)
+                .collect(),
+            (None, false) => (0..locations.len())
+                .rev()
+                .filter(|&i| locations[i].output_line <= row)
+                .collect(),
+        };
+        (output, locations, candidates)
+    } else if let Some((output, last)) = state.error_output.clone() {
+        let content = match state.get_file(&output) {
+            Some(file) => file.str(),
+            None => fs::read_to_string(&output)?,
+        };
+        let locations = locations::parse(&content);
+        let candidates = if forward {
+            (last + 1..locations.len()).collect::<Vec<_>>()
+        } else {
+            (0..last.min(locations.len())).rev().collect()
+        };
+        (output, locations, candidates)
+    } else {
+        return Err(Error::Arguments(
+            "No error locations found, open compiler output first".to_string(),
+        ));
+    };
+
+    // relative paths are relative to the project or to the output
+    let resolve = |path: &str| {
+        let path = Path::new(path);
+        [
+            state.project_path.join(path),
+            output.parent().unwrap_or(Path::new("")).join(path),
+        ]
+        .into_iter()
+        .find(|path| path.is_file())
+    };
+    let Some((index, target)) = candidates
+        .into_iter()
+        .find_map(|i| Some((i, resolve(&locations[i].path)?)))
+    else {
+        notify(
+            siv,
+            if forward {
+                "No further error location"
+            } else {
+                "No previous error location"
+            },
+        );
+        return Ok(());
+    };
+    let location = &locations[index];
+
+    // the output keeps its cursor at the location, so continuing from it there works
+    if state.current_file.as_ref() == Some(&output) {
+        let callback = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                edit_area.set_cursor_row(location.output_line)
+            })
+            .unwrap();
+        callback(siv);
+    }
+    siv.with_user_data(|state: &mut State| state.error_output = Some((output.clone(), index)));
+
+    open_file(siv, &target)?;
+    let callback = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.set_cursor_row_column(
+                location.line - 1,
+                location.column.unwrap_or(1).saturating_sub(1),
+            )
+        })
+        .unwrap();
+    callback(siv);
+    notify(
+        siv,
+        format!(
+            "Error location {} of {}: {}:{}",
+            index + 1,
+            locations.len(),
+            location.path,
+            location.line
+        ),
+    );
+    Ok(())
+}
 
-    let data = if let Some(other) = other {
-        Some(other)
+/// Switches the focus between the file tree and the editor
+pub fn switch_focus(siv: &mut Cursive) -> Result<()> {
+    let target = if is_editor_focused(siv) {
+        "tree"
     } else {
-        current_file
+        "editor"
+    };
+
+    // the editor can't be focused if no file is opened
+    if let Ok(result) = siv.focus_name(target) {
+        result.process(siv);
+    }
+    update_focus(siv);
+
+    Ok(())
+}
+
+/// Shows a dialog, once it's closed the focused panel is checked again
+///
+/// The panel focused before the dialog keeps the focus, unless it can't be focused anymore, like
+/// the editor after its file was closed or deleted, then the tree is focused instead. The titles
+/// and borders of the panels are updated, so they show the focus correctly.
+pub fn add_dialog<V: IntoBoxedView>(siv: &mut Cursive, dialog: V) {
+    let cb_sink = siv.cb_sink().clone();
+    siv.add_layer(RestoreFocus {
+        dialog: BoxedView::boxed(dialog),
+        cb_sink,
+    });
+}
+
+/// Wraps a dialog, restoring the focus of the panels once it's removed
+struct RestoreFocus {
+    dialog: BoxedView,
+    cb_sink: CbSink,
+}
+
+impl ViewWrapper for RestoreFocus {
+    type V = BoxedView;
+
+    fn with_view<F: FnOnce(&Self::V) -> R, R>(&self, f: F) -> Option<R> {
+        Some(f(&self.dialog))
+    }
+
+    fn with_view_mut<F: FnOnce(&mut Self::V) -> R, R>(&mut self, f: F) -> Option<R> {
+        Some(f(&mut self.dialog))
+    }
+}
+
+impl Drop for RestoreFocus {
+    fn drop(&mut self) {
+        // the app may have been quit in the meantime
+        let _ = self.cb_sink.send(Box::new(restore_focus));
+    }
+}
+
+/// Focuses the tree if the editor has the focus but can't take it anymore
+fn restore_focus(siv: &mut Cursive) {
+    // other dialogs are still shown, they restore the focus once they're closed
+    if siv.screen().len() > 1 {
+        return;
+    }
+    let editor_enabled = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| edit_area.is_enabled())
+        .unwrap_or_default();
+    if is_editor_focused(siv) && !editor_enabled {
+        if let Ok(result) = siv.focus_name("tree") {
+            result.process(siv);
+        }
+    }
+    update_focus(siv);
+}
+
+/// Lists all lines of the project files containing a marker like `TODO`
+///
+/// Selecting one opens the file at that line.
+pub fn markers(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("markers") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let state = siv
+        .with_user_data(|state: &mut State| state.clone())
+        .unwrap();
+
+    let mut found = Vec::new();
+    collect_markers(
+        &state.project_path,
+        &state.config,
+        &mut HashSet::new(),
+        &mut found,
+    );
+
+    let mut select = SelectView::new();
+    for (path, row, line) in found {
+        let relative = path.strip_prefix(&state.project_path).unwrap_or(&path);
+        let label = format!(
+            "{}:{}: {}",
+            relative.to_string_lossy(),
+            row + 1,
+            line.trim()
+        );
+        select.add_item(label, (path, row));
+    }
+    if select.is_empty() {
+        select.add_item("No markers found", (PathBuf::new(), 0));
+    }
+
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Markers")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(ScrollView::new(select.on_submit(
+                |siv, (path, row): &(PathBuf, usize)| {
+                    if path.as_os_str().is_empty() {
+                        return;
+                    }
+                    if let Err(e) = open_file(siv, path) {
+                        e.to_dialog(siv);
+                        return;
+                    }
+                    let callback = siv
+                        .call_on_name("editor", |edit_area: &mut EditArea| {
+                            edit_area.set_cursor_row(*row)
+                        })
+                        .unwrap();
+                    callback(siv);
+                    siv.pop_layer();
+                },
+            )))
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("markers"),
+    );
+
+    Ok(())
+}
+
+/// Maximal number of lines listed by `markers`
+const MAX_MARKERS: usize = 1000;
+
+/// Recursively collects all lines containing a marker, skipping hidden and build directories
+fn collect_markers(
+    dir: &Path,
+    config: &Config,
+    visited: &mut HashSet<PathBuf>,
+    found: &mut Vec<(PathBuf, usize, String)>,
+) {
+    // every directory is only searched once, so linked directories can't cause cycles
+    if !visited.insert(dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
     };
 
-    if let Some(data) = data {
-        let old_content = fs::read_to_string(data.0)?;
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        if found.len() >= MAX_MARKERS {
+            return;
+        }
+
+        if file_tree::is_ignored(&path) {
+            continue;
+        }
 
-        if &old_content != data.1 {
-            // just write when something really changed
-            fs::write(data.0.clone(), data.1)?;
+        if path.is_dir() {
+            if config.follow_symlinks || !path.is_symlink() {
+                collect_markers(&path, config, visited, found);
+            }
+        } else if !visited.insert(path.canonicalize().unwrap_or_else(|_| path.clone())) {
+            // linked files are only listed once
+            continue;
+        } else if let Ok(content) = fs::read_to_string(&path) {
+            let lines = content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| {
+                    config
+                        .markers
+                        .iter()
+                        .any(|marker| line.contains(marker.as_str()))
+                })
+                .map(|(row, line)| (path.clone(), row, line.to_string()));
+            found.extend(lines.take(MAX_MARKERS - found.len()));
         }
+    }
+}
+
+/// Maximal number of files opened by `open_matching`
+const MAX_MATCHING: usize = 100;
+
+/// Opens all text files inside of the directory of the selected tree entry matching a glob
+/// pattern like `**/*.rs`
+///
+/// Hidden and build directories are skipped like for the markers. The first match becomes
+/// the current file, the others can be switched to via goto.
+pub fn open_matching(siv: &mut Cursive) -> Result<()> {
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("open_matching") {
+        siv.screen_mut().remove_layer(pos);
+        return Ok(());
+    }
+
+    let dir = selected_dir(siv);
+    let submit_dir = dir.clone();
+    let confirm_dir = dir.clone();
+    add_dialog(
+        siv,
+        Dialog::new()
+            .title("Open Matching Files")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                LinearLayout::vertical()
+                    .child(TextView::new(format!(
+                        "Files inside of {} matching",
+                        dir.to_string_lossy()
+                    )))
+                    .child(TextView::new(" "))
+                    .child(
+                        EditView::new()
+                            .content("**/*")
+                            .on_submit(move |siv, pattern| {
+                                open_matches(siv, &submit_dir, pattern).handle(siv);
+                            })
+                            .with_name("matching_pattern"),
+                    ),
+            )
+            .button("Confirm", move |siv| {
+                let pattern = siv
+                    .call_on_name("matching_pattern", |view: &mut EditView| view.get_content())
+                    .unwrap();
+                open_matches(siv, &confirm_dir, &pattern).handle(siv);
+            })
+            .dismiss_button("Cancel")
+            .full_width()
+            .with_name("open_matching"),
+    );
+    Ok(())
+}
+
+/// Opens the files inside of the directory matching the pattern and reports their number
+fn open_matches(siv: &mut Cursive, dir: &Path, pattern: &str) -> Result<()> {
+    let config = siv
+        .with_user_data(|state: &mut State| state.config.clone())
+        .unwrap_or_default();
 
-        update_title(siv, None, data.0);
+    let mut found = Vec::new();
+    collect_matching(dir, dir, pattern, &config, &mut HashSet::new(), &mut found);
+    if found.is_empty() {
+        notify(siv, format!("No text files match {pattern}"));
+        return Ok(());
+    }
+    let skipped = found.len() > MAX_MATCHING;
+    found.truncate(MAX_MATCHING);
 
-        state.files_edited.remove(data.0);
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("open_matching") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    // the first match is opened last, so it becomes the current file
+    for file in found.iter().rev() {
+        open_file(siv, file)?;
+    }
 
-        siv.set_user_data(state);
+    let mut message = format!("Opened {} files matching {pattern}", found.len());
+    if skipped {
+        message.push_str(&format!(", the others were skipped after {MAX_MATCHING}"));
     }
+    notify(siv, message);
     Ok(())
 }
+
+/// Recursively collects the text files matching the pattern relative to `base`, at most one
+/// more than `MAX_MATCHING`
+fn collect_matching(
+    dir: &Path,
+    base: &Path,
+    pattern: &str,
+    config: &Config,
+    visited: &mut HashSet<PathBuf>,
+    found: &mut Vec<PathBuf>,
+) {
+    // every directory is only searched once, so linked directories can't cause cycles
+    if !visited.insert(dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        if found.len() > MAX_MATCHING {
+            return;
+        }
+        if file_tree::is_ignored(&path) {
+            continue;
+        }
+
+        if path.is_dir() {
+            if config.follow_symlinks || !path.is_symlink() {
+                collect_matching(&path, base, pattern, config, visited, found);
+            }
+        } else {
+            let relative = path.strip_prefix(base).unwrap_or(&path);
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if glob::matches(pattern, &relative) && matches!(read_text(&path, None), Ok(Some(_))) {
+                found.push(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cursive::{
+        event::Event,
+        views::{Panel, ResizedView, ThemedView},
+        View,
+    };
+    use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
+
+    use super::*;
+    use crate::testing::temp_dir;
+
+    /// Opens the file like [`open_file`] does, but without the editor
+    fn open(siv: &mut Cursive, path: &Path) -> String {
+        let (content, format) = read_text(path, None).unwrap().unwrap();
+        siv.with_user_data(|state: &mut State| {
+            state.files.insert(
+                path.to_path_buf(),
+                FileData {
+                    saved_hash: content_hash(&content),
+                    saved_len: content.len(),
+                    text: Rope::from_str(&content),
+                    format,
+                    ..Default::default()
+                },
+            );
+        });
+        content
+    }
+
+    #[test]
+    fn saving_without_edits_keeps_the_fixtures() {
+        let dir = temp_dir("fixtures");
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        for entry in fs::read_dir(fixtures).unwrap() {
+            let fixture = entry.unwrap().path();
+            let path = dir.join(fixture.file_name().unwrap());
+            fs::copy(&fixture, &path).unwrap();
+
+            let mut siv = Cursive::new();
+            siv.set_user_data(State::default());
+            let content = open(&mut siv, &path);
+            save_file(&mut siv, &path, &content, false).unwrap();
+            assert_eq!(fs::read(&path).unwrap(), fs::read(&fixture).unwrap());
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn saving_edits_keeps_the_format() {
+        let dir = temp_dir("format");
+        let path = dir.join("crlf.txt");
+        fs::write(&path, b"\xef\xbb\xbfa\r\nb\r\n").unwrap();
+
+        let mut siv = Cursive::new();
+        siv.set_user_data(State::default());
+        let content = open(&mut siv, &path);
+        assert_eq!(content, "a\nb\n");
+        save_file(&mut siv, &path, "a\nnew\nb\n", false).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"\xef\xbb\xbfa\r\nnew\r\nb\r\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn backup_errors_are_kept_in_the_notifications() {
+        let dir = temp_dir("backup_error");
+        let path = dir.join("file.txt");
+        fs::write(&path, "content").unwrap();
+        // a directory in place of the backup can't be replaced
+        fs::create_dir(dir.join("file.txt~")).unwrap();
+
+        let mut siv = Cursive::new();
+        siv.set_user_data(State::default());
+        siv.with_user_data(|state: &mut State| state.config.backup = true);
+        open(&mut siv, &path);
+        save_file(&mut siv, &path, "edited", false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "edited");
+        let notifications = siv
+            .with_user_data(|state: &mut State| state.notifications.clone())
+            .unwrap();
+        assert!(notifications[1].starts_with("Couldn't save the backup"));
+        assert_eq!(notifications[0], "Saved file.txt");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Returns the app with the panels of the editor and the tree showing the project
+    fn app(project: &Path) -> Cursive {
+        let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+        let edit_area = EditArea::new(Arc::new(SyntaxSet::load_defaults_newlines()), theme);
+        let mut siv = Cursive::new();
+        let theme = siv.current_theme().clone();
+        let editor_panel = Panel::new(ThemedView::new(
+            theme.clone(),
+            edit_area.with_name("editor").full_screen(),
+        ))
+        .with_name("editor_title");
+        let file_tree_panel = Panel::new(ThemedView::new(
+            theme,
+            file_tree::new(&project.to_path_buf(), false),
+        ))
+        .fixed_width(40)
+        .with_name("tree_title");
+        siv.add_layer(
+            LinearLayout::vertical()
+                .child(file_tree_panel)
+                .child(editor_panel)
+                .child(TextView::new("").with_name("status")),
+        );
+        siv.set_user_data(State::default().open_new_project(project, None));
+        siv
+    }
+
+    /// Shows the dialog for creating a path and presses the button with the path entered
+    fn create(siv: &mut Cursive, path: &Path, button: &str) {
+        new(siv).unwrap();
+        siv.call_on_name("new_path_edit", |view: &mut EditView| {
+            view.set_content(path.to_string_lossy());
+        })
+        .unwrap();
+        siv.call_on_name("new", |dialog: &mut ResizedView<Dialog>| {
+            let button = dialog
+                .get_inner_mut()
+                .buttons_mut()
+                .find(|other| other.label().contains(button));
+            button.unwrap().on_event(Event::Key(Key::Enter))
+        })
+        .unwrap()
+        .process(siv);
+    }
+
+    #[test]
+    fn new_paths_dont_replace_existing_ones() {
+        let dir = temp_dir("new");
+        let file = dir.join("file.txt");
+        fs::write(&file, "content").unwrap();
+        let mut siv = Cursive::new();
+        siv.set_user_data(State::default());
+
+        // an existing file is only replaced after asking
+        create(&mut siv, &file, "A File");
+        assert_eq!(siv.screen().len(), 2);
+        assert!(siv.find_name::<Dialog>("error").is_none());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "content");
+        while siv.pop_layer().is_some() {}
+
+        // a directory can't be replaced by a file, a file can't be replaced by a directory
+        create(&mut siv, &dir, "A File");
+        assert!(siv.find_name::<Dialog>("error").is_some());
+        assert!(dir.is_dir());
+        while siv.pop_layer().is_some() {}
+        create(&mut siv, &file, "A Directory");
+        assert!(siv.find_name::<Dialog>("error").is_some());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "content");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn renaming_onto_a_file_drops_its_buffer() {
+        let dir = temp_dir("rename-file").canonicalize().unwrap();
+        let (from, to) = (dir.join("from.txt"), dir.join("to.txt"));
+        fs::write(&from, "from").unwrap();
+        fs::write(&to, "to").unwrap();
+        let mut siv = app(&dir);
+        open(&mut siv, &from);
+        open(&mut siv, &to);
+        siv.with_user_data(|state: &mut State| state.files_edited.insert(to.clone(), true));
+
+        rename_path(&mut siv, &from, &to).unwrap();
+        assert_eq!(fs::read_to_string(&to).unwrap(), "from");
+        let state = siv.take_user_data::<State>().unwrap();
+        assert_eq!(state.files.len(), 1);
+        assert_eq!(state.files[&to].str(), "from");
+        assert!(state.files_edited.is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn renaming_a_directory_moves_the_buffers_inside() {
+        let dir = temp_dir("rename-dir").canonicalize().unwrap();
+        fs::create_dir_all(dir.join("from/sub")).unwrap();
+        fs::write(dir.join("from/sub/file.txt"), "content").unwrap();
+        fs::write(dir.join("other.txt"), "other").unwrap();
+        let mut siv = app(&dir);
+        open(&mut siv, &dir.join("from/sub/file.txt"));
+        open(&mut siv, &dir.join("other.txt"));
+        siv.with_user_data(|state: &mut State| {
+            state
+                .files_edited
+                .insert(dir.join("from/sub/file.txt"), true)
+        });
+
+        rename_path(&mut siv, &dir.join("from"), &dir.join("to")).unwrap();
+        let moved = dir.join("to/sub/file.txt");
+        let state = siv.take_user_data::<State>().unwrap();
+        assert_eq!(state.files.len(), 2);
+        assert_eq!(state.files[&moved].str(), "content");
+        assert_eq!(state.files[&dir.join("other.txt")].str(), "other");
+        assert_eq!(state.files_edited.keys().collect::<Vec<_>>(), [&moved]);
+        assert_eq!(state.project_path, dir);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn following_keeps_crlf_line_endings() {
+        let dir = temp_dir("follow");
+        let path = dir.join("log.txt");
+        fs::write(&path, b"a\r\n").unwrap();
+        let mut siv = app(&dir);
+        open(&mut siv, &path);
+        siv.with_user_data(|state: &mut State| state.current_file = Some(path.clone()));
+        follow(&mut siv).unwrap();
+
+        let buffer = |siv: &mut Cursive| {
+            siv.with_user_data(|state: &mut State| state.files[&path].str())
+                .unwrap()
+        };
+        assert_eq!(buffer(&mut siv), "a\n");
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"b\r\n")
+            .unwrap();
+        // the appended content arrives through the callbacks of the watcher
+        let mut runner = siv.runner(cursive::backends::puppet::Backend::init(None));
+        let start = std::time::Instant::now();
+        while buffer(&mut runner) != "a\nb\n" && start.elapsed() < Duration::from_secs(5) {
+            runner.step();
+            thread::sleep(Duration::from_millis(20));
+        }
+        stop_following(&mut runner);
+        assert_eq!(buffer(&mut runner), "a\nb\n");
+
+        // the buffer matches the file, so it isn't seen as changed on disk
+        save_file(&mut runner, &path, "a\nb\nc\n", false).unwrap();
+        assert!(runner.find_name::<Dialog>("external_changes").is_none());
+        assert_eq!(fs::read(&path).unwrap(), b"a\r\nb\r\nc\r\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn closing_keeps_buffers_which_werent_saved() {
+        let dir = temp_dir("close");
+        let path = dir.join("file.txt");
+        fs::write(&path, "content").unwrap();
+        let mut siv = app(&dir);
+        open(&mut siv, &path);
+        siv.with_user_data(|state: &mut State| {
+            state.current_file = Some(path.clone());
+            state.files.get_mut(&path).unwrap().text = Rope::from_str("edited");
+            state.files_edited.insert(path.clone(), true);
+        });
+
+        // the external changes are shown first, the buffer must stay for overwriting them
+        fs::write(&path, "changed").unwrap();
+        save_and_close(&mut siv, &path);
+        assert!(siv.find_name::<Dialog>("external_changes").is_some());
+        assert!(siv
+            .with_user_data(|state: &mut State| state.files.contains_key(&path))
+            .unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "changed");
+
+        fs::write(&path, "content").unwrap();
+        save_and_close(&mut siv, &path);
+        assert!(!siv
+            .with_user_data(|state: &mut State| state.files.contains_key(&path))
+            .unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "edited");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn quitting_without_asking_keeps_the_main_layer() {
+        let dir = temp_dir("quit");
+        let mut siv = app(&dir);
+        siv.with_user_data(|state: &mut State| {
+            state.config.confirmations.quit_unsaved = crate::config::Confirm::Never;
+        });
+        open_scratch(&mut siv, "scratch".to_string(), String::new());
+        siv.with_user_data(|state: &mut State| {
+            state.scratches[0].1.text = Rope::from_str("edited");
+        });
+
+        quit(&mut siv).unwrap();
+        assert!(siv.screen_mut().find_layer_from_name("save_as").is_some());
+        assert!(siv.find_name::<TextView>("status").is_some());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn saving_without_a_file_does_nothing() {
+        let mut siv = Cursive::new();
+        siv.set_user_data(State::default());
+        save(&mut siv, None).unwrap();
+        save_all(&mut siv).unwrap();
+        let state = siv.take_user_data::<State>().unwrap();
+        assert!(state.files.is_empty() && state.files_edited.is_empty());
+        assert_eq!(state.current_file, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn markers_are_collected_once_inside_of_link_cycles() {
+        use std::os::unix::fs::symlink;
+
+        let dir = temp_dir("markers");
+        fs::create_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/file.rs"), "// TODO: once\n").unwrap();
+        symlink(&dir, dir.join("sub/parent")).unwrap();
+        symlink("file.rs", dir.join("sub/link.rs")).unwrap();
+
+        let config = Config {
+            markers: vec!["TODO".to_string()],
+            ..Default::default()
+        };
+        let mut found = Vec::new();
+        collect_markers(&dir, &config, &mut HashSet::new(), &mut found);
+        assert_eq!(found.len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_atomically_replaces_the_file() {
+        let dir = temp_dir("replace");
+        let path = dir.join("file.txt");
+        write_atomically(&path, b"new").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o751)).unwrap();
+        }
+        write_atomically(&path, b"replaced").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"replaced");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o751);
+        }
+        // no temporary file is left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomically_keeps_links() {
+        let dir = temp_dir("write-links");
+        let target = dir.join("target.txt");
+        fs::write(&target, "old").unwrap();
+
+        let symlink = dir.join("symlink.txt");
+        std::os::unix::fs::symlink(&target, &symlink).unwrap();
+        write_atomically(&symlink, b"via symlink").unwrap();
+        assert!(fs::symlink_metadata(&symlink).unwrap().is_symlink());
+        assert_eq!(fs::read(&target).unwrap(), b"via symlink");
+
+        let hard_link = dir.join("hard_link.txt");
+        fs::hard_link(&target, &hard_link).unwrap();
+        write_atomically(&hard_link, b"via hard link").unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"via hard link");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn only_errors_of_replacing_fall_back() {
+        let error = |kind| io::Error::new(kind, "");
+        assert!(is_not_replaceable_error(&error(
+            ErrorKind::PermissionDenied
+        )));
+        assert!(!is_not_replaceable_error(&error(ErrorKind::NotFound)));
+        assert!(!is_not_replaceable_error(&error(ErrorKind::Other)));
+        #[cfg(unix)]
+        {
+            let os_error = io::Error::from_raw_os_error;
+            assert!(is_not_replaceable_error(&os_error(libc::EROFS)));
+            assert!(is_not_replaceable_error(&os_error(libc::EXDEV)));
+            assert!(!is_not_replaceable_error(&os_error(libc::ENOSPC)));
+            assert!(!is_not_replaceable_error(&os_error(libc::EIO)));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn write_atomically_doesnt_truncate_on_write_errors() {
+        // writing always fails with a full disk
+        let full = Path::new("/dev/full");
+        if !full.exists() {
+            return;
+        }
+        let dir = temp_dir("full");
+        let path = dir.join("file.txt");
+        fs::write(&path, "old").unwrap();
+        let error = replace_with_temp(&path, full, b"new", None).unwrap_err();
+        assert_eq!(error.raw_os_error(), Some(libc::ENOSPC));
+        assert_eq!(fs::read(&path).unwrap(), b"old");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,40 @@
+/// Returns `true` if the relative path, separated by `/`, matches the glob pattern
+///
+/// `*` matches any chars except `/`, `?` a single one and `**` any number of directories.
+/// A pattern without a `/`, like `*.rs`, is matched against the file name at any depth.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./");
+    if !pattern.contains('/') {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        return matches_name(&chars(pattern), &chars(name));
+    }
+
+    let pattern = pattern.split('/').collect::<Vec<_>>();
+    let path = path.split('/').collect::<Vec<_>>();
+    matches_segments(&pattern, &path)
+}
+
+/// Matches the directories and the file name of a path, `**` stands for any number of them
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| matches_segments(rest, &path[i..])),
+        Some((segment, rest)) => path.split_first().is_some_and(|(name, path)| {
+            matches_name(&chars(segment), &chars(name)) && matches_segments(rest, path)
+        }),
+    }
+}
+
+/// Matches a single directory or file name
+fn matches_name(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| matches_name(rest, &name[i..])),
+        Some(('?', rest)) => !name.is_empty() && matches_name(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && matches_name(rest, &name[1..]),
+    }
+}
+
+fn chars(text: &str) -> Vec<char> {
+    text.chars().collect()
+}
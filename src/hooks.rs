@@ -0,0 +1,83 @@
+use std::{path::Path, process::Stdio, thread};
+
+use cursive::Cursive;
+
+use crate::{
+    app::State,
+    error::{Error, Result},
+    events,
+    ui::notify,
+};
+
+/// The events of a file the configured hooks are run on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// The file was loaded into a new buffer
+    Open,
+    /// The file is about to be written
+    BeforeSave,
+    /// The file was written
+    AfterSave,
+}
+
+/// Runs the commands configured for the hook in the background, the path of the file is passed
+/// via the `FILE` environment variable
+///
+/// The commands run one after another inside of the project directory, or the directory of a
+/// file outside of it, so they neither block the editor nor the save. Failing commands are shown inside of the status bar.
+pub fn run(siv: &mut Cursive, hook: Hook, path: &Path) {
+    let (commands, dir) = siv
+        .with_user_data(|state: &mut State| {
+            let hooks = &state.config.hooks;
+            let commands = match hook {
+                Hook::Open => &hooks.open,
+                Hook::BeforeSave => &hooks.before_save,
+                Hook::AfterSave => &hooks.after_save,
+            };
+            // while opening the paths given at startup there is no project yet
+            let dir = if state.project_path.is_dir() && path.starts_with(&state.project_path) {
+                state.project_path.clone()
+            } else {
+                path.parent().unwrap_or(path).to_path_buf()
+            };
+            (commands.clone(), dir)
+        })
+        .unwrap_or_default();
+    if commands.is_empty() {
+        return;
+    }
+
+    let path = path.to_path_buf();
+    let cb_sink = siv.cb_sink().clone();
+    thread::spawn(move || {
+        for command in commands {
+            if let Err(e) = run_command(&command, &path, &dir) {
+                // the app may have been quit in the meantime
+                let _ = cb_sink.send(Box::new(move |siv| notify(siv, e.to_string())));
+            }
+        }
+    });
+}
+
+/// Runs a hook command for the file, a nonzero exit status is an error containing the last line
+/// of its error output
+fn run_command(command: &str, path: &Path, dir: &Path) -> Result<()> {
+    let output = events::shell(command)
+        .env("FILE", path)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let reason = stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map_or_else(|| output.status.to_string(), |line| line.trim().to_string());
+    Err(Error::Command(format!("Hook {command} failed: {reason}")))
+}
@@ -0,0 +1,169 @@
+/// A location inside of a file mentioned by compiler output, like `src/main.rs:10:5`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Line of the output mentioning the location, starting at `0`
+    pub output_line: usize,
+    /// Path of the file as it's written
+    pub path: String,
+    /// Line inside of the file, starting at `1`
+    pub line: usize,
+    /// Column inside of the file, starting at `1`
+    pub column: Option<usize>,
+}
+
+/// Returns the locations mentioned by compiler output, in the order they're mentioned
+///
+/// Locations like `src/main.rs:10:5` are found anywhere in a line, like the ones of rustc
+/// behind `-->` or the ones of gcc and clang at the start. Locations of the stylish format of
+/// eslint, where the file stands on its own line and is followed by lines like `10:5  error`,
+/// are found as well. Only the first location of a line is returned.
+pub fn parse(output: &str) -> Vec<Location> {
+    let mut locations = Vec::new();
+    let mut file = None;
+    for (output_line, text) in output.lines().enumerate() {
+        let mut tokens = text.split_whitespace();
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+
+        if let Some((path, line, column)) = text.split_whitespace().find_map(parse_token) {
+            locations.push(Location {
+                output_line,
+                path,
+                line,
+                column,
+            });
+        } else if let (Some(path), Some((line, column))) = (&file, parse_position(first)) {
+            locations.push(Location {
+                output_line,
+                path: String::clone(path),
+                line,
+                column,
+            });
+        } else if tokens.next().is_none() && is_path(first) && !text.starts_with(' ') {
+            // the heading of the stylish format
+            file = Some(first.to_string());
+        }
+    }
+    locations
+}
+
+/// Parses a token like `src/main.rs:10:5:` into its path, line and column
+fn parse_token(token: &str) -> Option<(String, usize, Option<usize>)> {
+    let token = token
+        .trim_start_matches(['(', '[', '\'', '"', '`'])
+        .trim_end_matches([':', ',', ';', ')', ']', '\'', '"', '`']);
+    let mut parts = token.rsplitn(3, ':');
+    let last = parts.next()?.parse::<usize>().ok()?;
+    let middle = parts.next()?;
+    let (path, line, column) = match (middle.parse::<usize>(), parts.next()) {
+        (Ok(line), Some(path)) => (path, line, Some(last)),
+        _ => (token.rsplit_once(':')?.0, last, None),
+    };
+    (is_path(path) && line > 0).then(|| (path.to_string(), line, column))
+}
+
+/// Parses a position like `10:5` of the stylish format
+fn parse_position(token: &str) -> Option<(usize, Option<usize>)> {
+    let (line, column) = token.split_once(':')?;
+    Some((line.parse().ok()?, Some(column.parse().ok()?)))
+}
+
+/// Returns `true` for text looking like a path to a file, like `main.c` or `src/lib`
+///
+/// Numbers like the ones of timestamps and urls aren't paths.
+fn is_path(text: &str) -> bool {
+    !text.is_empty()
+        && !text.contains("://")
+        && text.contains(['.', '/', '\\'])
+        && !text.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(output_line: usize, path: &str, line: usize, column: Option<usize>) -> Location {
+        Location {
+            output_line,
+            path: path.to_string(),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn rustc_locations_are_found_behind_arrows() {
+        let output = "\
+error[E0425]: cannot find value `x` in this scope
+  --> src/main.rs:10:5
+   |
+10 |     x
+   |     ^ not found in this scope
+";
+        assert_eq!(parse(output), [location(1, "src/main.rs", 10, Some(5))]);
+    }
+
+    #[test]
+    fn gcc_locations_are_found_at_the_start() {
+        let output = "\
+main.c: In function 'main':
+main.c:3:12: error: expected ';' before '}' token
+lib/util.h:7: note: declared here
+";
+        assert_eq!(
+            parse(output),
+            [
+                location(1, "main.c", 3, Some(12)),
+                location(2, "lib/util.h", 7, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn eslint_locations_are_found_below_their_file() {
+        let output = "\
+/home/user/project/src/app.js
+   1:10  error  'foo' is defined but never used  no-unused-vars
+  12:3   warning  Unexpected console statement   no-console
+
+/home/user/project/src/util.js
+  4:1  error  Missing semicolon  semi
+
+✖ 3 problems (2 errors, 1 warning)
+";
+        assert_eq!(
+            parse(output),
+            [
+                location(1, "/home/user/project/src/app.js", 1, Some(10)),
+                location(2, "/home/user/project/src/app.js", 12, Some(3)),
+                location(5, "/home/user/project/src/util.js", 4, Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn windows_paths_keep_their_drive() {
+        let output = "\
+C:\\project\\src\\main.rs:10:5: error
+  --> C:\\project\\src\\lib.rs:3
+";
+        assert_eq!(
+            parse(output),
+            [
+                location(0, "C:\\project\\src\\main.rs", 10, Some(5)),
+                location(1, "C:\\project\\src\\lib.rs", 3, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn timestamps_and_urls_are_no_locations() {
+        let output = "\
+[12:30:45] Compiling at 2024-01-01T12:30:45
+Listening on http://localhost:8080
+See https://example.com:443/docs for 1.2.3:4
+";
+        assert!(parse(output).is_empty());
+    }
+}
@@ -3,10 +3,28 @@
 #![warn(clippy::map_unwrap_or)]
 #![warn(clippy::implicit_clone)]
 
+pub mod ansi;
 pub mod app;
+pub mod backup;
 pub mod clipboard;
+pub mod config;
+pub mod cursor;
+pub mod diff;
+pub mod encoding;
 pub mod error;
 pub mod events;
+pub mod glob;
+pub mod hooks;
+pub mod locations;
+pub mod paste;
+pub mod pinned;
+pub mod positions;
+pub mod recent;
+pub mod search;
+pub mod snippet;
+pub mod spell;
+#[cfg(test)]
+pub mod testing;
 pub mod ui;
 
 use cursive::logger::reserve_logs;
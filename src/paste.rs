@@ -0,0 +1,225 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use cursive::{
+    backend::Backend,
+    event::{Event, Key},
+    reexports::log::warn,
+    theme, Cursive, Vec2,
+};
+
+/// Set while the events of a bracketed paste are processed
+static PASTING: AtomicBool = AtomicBool::new(false);
+
+/// Prefix of the event a whole bracketed paste is passed on as, followed by the pasted text
+const PASTE_EVENT: &[u8] = b"\x1b[200~";
+
+/// Escape sequence the terminal sends before pasted text, without the leading `Esc`
+const PASTE_START: [char; 5] = ['[', '2', '0', '0', '~'];
+/// Escape sequence the terminal sends after pasted text, without the leading `Esc`
+const PASTE_END: [char; 5] = ['[', '2', '0', '1', '~'];
+
+/// Returns `true` if the current event is part of a bracketed paste
+///
+/// Pasted text should be inserted verbatim, so auto-pairs etc. should be skipped.
+pub fn is_pasting() -> bool {
+    PASTING.load(Ordering::Relaxed)
+}
+
+/// Returns the pasted text if the event is a whole bracketed paste
+pub fn pasted_text(event: &Event) -> Option<String> {
+    match event {
+        Event::Unknown(bytes) => bytes
+            .strip_prefix(PASTE_EVENT)
+            .map(|text| String::from_utf8_lossy(text).into_owned()),
+        _ => None,
+    }
+}
+
+/// Passes the pasted text to the focused view char by char, for views which can't take it at
+/// once like the inputs of dialogs
+///
+/// While the chars are passed on, [`is_pasting`] returns `true`.
+pub fn replay(siv: &mut Cursive, text: &str) {
+    PASTING.store(true, Ordering::Relaxed);
+    for c in text.chars() {
+        siv.on_event(match c {
+            '\n' => Event::Key(Key::Enter),
+            '\t' => Event::Key(Key::Tab),
+            c => Event::Char(c),
+        });
+    }
+    PASTING.store(false, Ordering::Relaxed);
+}
+
+/// Backend wrapper enabling bracketed paste mode of the terminal
+///
+/// The paste markers are removed from the event stream and the pasted text is passed on as a
+/// single event, so it can be inserted at once, see [`pasted_text`]. Terminals which don't
+/// support bracketed paste just ignore the mode, so pasting works like typing there.
+pub struct BracketedPaste<B: Backend> {
+    inner: B,
+    /// Events which were read while checking for a paste marker
+    pending: VecDeque<Event>,
+    /// Text of the bracketed paste which is read, until its end marker arrives
+    pasted: Option<String>,
+}
+
+impl<B: Backend> BracketedPaste<B> {
+    /// Wraps the backend and enables bracketed paste mode
+    pub fn new(inner: B) -> Self {
+        set_mode(true);
+        Self {
+            inner,
+            pending: VecDeque::new(),
+            pasted: None,
+        }
+    }
+
+    /// Tries to read the rest of a paste marker after an `Esc`
+    ///
+    /// If it doesn't match, all read events are kept for later.
+    fn read_marker(&mut self) -> Option<bool> {
+        let mut read = Vec::new();
+        let mut start = true;
+        let mut end = true;
+
+        for (start_char, end_char) in PASTE_START.into_iter().zip(PASTE_END) {
+            match self.inner.poll_event() {
+                Some(Event::Char(c)) => {
+                    start &= c == start_char;
+                    end &= c == end_char;
+                    read.push(Event::Char(c));
+                }
+                Some(event) => {
+                    read.push(event);
+                    start = false;
+                    end = false;
+                }
+                None => {
+                    start = false;
+                    end = false;
+                }
+            }
+            if !start && !end {
+                break;
+            }
+        }
+
+        if start || end {
+            Some(start)
+        } else {
+            self.pending.extend(read);
+            None
+        }
+    }
+}
+
+impl<B: Backend> Drop for BracketedPaste<B> {
+    fn drop(&mut self) {
+        set_mode(false);
+    }
+}
+
+impl<B: Backend> Backend for BracketedPaste<B> {
+    fn poll_event(&mut self) -> Option<Event> {
+        loop {
+            // a paste may arrive in parts, the text read so far is kept until the next poll
+            let event = match self.pending.pop_front() {
+                Some(event) => event,
+                None => self.inner.poll_event()?,
+            };
+            if event == Event::Key(Key::Esc) {
+                match self.read_marker() {
+                    Some(true) => {
+                        self.pasted = Some(String::new());
+                        continue;
+                    }
+                    Some(false) => match self.pasted.take() {
+                        Some(text) => {
+                            let mut bytes = PASTE_EVENT.to_vec();
+                            bytes.extend(text.into_bytes());
+                            return Some(Event::Unknown(bytes));
+                        }
+                        None => continue,
+                    },
+                    None => {}
+                }
+            }
+
+            let Some(pasted) = &mut self.pasted else {
+                return Some(event);
+            };
+            match event {
+                Event::Char(c) => pasted.push(c),
+                Event::Key(Key::Enter) => pasted.push('\n'),
+                Event::Key(Key::Tab) => pasted.push('\t'),
+                _ => {}
+            }
+        }
+    }
+
+    fn set_title(&mut self, title: String) {
+        self.inner.set_title(title);
+    }
+
+    fn refresh(&mut self) {
+        self.inner.refresh();
+    }
+
+    fn has_colors(&self) -> bool {
+        self.inner.has_colors()
+    }
+
+    fn screen_size(&self) -> Vec2 {
+        self.inner.screen_size()
+    }
+
+    fn print_at(&self, pos: Vec2, text: &str) {
+        self.inner.print_at(pos, text);
+    }
+
+    fn print_at_rep(&self, pos: Vec2, repetitions: usize, text: &str) {
+        self.inner.print_at_rep(pos, repetitions, text);
+    }
+
+    fn clear(&self, color: theme::Color) {
+        self.inner.clear(color);
+    }
+
+    fn set_color(&self, colors: theme::ColorPair) -> theme::ColorPair {
+        self.inner.set_color(colors)
+    }
+
+    fn set_effect(&self, effect: theme::Effect) {
+        self.inner.set_effect(effect);
+    }
+
+    fn unset_effect(&self, effect: theme::Effect) {
+        self.inner.unset_effect(effect);
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// Enables/Disables bracketed paste mode of the terminal
+fn set_mode(enabled: bool) {
+    let sequence = if enabled {
+        "\x1b[?2004h"
+    } else {
+        "\x1b[?2004l"
+    };
+    let mut stdout = io::stdout();
+    if stdout
+        .write_all(sequence.as_bytes())
+        .and_then(|_| stdout.flush())
+        .is_err()
+    {
+        warn!("Bracketed paste mode couldn't be changed");
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{config, error::Result};
+
+/// Loads the pinned files and directories of the project, in the order they were pinned
+///
+/// Paths which don't exist anymore are kept, so they can be shown as stale and removed.
+pub fn load(project: &Path) -> Vec<PathBuf> {
+    load_all().remove(project).unwrap_or_default()
+}
+
+/// Pins the path inside of the project, or removes it if it's already pinned
+///
+/// Returns `true` if the path is pinned afterwards.
+pub fn toggle(project: &Path, path: &Path) -> Result<bool> {
+    let mut all = load_all();
+    let pinned = all.entry(project.to_path_buf()).or_default();
+    let was_pinned = pinned.iter().any(|other| other == path);
+    if was_pinned {
+        pinned.retain(|other| other != path);
+    } else {
+        pinned.push(path.to_path_buf());
+    }
+    store(all)?;
+    Ok(!was_pinned)
+}
+
+/// Removes a pinned path of the project, like one which doesn't exist anymore
+pub fn remove(project: &Path, path: &Path) -> Result<()> {
+    let mut all = load_all();
+    if let Some(pinned) = all.get_mut(project) {
+        pinned.retain(|other| other != path);
+    }
+    store(all)
+}
+
+/// Loads the pinned paths of all projects
+fn load_all() -> HashMap<PathBuf, Vec<PathBuf>> {
+    let Some(content) = path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return HashMap::new();
+    };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+/// Stores the pinned paths of all projects, projects without any are dropped
+fn store(mut all: HashMap<PathBuf, Vec<PathBuf>>) -> Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    all.retain(|_, pinned| !pinned.is_empty());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&all)?)?;
+    Ok(())
+}
+
+/// The path of the file storing the pinned paths, inside of the config directory
+fn path() -> Option<PathBuf> {
+    config::dir().map(|dir| dir.join("pinned.json"))
+}
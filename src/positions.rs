@@ -0,0 +1,117 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config, error::Result};
+
+/// Maximal number of remembered positions
+const MAX_POSITIONS: usize = 500;
+
+/// The last cursor and scroll position inside of a file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    /// Byte offset of the cursor
+    pub byte_offset: usize,
+    /// Horizontal and vertical scroll offset
+    pub scroll: (usize, usize),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    file: PathBuf,
+    #[serde(flatten)]
+    position: Position,
+}
+
+/// Returns the remembered position of a file
+pub fn get(file: &Path) -> Option<Position> {
+    load()
+        .into_iter()
+        .find(|entry| entry.file == file)
+        .map(|entry| entry.position)
+}
+
+/// Remembers the positions of the files, they're moved to the front of the store
+///
+/// Only the most recent positions are kept.
+pub fn set<'a>(positions: impl IntoIterator<Item = (&'a Path, Position)>) -> Result<()> {
+    let mut entries = positions
+        .into_iter()
+        .map(|(file, position)| Entry {
+            file: file.to_path_buf(),
+            position,
+        })
+        .collect::<Vec<_>>();
+    let mut old = load();
+    old.retain(|entry| entries.iter().all(|new| new.file != entry.file));
+    entries.extend(old);
+    store(entries)
+}
+
+/// Forgets the positions of deleted files, only done when quitting as every file is checked
+///
+/// Files inside of directories which don't exist, like the ones of unmounted volumes, are kept.
+pub fn prune() -> Result<()> {
+    let mut entries = load();
+    let len = entries.len();
+    entries.retain(|entry| !is_deleted(&entry.file));
+    if entries.len() == len {
+        return Ok(());
+    }
+    store(entries)
+}
+
+/// Returns `true` if the file doesn't exist, but its directory does
+fn is_deleted(file: &Path) -> bool {
+    !file.is_file() && file.parent().is_some_and(Path::is_dir)
+}
+
+/// Loads the remembered positions, most recent first
+fn load() -> Vec<Entry> {
+    let Some(content) = path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+/// Writes the positions, only the most recent ones are kept
+fn store(mut entries: Vec<Entry>) -> Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+
+    entries.truncate(MAX_POSITIONS);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(&entries)?)?;
+    Ok(())
+}
+
+/// The path of the file storing the positions, inside of the config directory
+fn path() -> Option<PathBuf> {
+    config::dir().map(|dir| dir.join("positions.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn only_files_of_existing_directories_are_deleted() {
+        let dir = temp_dir("positions");
+        let file = dir.join("main.rs");
+        fs::write(&file, "").unwrap();
+        assert!(!is_deleted(&file));
+        assert!(is_deleted(&dir.join("deleted.rs")));
+        // like the files of an unmounted volume
+        assert!(!is_deleted(&dir.join("unmounted/main.rs")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
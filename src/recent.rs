@@ -0,0 +1,47 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{config, error::Result};
+
+/// Maximal number of remembered files
+const MAX_RECENT_FILES: usize = 10;
+
+/// Loads the recently opened files, most recent first
+///
+/// Files which don't exist anymore are skipped.
+pub fn load() -> Vec<PathBuf> {
+    let Some(content) = path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+
+    serde_json::from_str::<Vec<PathBuf>>(&content)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|file| file.is_file())
+        .collect()
+}
+
+/// Adds a file as the most recent one, it's moved to the front if it's already contained
+pub fn add(file: &Path) -> Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+
+    let mut files = load();
+    files.retain(|recent| recent != file);
+    files.insert(0, file.to_path_buf());
+    files.truncate(MAX_RECENT_FILES);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&files)?)?;
+    Ok(())
+}
+
+/// The path of the file storing the recent files, inside of the config directory
+fn path() -> Option<PathBuf> {
+    config::dir().map(|dir| dir.join("recent.json"))
+}
@@ -0,0 +1,69 @@
+use std::{iter, ops::Range};
+
+use crate::config::SearchCase;
+
+/// A searched text together with how its case is matched, shared by the editor and the output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    /// The searched text, never empty
+    pub text: String,
+    /// Matching lowercase and uppercase letters alike
+    pub ignore_case: bool,
+}
+
+impl Search {
+    /// Creates the search for the text, `None` if it's empty
+    ///
+    /// With [`SearchCase::Smart`] the case is only matched if the text contains an uppercase
+    /// letter, like `Foo`.
+    pub fn new(text: &str, case: SearchCase) -> Option<Self> {
+        if text.is_empty() {
+            return None;
+        }
+        let ignore_case = match case {
+            SearchCase::Sensitive => false,
+            SearchCase::Insensitive => true,
+            SearchCase::Smart => !text.chars().any(char::is_uppercase),
+        };
+        Some(Self {
+            text: text.to_string(),
+            ignore_case,
+        })
+    }
+
+    /// Returns the byte ranges of the matches inside of the haystack, they don't overlap
+    ///
+    /// Ignoring the case compares the lowercase forms of the chars, so a match may have another
+    /// length in bytes than the searched text.
+    pub fn matches<'a>(&'a self, haystack: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+        let mut start = 0;
+        iter::from_fn(move || {
+            let rest = haystack.get(start..)?;
+            let found = if self.ignore_case {
+                rest.char_indices()
+                    .find_map(|(i, _)| self.match_len(&rest[i..]).map(|len| i..i + len))
+            } else {
+                rest.find(&self.text).map(|i| i..i + self.text.len())
+            };
+            let Some(found) = found else {
+                start = haystack.len() + 1;
+                return None;
+            };
+            let found = start + found.start..start + found.end;
+            start = found.end;
+            Some(found)
+        })
+    }
+
+    /// Returns the length in bytes of a match at the start of the text, ignoring the case
+    fn match_len(&self, text: &str) -> Option<usize> {
+        let mut chars = text.char_indices();
+        for expected in self.text.chars() {
+            let (_, c) = chars.next()?;
+            if c != expected && !c.to_lowercase().eq(expected.to_lowercase()) {
+                return None;
+            }
+        }
+        Some(chars.next().map_or(text.len(), |(i, _)| i))
+    }
+}
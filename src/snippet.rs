@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    Local,
+};
+
+use crate::config::DEFAULT_DATE_FORMAT;
+
+/// The text of an expanded snippet together with its tab-stops
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    pub text: String,
+    /// Byte ranges of the tab-stops inside of the text in the order they're visited, the
+    /// range covers the placeholder text
+    pub stops: Vec<Range<usize>>,
+}
+
+/// Expands the body of a snippet
+///
+/// `$1` to `$9` are tab-stops, `${1:name}` ones with a placeholder text and `$0` is the final
+/// one, visited after all others. `$DATE` is replaced by the current date/time and `$$` by a
+/// single `$`. Lines after the first one are indented by `indent`.
+pub fn expand(body: &str, indent: &str, date_format: &str) -> Snippet {
+    let mut text = String::new();
+    let mut stops: Vec<(usize, Range<usize>)> = Vec::new();
+    let mut rest = body;
+    while let Some(ch) = rest.chars().next() {
+        rest = &rest[ch.len_utf8()..];
+        match ch {
+            '\n' => {
+                text.push('\n');
+                text.push_str(indent);
+            }
+            '$' => {
+                if let Some(after) = rest.strip_prefix('$') {
+                    text.push('$');
+                    rest = after;
+                } else if let Some(after) = rest.strip_prefix("DATE") {
+                    let date = format_date(date_format)
+                        .or_else(|| format_date(DEFAULT_DATE_FORMAT))
+                        .unwrap_or_default();
+                    text.push_str(&date);
+                    rest = after;
+                } else if let Some((number, placeholder, after)) = parse_stop(rest) {
+                    let start = text.len();
+                    text.push_str(placeholder);
+                    // only the first occurrence of a number is a tab-stop
+                    if stops.iter().all(|(other, _)| *other != number) {
+                        stops.push((number, start..text.len()));
+                    }
+                    rest = after;
+                } else {
+                    text.push('$');
+                }
+            }
+            ch => text.push(ch),
+        }
+    }
+
+    // `$0` is visited last, without it the end of the snippet is
+    stops.sort_by_key(|(number, _)| if *number == 0 { usize::MAX } else { *number });
+    if !stops.is_empty() && stops.last().map(|(number, _)| *number) != Some(0) {
+        stops.push((0, text.len()..text.len()));
+    }
+    Snippet {
+        text,
+        stops: stops.into_iter().map(|(_, range)| range).collect(),
+    }
+}
+
+/// Parses a tab-stop like `1` or `{1:name}` behind a `$`, returns its number, placeholder and
+/// the text behind it
+fn parse_stop(text: &str) -> Option<(usize, &str, &str)> {
+    if let Some(inner) = text.strip_prefix('{') {
+        let end = inner.find('}')?;
+        let (number, placeholder) = match inner[..end].split_once(':') {
+            Some((number, placeholder)) => (number, placeholder),
+            None => (&inner[..end], ""),
+        };
+        Some((number.parse().ok()?, placeholder, &inner[end + 1..]))
+    } else {
+        let digit = text.chars().next()?.to_digit(10)?;
+        Some((digit as usize, "", &text[1..]))
+    }
+}
+
+/// Formats the current local date/time, `None` if the format is invalid
+pub fn format_date(format: &str) -> Option<String> {
+    let items = StrftimeItems::new(format).collect::<Vec<_>>();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return None;
+    }
+    Some(
+        Local::now()
+            .format_with_items(items.into_iter())
+            .to_string(),
+    )
+}
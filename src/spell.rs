@@ -0,0 +1,203 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    ops::Range,
+    path::PathBuf,
+};
+
+use crate::{config, error::Result};
+
+/// Maximal number of suggestions for a misspelled word
+const MAX_SUGGESTIONS: usize = 8;
+
+/// A Hunspell dictionary, like the ones of LibreOffice, together with the user dictionary
+#[derive(Clone)]
+pub struct Dictionary {
+    dictionary: spellbook::Dictionary,
+}
+
+impl fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dictionary").finish_non_exhaustive()
+    }
+}
+
+impl Dictionary {
+    /// Loads the Hunspell dictionary at the path of its `.dic` file and the user dictionary
+    ///
+    /// The affix file is the `.aff` file next to it. Without a `path` the English dictionary
+    /// of the system is used, which only exists on Unix.
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        let path = path
+            .map(PathBuf::from)
+            .or_else(default_path)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "No dictionary is configured")
+            })?;
+        let dic = fs::read_to_string(&path)?;
+        let aff = fs::read_to_string(path.with_extension("aff"))?;
+        let mut dictionary = Self::new(&aff, &dic)?;
+
+        if let Some(user_path) = user_path() {
+            match fs::read_to_string(user_path) {
+                Ok(words) => {
+                    for word in words.lines().map(str::trim).filter(|word| !word.is_empty()) {
+                        dictionary.insert(word)?;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(dictionary)
+    }
+
+    /// Parses the affix and word files of a Hunspell dictionary
+    fn new(aff: &str, dic: &str) -> io::Result<Self> {
+        let dictionary = spellbook::Dictionary::new(aff, dic).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("The dictionary is invalid: {e}"),
+            )
+        })?;
+        Ok(Self { dictionary })
+    }
+
+    /// Returns `true` if the word is spelled correctly
+    ///
+    /// Single letters, acronyms like `HTTP` and words with inner capitals like `camelCase`
+    /// are always correct.
+    pub fn is_correct(&self, word: &str) -> bool {
+        let mut chars = word.chars();
+        chars.next();
+        if chars.clone().next().is_none() || chars.any(char::is_uppercase) {
+            return true;
+        }
+        self.dictionary.check(word)
+    }
+
+    /// Returns the byte ranges of the misspelled words of the text
+    pub fn misspelled(&self, text: &str) -> Vec<Range<usize>> {
+        word_ranges(text)
+            .into_iter()
+            .filter(|range| !self.is_correct(&text[range.clone()]))
+            .collect()
+    }
+
+    /// Returns the most similar words, the closest first
+    ///
+    /// A capitalized word gets capitalized suggestions.
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        self.dictionary.suggest(word, &mut suggestions);
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    /// Adds a word to the user dictionary, so it's spelled correctly from now on
+    pub fn add(&mut self, word: &str) -> Result<()> {
+        self.insert(word)?;
+        if let Some(path) = user_path() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{word}")?;
+        }
+        Ok(())
+    }
+
+    /// Adds a word of the user dictionary, flags like `/G` behind it are used as well
+    fn insert(&mut self, word: &str) -> io::Result<()> {
+        self.dictionary.add(word).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("The word {word} couldn't be added: {e}"),
+            )
+        })
+    }
+}
+
+/// Returns the byte ranges of the words of prose text
+///
+/// Apostrophes between letters are part of a word, like in `don't`. Words containing digits
+/// or underscores are identifiers, like `foo_bar`, and are skipped.
+pub fn word_ranges(text: &str) -> Vec<Range<usize>> {
+    let is_token = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    for (i, c) in text.char_indices().filter(|&(_, c)| is_token(c)) {
+        match tokens.last_mut() {
+            Some(token) if token.end == i => token.end = i + c.len_utf8(),
+            _ => tokens.push(i..i + c.len_utf8()),
+        }
+    }
+
+    tokens
+        .into_iter()
+        .filter_map(|token| {
+            let token_text = &text[token.clone()];
+            let start = token.end - token_text.trim_start_matches('\'').len();
+            let end = token.start + token_text.trim_end_matches('\'').len();
+            (start < end
+                && text[start..end]
+                    .chars()
+                    .all(|c| c.is_alphabetic() || c == '\''))
+            .then_some(start..end)
+        })
+        .collect()
+}
+
+/// The English dictionary of the system
+fn default_path() -> Option<PathBuf> {
+    #[cfg(unix)]
+    return Some(PathBuf::from("/usr/share/hunspell/en_US.dic"));
+    #[cfg(not(unix))]
+    return None;
+}
+
+/// The path of the user dictionary, inside of the config directory
+fn user_path() -> Option<PathBuf> {
+    config::dir().map(|dir| dir.join("dictionary.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dictionary whose words can get an `s` appended via the flag `S`
+    fn dictionary() -> Dictionary {
+        let aff = "SET UTF-8\nSFX S Y 1\nSFX S 0 s .\n";
+        Dictionary::new(aff, "3\nhello\nworld/S\nhouse\n").unwrap()
+    }
+
+    #[test]
+    fn words_are_checked_with_their_affixes() {
+        let dictionary = dictionary();
+        assert!(dictionary.is_correct("hello"));
+        assert!(dictionary.is_correct("worlds"));
+        assert!(!dictionary.is_correct("hellos"));
+        // capitalized words and words which aren't checked
+        assert!(dictionary.is_correct("Hello"));
+        assert!(dictionary.is_correct("HTTP"));
+        assert!(dictionary.is_correct("camelCase"));
+        assert!(dictionary.is_correct("x"));
+        assert_eq!(dictionary.misspelled("hello wrold"), vec![6..11]);
+    }
+
+    #[test]
+    fn suggestions_keep_the_case() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggestions("wrold")[0], "world");
+        assert_eq!(dictionary.suggestions("Hoose")[0], "House");
+    }
+
+    #[test]
+    fn added_words_are_correct() {
+        let mut dictionary = dictionary();
+        assert!(!dictionary.is_correct("omega"));
+        dictionary.insert("omega/S").unwrap();
+        assert!(dictionary.is_correct("omega"));
+        assert!(dictionary.is_correct("omegas"));
+    }
+}
@@ -0,0 +1,24 @@
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use crate::app::PKG_NAME;
+
+/// Names of the directories returned by [`temp_dir`] during this run
+static TEMP_DIRS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Returns an empty directory for the test, removing the one of a previous run
+///
+/// The tests run in parallel, so each one needs a name of its own, names used twice panic.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let mut names = TEMP_DIRS.lock().unwrap_or_else(|e| e.into_inner());
+    assert!(
+        !names.iter().any(|used| used == name),
+        "The temporary directory `{name}` is used by another test"
+    );
+    names.push(name.to_string());
+    drop(names);
+
+    let dir = std::env::temp_dir().join(format!("{PKG_NAME}-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
@@ -4,28 +4,209 @@ use cursive::{
     reexports::log::error,
     theme::{BaseColor, Color, ColorStyle, Effect, PaletteColor, PaletteStyle, Style},
     utils::{
-        lines::simple::{simple_prefix, LinesIterator, Row},
+        lines::simple::{LinesIterator, Row},
         markup::StyledString,
         span::SpannedString,
     },
     view::{CannotFocus, SizeCache},
-    Cursive, Printer, Rect, Vec2, View, With, XY,
+    CbSink, Cursive, Printer, Rect, Vec2, View, With, XY,
 };
 use cursive::{
     impl_scroller,
     view::{scroll, ScrollStrategy},
 };
 use std::{
+    cell::RefCell,
     cmp::{max, min},
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    iter, mem,
+    ops::Range,
     rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 use syntect::{
-    highlighting::Theme,
-    parsing::{SyntaxReference, SyntaxSet},
+    easy::HighlightLines,
+    highlighting::{HighlightState, Highlighter, Theme},
+    parsing::{ParseState, Regex, ScopeStack, ScopeStackOp, SyntaxReference, SyntaxSet},
 };
 use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    config::{IndentRules, LineNumbers},
+    cursor, paste,
+    search::Search,
+    snippet,
+    spell::Dictionary,
+};
 use unicode_width::UnicodeWidthStr;
 
+/// Opening and closing characters which are getting auto-paired, unless the language has
+/// its own ones configured
+const PAIRS: [(char, char); 6] = [
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('"', '"'),
+    ('\'', '\''),
+    ('`', '`'),
+];
+
+/// Elements of HTML which don't have a closing tag
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Byte length from which on rows are only highlighted in the visible part
+const LONG_ROW: usize = 4096;
+
+/// Number of rows which are highlighted at once while drawing, to reach rows further down
+/// they're highlighted step by step while the rows are shown as plain text
+const BLOCKING_ROWS: usize = 2_000;
+
+/// Time spent highlighting per step
+const HIGHLIGHT_STEP: Duration = Duration::from_millis(20);
+
+/// Number of rows whose highlighting is cached by their text, before that cache is cleared
+const HIGHLIGHT_CACHE_SIZE: usize = 4096;
+
+/// Maximal number of counted search matches, so counting stays fast in huge files
+pub const MAX_COUNTED_MATCHES: usize = 10_000;
+
+/// Closure type for callbacks when something happens, for example the cursor is moved.
+///
+/// Arguments are the `Cursive`, the scroll offset and the cursor position
+pub type OnChange = dyn Fn(&mut Cursive, Vec2, Cursor);
+
+/// Closure type for callbacks when the content is modified.
+///
+/// Arguments are the `Cursive`, the edits since the last call in their order, the scroll
+/// offset and the cursor position
+pub type OnEdit = dyn Fn(&mut Cursive, &[Edit], Vec2, Cursor);
+
+/// Replacement of a byte range of the content by a text, it's applied to a copy of the content
+/// instead of cloning the whole content on every keystroke
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Closure type for callbacks when a large text is pasted.
+pub type OnLargePaste = dyn Fn(&mut Cursive, String);
+
+/// The cursor offset
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Cursor {
+    /// Vertical rows from top, rows are separated with a `\n`
+    pub row: usize,
+    /// Terminal cells from left to right, wide chars like CJK take two cells
+    pub column: usize,
+    /// Byte offset of the currently selected grapheme
+    pub byte_offset: usize,
+    /// Number of chars in front of the cursor within its row
+    pub char_index: usize,
+    /// Visual column of the cursor within its row, tabs are expanded to the tab size
+    pub display_column: usize,
+}
+
+/// Case a word can be converted to
+#[derive(Clone, Copy)]
+enum Case {
+    Upper,
+    Lower,
+    /// First letter uppercase, the others lowercase
+    Title,
+}
+
+impl Case {
+    /// Converts the text, chars without a case are kept
+    fn convert(self, text: &str) -> String {
+        match self {
+            Case::Upper => text.to_uppercase(),
+            Case::Lower => text.to_lowercase(),
+            Case::Title => {
+                let mut chars = text.chars();
+                chars.next().map_or(String::new(), |first| {
+                    first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect()
+                })
+            }
+        }
+    }
+}
+
+/// Parse and highlight state at the start of a row
+type LineState = (ParseState, HighlightState);
+
+/// Highlighted row with the state at its start and the state for the next row
+type HighlightedLine = (LineState, Option<StyledString>, LineState);
+
+/// Highlighting of the rows, which keeps the state at the start of every row
+///
+/// After an edit only the rows from the edit on are highlighted again, until the
+/// state at the start of a row is the same as before the edit.
+#[derive(Default)]
+struct HighlightCache {
+    /// State at the start of each row, `None` if unknown
+    states: Vec<Option<LineState>>,
+    /// Highlighted text of each row, `None` if not highlighted yet
+    lines: Vec<Option<StyledString>>,
+    /// Number of leading rows whose state is correct
+    valid: usize,
+    /// Number of leading rows whose state is correct again, once the state reconverged
+    converged: usize,
+    /// Highlighted rows by the hash of their text, reused for rows with the same text and state
+    ///
+    /// Unlike the rest it's kept if the content is replaced, like when switching files.
+    by_text: HashMap<u64, HighlightedLine>,
+}
+
+impl HighlightCache {
+    /// Removes the highlighting of the rows, highlighting starts again from the first row
+    ///
+    /// Rows which are highlighted like before are still taken from [`Self::by_text`].
+    fn clear(&mut self) {
+        *self = Self {
+            by_text: mem::take(&mut self.by_text),
+            ..Self::default()
+        };
+    }
+
+    /// Removes everything, like if the highlighting changes
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Updates the cache after `removed` rows starting at `first` were replaced by `added` rows
+    fn splice(&mut self, first: usize, removed: usize, added: usize) {
+        let range = |len: usize, start: usize, end: usize| min(start, len)..min(end, len);
+
+        // the state at the start of the first row is still correct
+        let states = range(self.states.len(), first + 1, first + removed);
+        self.states
+            .splice(states, iter::repeat(None).take(added.saturating_sub(1)));
+        let lines = range(self.lines.len(), first, first + removed);
+        self.lines.splice(lines, iter::repeat(None).take(added));
+
+        self.converged = if self.valid > first + removed {
+            self.valid - removed + added
+        } else if self.valid > first && self.converged > first + removed {
+            // the row edited last is edited again, like while typing, so the rows behind it
+            // are still highlighted like before once the state reconverges, the states of the
+            // added rows are unknown so it can't reconverge before them
+            self.converged - removed + added
+        } else {
+            min(self.valid, first + 1)
+        };
+        self.valid = min(self.valid, first + 1);
+    }
+}
+
 /// Improved Multi-lines text editor.
 ///
 /// A `EditArea` will attempt to grow vertically and horizontally
@@ -44,24 +225,6 @@ use unicode_width::UnicodeWidthStr;
 ///     .fixed_width(30)
 ///     .min_height(5);
 /// ```
-
-/// Closure type for callbacks when something happens, for example the content is modified.
-///
-/// Arguments are the `Cursive`, current content of the input and cursor
-/// position
-pub type OnChange = dyn Fn(&mut Cursive, &str, Vec2, Cursor);
-
-/// The cursor offset
-#[derive(Clone, Copy, Debug, Default)]
-pub struct Cursor {
-    /// Vertical rows from top, rows are separated with a `\n`
-    pub row: usize,
-    /// From left to right
-    pub column: usize,
-    /// Byte offset of the currently selected grapheme
-    pub byte_offset: usize,
-}
-
 pub struct EditArea {
     // TODO: use a smarter data structure (rope?)
     content: String,
@@ -74,8 +237,8 @@ pub struct EditArea {
     /// Invariant: never empty.
     rows: Vec<Row>,
 
-    /// Syntax Set
-    syntax: SyntaxSet,
+    /// Syntax Set, shared with the rest of the app
+    syntax: Arc<SyntaxSet>,
 
     /// Current Theme for highlighting
     theme: Theme,
@@ -86,20 +249,113 @@ pub struct EditArea {
     /// When `false`, we don't take any input.
     enabled: bool,
 
+    /// When `true`, the content can only be navigated, not edited.
+    read_only: bool,
+
+    /// Hint shown instead of the content while disabled
+    placeholder: String,
+
+    /// When `true`, brackets and quotes are closed automatically.
+    auto_pairs: bool,
+
+    /// Opening and closing chars which are auto-paired in the current language
+    pairs: Vec<(char, char)>,
+
+    /// Lines kept visible above and below the cursor while scrolling
+    scroll_off: usize,
+
+    /// Offset the view was centered to by the current event, it's kept after the event
+    centered: Option<Vec2>,
+
+    /// Columns at which vertical rulers are drawn
+    rulers: Vec<usize>,
+
+    /// When `true`, the text behind the last ruler is highlighted.
+    highlight_overflow: bool,
+
+    /// Maximal number of enclosing block headers pinned to the top, `0` disables them
+    sticky_lines: usize,
+
+    /// Width of an indentation level and of a tab character
+    tab_size: usize,
+
+    /// When `true`, new lines are indented like the line before them.
+    auto_indent: bool,
+
+    /// Lines after which new lines are indented one level deeper, like ones ending with `{`
+    indent_increase: Option<Regex>,
+
+    /// Lines which are indented one level less once they're typed, like ones starting with `}`
+    indent_decrease: Option<Regex>,
+
+    /// Chars besides letters and digits which are part of words
+    word_chars: String,
+
+    /// Numbering of the lines, absolute ones or relative to the cursor
+    line_numbers: LineNumbers,
+
+    /// Words like `TODO` which are highlighted inside of comments
+    markers: Vec<String>,
+
+    /// Color of the highlighted markers
+    marker_color: Color,
+
+    /// Dictionary for underlining misspelled words, spell checking is disabled without one
+    dictionary: Option<Arc<Dictionary>>,
+
+    /// Searched text whose matches are highlighted until the search is cleared
+    search: Option<Search>,
+
+    /// Rows with a note, they're marked next to their line number
+    notes: BTreeSet<usize>,
+
+    /// Snippet bodies by their trigger word, expanded by `Tab`
+    snippets: HashMap<String, String>,
+
+    /// Format of `$DATE` inside of snippets
+    date_format: String,
+
+    /// Remaining tab-stops of the last expanded snippet
+    snippet_stops: Option<SnippetStops>,
+
+    /// When `false`, the text is shown without syntax highlighting, like for huge files
+    highlighted: bool,
+
+    /// Highlighting of the rows, so unchanged rows aren't highlighted again
+    ///
+    /// Needs to be reset if the highlighting changes.
+    highlight_cache: RefCell<HighlightCache>,
+
+    /// Redraws to highlight the next rows, so rows far down are reached step by step, without
+    /// it they're highlighted at once while drawing
+    cb_sink: Option<CbSink>,
+
     /// Callback when the cursor is moved.
     ///
-    /// Will be called with the current content and the cursor position.
+    /// Will be called with the scroll offset and the cursor position.
     on_interact: Option<Rc<OnChange>>,
 
-    /// Callback when the content is modified.
+    /// Callback when the view is scrolled.
     ///
-    /// Will be called with the current content and the cursor position.
+    /// Will be called with the scroll offset and the cursor position.
     on_scroll: Option<Rc<OnChange>>,
 
     /// Callback when the content is modified.
     ///
-    /// Will be called with the current content and the cursor position.
-    on_edit: Option<Rc<OnChange>>,
+    /// Will be called with the edits, the scroll offset and the cursor position.
+    on_edit: Option<Rc<OnEdit>>,
+
+    /// Edits not passed to `on_edit` yet, they're taken when its callback runs, so the edits of
+    /// a dropped callback are passed to the next one
+    edits: Rc<RefCell<Vec<Edit>>>,
+
+    /// Size in bytes above which pasted text is passed to `on_large_paste` instead
+    paste_limit: Option<usize>,
+
+    /// Callback when a text larger than the `paste_limit` is pasted.
+    ///
+    /// Will be called with the pasted text, it isn't inserted.
+    on_large_paste: Option<Rc<OnLargePaste>>,
 
     /// Base for scrolling features
     scroll_core: scroll::Core,
@@ -109,35 +365,268 @@ pub struct EditArea {
 
     /// Cursor offset view the `struct::Cursor` for further details
     cursor: Cursor,
+
+    /// Other end row of the column cursors
+    ///
+    /// When set, there is a cursor at the column of the cursor on every row between both.
+    column_end: Option<usize>,
 }
 
 impl_scroller!(EditArea::scroll_core);
 
+/// Tab-stops of an expanded snippet which weren't visited yet
+struct SnippetStops {
+    /// Byte ranges of the remaining stops, as they were when the content had `content_len` bytes
+    stops: VecDeque<Range<usize>>,
+    /// Placeholder of the current stop, which is replaced by the first typed char
+    placeholder: Range<usize>,
+    /// Length of the content when the stops were updated the last time
+    content_len: usize,
+}
+
+impl SnippetStops {
+    /// Shifts the remaining stops by the change of the content's length
+    ///
+    /// Text is only typed before the remaining stops, so all of them move by the same amount.
+    fn update(&mut self, content_len: usize) {
+        for stop in &mut self.stops {
+            *stop = (stop.start + content_len).saturating_sub(self.content_len)
+                ..(stop.end + content_len).saturating_sub(self.content_len);
+        }
+        self.content_len = content_len;
+    }
+}
+
 fn make_rows(text: &str) -> Vec<Row> {
     // Full width, no limits
-    let width = usize::MAX;
-    LinesIterator::new(text, width).show_spaces().collect()
+    let lines = |start: usize, end: usize| {
+        LinesIterator::new(&text[start..end], usize::MAX)
+            .show_spaces()
+            .map(move |mut row| {
+                row.shift(start);
+                row
+            })
+    };
+
+    // segmenting rows into graphemes takes too long for long rows, like minified code, so
+    // they are measured at once
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        if line.len() > LONG_ROW {
+            rows.extend(lines(start, line_start));
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            rows.push(Row {
+                start: line_start,
+                end: line_start + line.len(),
+                width: line.width(),
+                is_wrapped: false,
+            });
+            start = line_end;
+        }
+        line_start = line_end;
+    }
+    rows.extend(lines(start, text.len()));
+    rows
+}
+
+/// Returns the grapheme at the start of the text, a tab or the end is shown as a single space
+fn selected_grapheme(text: &str) -> &str {
+    text.graphemes(true)
+        .next()
+        .map_or(" ", |g| if g == "\t" { " " } else { g })
+}
+
+/// Returns `true` for events which only move the cursor or scroll, like while read-only
+fn is_navigation(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::Key(
+            Key::Up
+                | Key::Down
+                | Key::Left
+                | Key::Right
+                | Key::Home
+                | Key::End
+                | Key::PageUp
+                | Key::PageDown
+                | Key::Esc
+        ) | Event::Ctrl(Key::Home | Key::End | Key::Left | Key::Right)
+            | Event::Shift(Key::PageUp | Key::PageDown)
+            | Event::CtrlChar('c' | 'l')
+            | Event::Mouse { .. }
+    )
+}
+
+/// Returns `true` if the indent rule matches somewhere inside of the text
+fn matches_rule(rule: &Option<Regex>, text: &str) -> bool {
+    rule.as_ref()
+        .is_some_and(|regex| regex.search(text, 0, text.len(), None))
+}
+
+/// Returns the byte ranges of the numbers of the line, including a minus sign or `0x` prefix
+///
+/// A minus is only part of a number if it doesn't follow a word or a closing bracket, so the
+/// `1` of `x-1` stays positive.
+fn number_ranges(line: &str) -> Vec<Range<usize>> {
+    let bytes = line.as_bytes();
+    let digits_end = |start: usize, hex: bool| {
+        bytes[start..]
+            .iter()
+            .position(|b| !(b.is_ascii_digit() || hex && b.is_ascii_hexdigit()))
+            .map_or(bytes.len(), |len| start + len)
+    };
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes[i] == b'0'
+            && matches!(bytes.get(i + 1), Some(b'x' | b'X'))
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit);
+        if hex {
+            let end = digits_end(i + 2, true);
+            ranges.push(i..end);
+            i = end;
+        } else if bytes[i].is_ascii_digit() {
+            let end = digits_end(i, false);
+            let follows_word = i > 1
+                && (bytes[i - 2].is_ascii_alphanumeric()
+                    || matches!(bytes[i - 2], b'_' | b')' | b']' | b'}'));
+            let negative = i > 0 && bytes[i - 1] == b'-' && !follows_word;
+            ranges.push(if negative { i - 1 } else { i }..end);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+/// Adds the step to the number at or behind the column of the line
+///
+/// Returns the byte range of the number and its new text. Hexadecimal numbers keep the case
+/// of their digits and numbers with leading zeros their number of digits.
+fn add_to_number(line: &str, column: usize, step: i64) -> Option<(Range<usize>, String)> {
+    let ranges = number_ranges(line);
+    let range = ranges
+        .iter()
+        .find(|range| range.end > column)
+        .or_else(|| ranges.iter().rfind(|range| range.end == column))?
+        .clone();
+    let number = &line[range.clone()];
+
+    let changed = if number.len() > 2 && number[1..2].eq_ignore_ascii_case("x") {
+        let (prefix, digits) = number.split_at(2);
+        let value = u64::from_str_radix(digits, 16)
+            .ok()?
+            .wrapping_add_signed(step);
+        let width = digits.len();
+        if digits.chars().any(|c| c.is_ascii_uppercase()) {
+            format!("{prefix}{value:0width$X}")
+        } else {
+            format!("{prefix}{value:0width$x}")
+        }
+    } else {
+        let value = number.parse::<i64>().ok()?.checked_add(step)?;
+        let digits = number.trim_start_matches('-');
+        if digits.len() > 1 && digits.starts_with('0') {
+            let width = digits.len();
+            let sign = if value < 0 { "-" } else { "" };
+            format!("{sign}{:0width$}", value.unsigned_abs())
+        } else {
+            value.to_string()
+        }
+    };
+    Some((range, changed))
+}
+
+/// Sets an element of the vector, growing it with `None` if needed
+fn set_at<T>(vec: &mut Vec<Option<T>>, index: usize, value: Option<T>) {
+    if vec.len() <= index {
+        vec.resize_with(index + 1, || None);
+    }
+    vec[index] = value;
+}
+
+/// Restyles the byte ranges of the styled line, the ranges need to be sorted
+pub fn restyle<F>(
+    line: &str,
+    styled: &StyledString,
+    ranges: &[Range<usize>],
+    style: F,
+) -> StyledString
+where
+    F: Fn(Style) -> Style,
+{
+    let mut result = StyledString::new();
+    let mut offset = 0;
+    for span in styled.spans() {
+        let span_end = offset + span.content.len();
+        let mut pos = offset;
+        for range in ranges {
+            let start = max(range.start, pos);
+            let end = min(range.end, span_end);
+            if start < end {
+                result.append_styled(&line[pos..start], *span.attr);
+                result.append_styled(&line[start..end], style(*span.attr));
+                pos = end;
+            }
+        }
+        result.append_styled(&line[pos..span_end], *span.attr);
+        offset = span_end;
+    }
+    result
 }
 
 impl EditArea {
-    /// Creates a new, empty EditArea with a specified theme.
-    pub fn new(theme: &Theme) -> Self {
+    /// Creates a new, empty EditArea with a specified syntax set and theme.
+    pub fn new(syntax: Arc<SyntaxSet>, theme: &Theme) -> Self {
         EditArea {
             content: String::new(),
             max_content_width: 0,
             rows: Vec::new(),
-            syntax: SyntaxSet::load_defaults_newlines(),
+            synref: syntax.find_syntax_plain_text().clone(),
+            syntax,
             theme: theme.to_owned(),
-            synref: SyntaxSet::load_defaults_newlines()
-                .find_syntax_plain_text()
-                .clone(),
             enabled: true,
+            read_only: false,
+            placeholder: String::new(),
+            auto_pairs: true,
+            pairs: PAIRS.to_vec(),
+            scroll_off: 0,
+            centered: None,
+            rulers: Vec::new(),
+            highlight_overflow: false,
+            sticky_lines: 0,
+            tab_size: 4,
+            auto_indent: true,
+            indent_increase: None,
+            indent_decrease: None,
+            word_chars: "_".to_string(),
+            line_numbers: LineNumbers::Absolute,
+            markers: Vec::new(),
+            marker_color: Color::Dark(BaseColor::Yellow),
+            dictionary: None,
+            search: None,
+            notes: BTreeSet::new(),
+            snippets: HashMap::new(),
+            date_format: String::new(),
+            snippet_stops: None,
+            highlighted: true,
+            highlight_cache: RefCell::new(HighlightCache::default()),
+            cb_sink: None,
             on_interact: None,
             on_scroll: None,
             on_edit: None,
+            edits: Rc::default(),
+            paste_limit: None,
+            on_large_paste: None,
             scroll_core: scroll::Core::new(),
             size_cache: None,
             cursor: Cursor::default(),
+            column_end: None,
         }
         .with(|area| {
             // Make sure we have valid rows, even for empty text.
@@ -181,6 +670,19 @@ impl EditArea {
         self.cursor
     }
 
+    /// Returns the text of the line of the cursor, without its newline.
+    pub fn current_line(&self) -> &str {
+        self.row_text(self.selected_row())
+    }
+
+    /// Returns the text of the lines of the column cursors, `None` without column cursors.
+    ///
+    /// The last line is without its newline.
+    pub fn selection(&self) -> Option<&str> {
+        self.column_rows()
+            .map(|rows| &self.content[self.rows[rows.start].start..self.rows[rows.end - 1].end])
+    }
+
     /// Moves the cursor to the given position.
     ///
     /// # Panics
@@ -193,9 +695,14 @@ impl EditArea {
 
         self.cursor = cursor;
 
+        // the logical index and visual column are always derived from the byte offset
+        let row = self.rows[self.row_at(cursor.byte_offset)];
+        let before = &self.content[row.start..cursor.byte_offset];
+        self.cursor.char_index = before.chars().count();
+        self.cursor.display_column = self.text_width(before);
+
         // fix scroll
-        self.scroll_core
-            .scroll_to(Vec2::new(self.cursor.column, self.cursor.row));
+        self.scroll_core.scroll_to_rect(self.cursor_area());
 
         self.on_interact_callback().unwrap_or(Callback::dummy())
     }
@@ -206,30 +713,38 @@ impl EditArea {
             row: self.row_at(byte_offset),
             column: self.col_at(byte_offset),
             byte_offset,
+            ..Default::default()
         })
     }
 
-    /// Only updates the byte offset
+    /// Only updates the byte offset and its row, the column is kept for moving vertically
     fn set_byte_offset(&mut self, byte_offset: usize) -> Callback {
         self.set_cursor(Cursor {
-            row: self.cursor.row,
-            column: self.cursor.column,
+            row: self.row_at(byte_offset),
             byte_offset,
+            ..self.cursor
         })
     }
 
     /// Sets the content of the view.
+    ///
+    /// This also removes the column cursors. It isn't passed to `on_edit` as an edit and the
+    /// pending edits are dropped, the caller stores the new content itself.
     pub fn set_content<S: Into<String>>(&mut self, content: S) -> Callback {
         self.content = content.into();
+        self.edits.borrow_mut().clear();
+        self.column_end = None;
+        self.snippet_stops = None;
 
         // First, make sure we are within the bounds.
-        self.set_curser_from_byte_offset(min(self.cursor.byte_offset, self.content.len()));
+        let mut byte_offset = min(self.cursor.byte_offset, self.content.len());
 
         // We have no guarantee cursor is now at a correct UTF8 location.
-        // So look backward until we find a valid grapheme start.
-        while !self.content.is_char_boundary(self.cursor.byte_offset) {
-            self.set_curser_from_byte_offset(self.cursor.byte_offset - 1);
+        // So look backward until we find a valid char start.
+        while !self.content.is_char_boundary(byte_offset) {
+            byte_offset -= 1;
         }
+        self.set_curser_from_byte_offset(byte_offset);
 
         if let Some(size) = self.size_cache.map(|s| s.map(|s| s.value)) {
             self.invalidate();
@@ -249,12 +764,23 @@ impl EditArea {
     }
 
     /// Set highlighting style via a file extension
-    pub fn set_highlighting(&mut self, extension: &str) {
-        self.synref = self
-            .syntax
-            .find_syntax_by_extension(extension)
+    ///
+    /// A configured syntax name like `"INI"` is used instead of detecting it by the extension.
+    /// Returns `false` if there is no syntax with that name, it's detected then.
+    pub fn set_highlighting(&mut self, extension: &str, syntax_name: Option<&str>) -> bool {
+        let configured = syntax_name.map(|name| {
+            self.syntax
+                .syntaxes()
+                .iter()
+                .find(|syntax| syntax.name.eq_ignore_ascii_case(name))
+        });
+        self.synref = configured
+            .flatten()
+            .or_else(|| self.syntax.find_syntax_by_extension(extension))
             .cloned()
             .unwrap_or(self.syntax.find_syntax_plain_text().clone());
+        self.highlight_cache.borrow_mut().reset();
+        !matches!(configured, Some(None))
     }
 
     /// Disables this view.
@@ -290,172 +816,831 @@ impl EditArea {
         self.enabled
     }
 
-    /// Sets a callback to be called whenever the cursor is modified.
-    ///
-    /// `callback` will be called with the view
-    /// content and the current cursor position.
-    ///
-    /// This callback can safely trigger itself recursively if needed
-    /// (for instance if you call `on_event` on this view from the callback).
-    ///
-    /// If you need a mutable closure and don't care about the recursive
-    /// aspect, see [`set_on_interact_mut`](#method.set_on_interact_mut).
-    pub fn set_on_interact<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut Cursive, &str, Vec2, Cursor) + 'static,
-    {
-        self.on_interact = Some(Rc::new(callback));
+    /// Allows only navigating the content, like moving the cursor and scrolling.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
     }
 
-    /// Sets a callback to be called whenever the view is scrolled.
-    ///
-    /// `callback` will be called with the view
-    /// content and the current cursor position.
-    ///
-    /// This callback can safely trigger itself recursively if needed
-    /// (for instance if you call `on_event` on this view from the callback).
-    ///
-    /// If you need a mutable closure and don't care about the recursive
-    /// aspect, see [`set_on_scroll_mut`](#method.set_on_scroll_mut).
-    pub fn set_on_scroll<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut Cursive, &str, Vec2, Cursor) + 'static,
-    {
-        self.on_scroll = Some(Rc::new(callback));
+    /// Returns `true` if the content can't be edited.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
-    /// Sets a callback to be called whenever the content is modified.
-    ///
-    /// `callback` will be called with the view
-    /// content and the current cursor position.
-    ///
-    /// This callback can safely trigger itself recursively if needed
-    /// (for instance if you call `on_event` on this view from the callback).
+    /// Sets the hint which is shown centered instead of the content while disabled.
     ///
-    /// If you need a mutable closure and don't care about the recursive
-    /// aspect, see [`set_on_edit_mut`](#method.set_on_edit_mut).
-    pub fn set_on_edit<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut Cursive, &str, Vec2, Cursor) + 'static,
-    {
-        self.on_edit = Some(Rc::new(callback));
+    /// Its lines are aligned to the left, so lists stay readable.
+    pub fn set_placeholder<S: Into<String>>(&mut self, placeholder: S) {
+        self.placeholder = placeholder.into();
     }
 
-    /// Finds the row containing the grapheme at the given offset
-    fn row_at(&self, byte_offset: usize) -> usize {
-        assert!(!self.rows.is_empty());
-        assert!(byte_offset >= self.rows[0].start);
-
-        self.rows
-            .iter()
-            .enumerate()
-            .take_while(|&(_, row)| row.start <= byte_offset)
-            .map(|(i, _)| i)
-            .last()
-            .unwrap()
+    /// Enables or disables the auto-pairing of brackets and quotes.
+    pub fn set_auto_pairs(&mut self, auto_pairs: bool) {
+        self.auto_pairs = auto_pairs;
     }
 
-    fn col_at(&self, byte_offset: usize) -> usize {
-        let row_id = self.row_at(byte_offset);
-        let row = self.rows[row_id];
-        // Number of cells to the left of the cursor
-        self.content[row.start..byte_offset].width()
+    /// Sets the chars which are auto-paired in the current language, `None` uses the default
+    /// brackets and quotes.
+    pub fn set_pairs(&mut self, pairs: Option<Vec<(char, char)>>) {
+        self.pairs = pairs.unwrap_or_else(|| PAIRS.to_vec());
     }
 
-    /// Finds the row containing the cursor
-    fn selected_row(&self) -> usize {
-        assert!(!self.rows.is_empty(), "Rows should never be empty.");
-        self.row_at(self.cursor.byte_offset)
+    /// Sets the snippets of the current language by their trigger word and the format of `$DATE`.
+    pub fn set_snippets(&mut self, snippets: HashMap<String, String>, date_format: String) {
+        self.snippets = snippets;
+        self.date_format = date_format;
+        self.snippet_stops = None;
     }
 
-    fn selected_col(&self) -> usize {
-        self.col_at(self.cursor.byte_offset)
+    /// Turns the syntax highlighting on or off, the text is shown plain without it.
+    pub fn set_highlighted(&mut self, highlighted: bool) {
+        self.highlighted = highlighted;
     }
 
-    fn page_up(&mut self) -> Callback {
-        for _ in 0..5 {
-            self.move_up();
-        }
-
-        self.on_interact_callback().unwrap_or(Callback::dummy())
+    /// Sets the sink for highlighting rows far down step by step, so the editor doesn't block
+    /// until they're reached.
+    pub fn set_cb_sink(&mut self, cb_sink: CbSink) {
+        self.cb_sink = Some(cb_sink);
     }
 
-    fn page_down(&mut self) -> Callback {
-        for _ in 0..5 {
-            self.move_down();
-        }
-
-        self.on_interact_callback().unwrap_or(Callback::dummy())
+    /// Returns `true` if the text is syntax highlighted.
+    pub fn is_highlighted(&self) -> bool {
+        self.highlighted
     }
 
-    fn move_up(&mut self) -> Callback {
-        let row_id = self.selected_row();
-        if row_id == 0 {
-            return Callback::dummy();
-        }
-
-        let prev_row = self.rows[row_id - 1];
-        let prev_cursor = self.cursor;
-        self.set_byte_offset(prev_row.start + prev_cursor.column.clamp(0, prev_row.width));
-
-        self.on_interact_callback().unwrap_or(Callback::dummy())
+    /// Returns the name of the syntax used for highlighting, like `"Rust"`.
+    pub fn syntax_name(&self) -> &str {
+        &self.synref.name
     }
 
-    fn move_down(&mut self) -> Callback {
-        let row_id = self.selected_row();
-        if row_id + 1 == self.rows.len() {
-            return Callback::dummy();
-        }
-
-        let next_row = self.rows[row_id + 1];
-        let prev_cursor = self.cursor;
-        self.set_byte_offset(next_row.start + prev_cursor.column.clamp(0, next_row.width));
+    /// Sets the number of lines kept visible above and below the cursor.
+    ///
+    /// A margin of `0` only keeps the cursor itself visible.
+    pub fn set_scroll_off(&mut self, scroll_off: usize) {
+        self.scroll_off = scroll_off;
+    }
 
-        self.on_interact_callback().unwrap_or(Callback::dummy())
+    /// Sets the columns at which vertical rulers are drawn.
+    ///
+    /// Tabs are expanded to the tab size, so the columns are visual ones. If
+    /// `highlight_overflow` is set, the text behind the last ruler is highlighted.
+    pub fn set_rulers(&mut self, rulers: Vec<usize>, highlight_overflow: bool) {
+        self.rulers = rulers;
+        self.highlight_overflow = highlight_overflow;
     }
 
-    /// Moves the cursor to the left.
-    fn move_left(&mut self) -> Callback {
-        self.set_curser_from_byte_offset(self.cursor.byte_offset - 1);
+    /// Sets the maximal number of enclosing block headers pinned to the top of the view.
+    ///
+    /// A maximum of `0` disables the sticky headers.
+    pub fn set_sticky_lines(&mut self, sticky_lines: usize) {
+        self.sticky_lines = sticky_lines;
+    }
 
-        self.on_interact_callback().unwrap_or(Callback::dummy())
+    /// Sets the width of an indentation level and of a tab character.
+    pub fn set_tab_size(&mut self, tab_size: usize) {
+        self.tab_size = max(tab_size, 1);
     }
 
-    /// Moves the cursor to the right.
-    fn move_right(&mut self) -> Callback {
-        self.set_curser_from_byte_offset(self.cursor.byte_offset + 1);
+    /// Sets the rules adjusting the indentation of new and typed lines, `None` disables the
+    /// auto-indentation.
+    pub fn set_indent_rules(&mut self, rules: Option<&IndentRules>) {
+        self.auto_indent = rules.is_some();
+        self.indent_increase = rules.and_then(IndentRules::increase_regex);
+        self.indent_decrease = rules.and_then(IndentRules::decrease_regex);
+    }
 
-        self.on_interact_callback().unwrap_or(Callback::dummy())
+    /// Sets the chars besides letters and digits which are part of words, like `_`.
+    ///
+    /// All word operations like transposing words or expanding snippets use them.
+    pub fn set_word_chars(&mut self, word_chars: String) {
+        self.word_chars = word_chars;
+        self.highlight_cache.borrow_mut().reset();
     }
 
-    fn is_cache_valid(&self, size: Vec2) -> bool {
-        match self.size_cache {
-            None => false,
-            Some(ref last) => last.x.accept(size.x) && last.y.accept(size.y),
-        }
+    /// Returns `true` if the char is part of a word like an identifier
+    fn is_word(&self, c: char) -> bool {
+        c.is_alphanumeric() || self.word_chars.contains(c)
     }
 
-    // If we are editing the text, we add a fake "space" character for the
-    // cursor to indicate where the next character will appear.
-    // If the current line is full, adding a character will overflow into the
-    // next line. To show that, we need to add a fake "ghost" row, just for
-    // the cursor.
-    fn fix_ghost_row(&mut self) {
-        if self.rows.is_empty() || self.rows.last().unwrap().end != self.content.len() {
-            // Add a fake, empty row at the end.
-            self.rows.push(Row {
-                start: self.content.len(),
-                end: self.content.len(),
-                width: 0,
-                is_wrapped: false,
-            });
+    /// Returns the byte ranges of the words of the text
+    fn word_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let mut words: Vec<Range<usize>> = Vec::new();
+        for (i, c) in text.char_indices().filter(|&(_, c)| self.is_word(c)) {
+            match words.last_mut() {
+                Some(word) if word.end == i => word.end = i + c.len_utf8(),
+                _ => words.push(i..i + c.len_utf8()),
+            }
         }
+        words
     }
 
-    fn compute_max_content_length(&mut self) {
-        self.max_content_width = self.rows.iter().map(|r| r.width).max().unwrap_or(1)
-            + self.rows.len().to_string().len()
-            + 1;
+    /// Sets whether the lines are numbered absolute or relative to the cursor.
+    pub fn set_line_numbers(&mut self, line_numbers: LineNumbers) {
+        self.line_numbers = line_numbers;
+    }
+
+    /// Aligns lines on the first occurrence of `delimiter` by padding the text before it.
+    ///
+    /// The lines of the column cursors are aligned, otherwise the lines around the cursor
+    /// containing the delimiter. The column cursors are kept.
+    pub fn align_rows(&mut self, delimiter: &str) -> Callback {
+        if delimiter.is_empty() {
+            return Callback::dummy();
+        }
+
+        let cursor_row = self.selected_row();
+        let rows = self.column_rows().unwrap_or_else(|| {
+            let contains = |i: &usize| self.row_text(*i).contains(delimiter);
+            let start = (0..=cursor_row)
+                .rev()
+                .take_while(contains)
+                .last()
+                .unwrap_or(cursor_row);
+            let end = (cursor_row..self.rows.len())
+                .take_while(contains)
+                .last()
+                .map_or(cursor_row, |end| end + 1);
+            start..end
+        });
+
+        // the text before the delimiter, without trailing whitespace
+        let prefixes = rows
+            .filter_map(|i| self.row_text(i).find(delimiter).map(|pos| (i, pos)))
+            .map(|(i, pos)| (i, pos, self.row_text(i)[..pos].trim_end().len()))
+            .collect::<Vec<_>>();
+        let Some(width) = prefixes
+            .iter()
+            .map(|&(i, _, len)| self.text_width(&self.row_text(i)[..len]))
+            .max()
+        else {
+            return Callback::dummy();
+        };
+        // keep a space in front of the delimiter, if it was used anywhere
+        let space = prefixes.iter().any(|&(_, pos, len)| pos != len);
+
+        // the cursor stays in front of or on the same char behind the delimiter
+        let mut cursor_in_line = self.cursor.byte_offset - self.rows[cursor_row].start;
+        let mut changed = false;
+        for &(i, pos, len) in prefixes.iter().rev() {
+            let row = self.rows[i];
+            let padding = width - self.text_width(&self.row_text(i)[..len]) + usize::from(space);
+            if self.row_text(i)[len..pos] == " ".repeat(padding) {
+                continue;
+            }
+            if i == cursor_row && cursor_in_line >= pos {
+                cursor_in_line = cursor_in_line + len + padding - pos;
+            } else if i == cursor_row {
+                cursor_in_line = min(cursor_in_line, len);
+            }
+            self.replace_range(row.start + len..row.start + pos, &" ".repeat(padding));
+            changed = true;
+        }
+
+        if changed {
+            self.set_curser_from_byte_offset(self.rows[cursor_row].start + cursor_in_line);
+            // changed stuff soooo, needing this
+            self.on_edit_callback().unwrap_or(Callback::dummy())
+        } else {
+            Callback::dummy()
+        }
+    }
+
+    /// Removes lines which are equal to the line in front of them, like `uniq`
+    ///
+    /// The lines of the column cursors are used, otherwise the whole content. The first
+    /// line of every run is kept and the cursor stays on its line, or on the kept one if its
+    /// line was removed. Returns the number of removed lines.
+    pub fn remove_duplicate_rows(&mut self) -> (usize, Callback) {
+        // the empty row after a trailing newline isn't a line of its own
+        let last_line = if self.content.ends_with('\n') {
+            self.rows.len().saturating_sub(2)
+        } else {
+            self.rows.len() - 1
+        };
+        let rows = self.column_rows().unwrap_or(0..last_line + 1);
+        let (first, last) = (rows.start, min(rows.end - 1, last_line));
+        if first >= last {
+            return (0, Callback::dummy());
+        }
+
+        let cursor_row = self.selected_row();
+        let mut kept: Vec<&str> = Vec::new();
+        let mut new_cursor_row = cursor_row;
+        for i in first..=last {
+            let line = self.row_text(i);
+            if kept.last() != Some(&line) {
+                kept.push(line);
+            }
+            if i == cursor_row {
+                new_cursor_row = first + kept.len() - 1;
+            }
+        }
+        let removed = last - first + 1 - kept.len();
+        if removed == 0 {
+            return (0, Callback::dummy());
+        }
+        if cursor_row > last {
+            new_cursor_row = cursor_row - removed;
+        }
+
+        let deduplicated = kept.join("\n");
+        let column = self.cursor.column;
+        self.replace_range(self.rows[first].start..self.rows[last].end, &deduplicated);
+        self.column_end = None;
+        self.set_curser_from_byte_offset(self.offset_at_column(new_cursor_row, column));
+        // changed stuff soooo, needing this
+        (
+            removed,
+            self.on_edit_callback().unwrap_or(Callback::dummy()),
+        )
+    }
+
+    /// Returns the byte range of the lines of the column cursors, otherwise of the whole content
+    pub fn lines_range(&self) -> Range<usize> {
+        match self.column_rows() {
+            Some(rows) => self.rows[rows.start].start..self.rows[rows.end - 1].end,
+            None => 0..self.content.len(),
+        }
+    }
+
+    /// Replaces the lines inside of the byte range, like by the output of a command
+    ///
+    /// The cursor stays at its row and column as far as possible, the column cursors are
+    /// removed.
+    pub fn replace_lines(&mut self, range: Range<usize>, text: &str) -> Callback {
+        let (row, column) = (self.selected_row(), self.cursor.column);
+        self.replace_range(range, text);
+        self.column_end = None;
+        self.set_curser_from_byte_offset(
+            self.offset_at_column(min(row, self.rows.len() - 1), column),
+        );
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Returns the indentation width of a row, `None` if it's blank
+    fn indentation(&self, row_id: usize) -> Option<usize> {
+        let text = self.row_text(row_id);
+        let content = text.trim_start();
+        (!content.is_empty()).then(|| self.text_width(&text[..text.len() - content.len()]))
+    }
+
+    /// Returns the header rows of the blocks enclosing a row, the outermost first
+    ///
+    /// Blocks are detected via the indentation, a header is the nearest row above which is
+    /// less indented. Blank rows get the indentation of the next non-blank row.
+    fn enclosing_rows(&self, row_id: usize) -> Vec<usize> {
+        let Some(mut level) = (row_id..self.rows.len()).find_map(|i| self.indentation(i)) else {
+            return Vec::new();
+        };
+
+        let mut headers = Vec::new();
+        for i in (0..row_id).rev() {
+            if level == 0 {
+                break;
+            }
+            if let Some(indentation) = self.indentation(i).filter(|&ind| ind < level) {
+                headers.push(i);
+                level = indentation;
+            }
+        }
+        headers.reverse();
+        headers
+    }
+
+    /// Returns the rows pinned to the top of the view, if the first visible row is `top`
+    ///
+    /// These are the headers of the blocks enclosing the first row below them. They never
+    /// cover the cursor or more than half of the view.
+    fn sticky_rows(&self, top: usize) -> Vec<usize> {
+        let max_lines = min(self.sticky_lines, self.page_height() / 2);
+        if max_lines == 0 {
+            return Vec::new();
+        }
+
+        // the sticky rows cover rows, whose headers may differ, so this is repeated
+        let mut sticky = Vec::new();
+        for _ in 0..=max_lines {
+            let row = min(top + sticky.len(), self.rows.len() - 1);
+            let mut headers = self.enclosing_rows(row);
+            headers.truncate(max_lines);
+            if headers == sticky {
+                break;
+            }
+            sticky = headers;
+        }
+
+        let cursor_row = self.selected_row();
+        if cursor_row >= top {
+            sticky.truncate(cursor_row - top);
+        }
+        sticky
+    }
+
+    /// Returns the byte offset and text of the visible part of a row
+    ///
+    /// Short rows are returned completely, so their highlighting has the full context.
+    /// Rows longer than `LONG_ROW` are cut to the visible columns, so drawing them
+    /// doesn't depend on their length.
+    fn visible_window<'a>(
+        &self,
+        text: &'a str,
+        printer: &Printer,
+        offset: usize,
+    ) -> (usize, &'a str) {
+        if text.len() <= LONG_ROW {
+            return (0, text);
+        }
+
+        let start_column = printer.content_offset.x.saturating_sub(offset);
+        let start = self.column_offset(text, start_column);
+        let end = self.column_offset(text, start_column + printer.output_size.x + 1);
+        (start, &text[start..end])
+    }
+
+    /// Returns the display width of the text, expanding tabs with the tab size
+    fn text_width(&self, text: &str) -> usize {
+        text.split('\t').enumerate().fold(0, |width, (i, part)| {
+            let width = if i > 0 {
+                (width / self.tab_size + 1) * self.tab_size
+            } else {
+                width
+            };
+            width + part.width()
+        })
+    }
+
+    /// Returns the byte offset of the grapheme at the given display column of the text
+    ///
+    /// This is the inverse of [`Self::text_width`], so tabs are expanded with the tab size.
+    /// Columns in the middle of a wide grapheme or a tab select it, columns behind the end
+    /// select the end of the text.
+    fn column_offset(&self, text: &str, column: usize) -> usize {
+        // printable ASCII takes a column per byte, so it's skipped without segmenting it, like
+        // long rows of minified code, only its last char may start a grapheme with the next one
+        let ascii = text
+            .bytes()
+            .take(column.saturating_add(2))
+            .take_while(|b| (b' '..=b'~').contains(b))
+            .count();
+        let skipped = ascii.saturating_sub(1);
+        if column < skipped {
+            return column;
+        }
+
+        let mut width = skipped;
+        for (offset, grapheme) in text[skipped..].grapheme_indices(true) {
+            let offset = skipped + offset;
+            width = if grapheme == "\t" {
+                (width / self.tab_size + 1) * self.tab_size
+            } else {
+                width + grapheme.width()
+            };
+            if width > column {
+                return offset;
+            }
+        }
+        text.len()
+    }
+
+    /// Sets the words which are highlighted inside of comments and their color.
+    pub fn set_markers(&mut self, markers: Vec<String>, color: Color) {
+        self.markers = markers;
+        self.marker_color = color;
+        self.highlight_cache.borrow_mut().reset();
+    }
+
+    /// Sets the dictionary for underlining misspelled words, `None` disables the spell checking.
+    pub fn set_dictionary(&mut self, dictionary: Option<Arc<Dictionary>>) {
+        self.dictionary = dictionary;
+        self.highlight_cache.borrow_mut().reset();
+    }
+
+    /// Sets the rows with a note, they're marked next to their line number
+    pub fn set_notes(&mut self, notes: BTreeSet<usize>) {
+        self.notes = notes;
+    }
+
+    /// Sets the search whose matches are highlighted, `None` clears it.
+    pub fn set_search(&mut self, search: Option<Search>) {
+        self.search = search;
+    }
+
+    /// Returns the search whose matches are highlighted.
+    pub fn search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    /// Returns the byte offset of the next or previous match of the search, the search wraps
+    /// around the end of the content
+    ///
+    /// With `skip_current` a match at the cursor isn't returned, so repeated searches continue.
+    pub fn find_match(&self, forward: bool, skip_current: bool) -> Option<usize> {
+        let search = self.search.as_ref()?;
+        let cursor = self.cursor.byte_offset;
+        let mut matches = search.matches(&self.content).map(|found| found.start);
+        if forward {
+            let first = matches.next()?;
+            iter::once(first)
+                .chain(matches)
+                .find(|&start| start > cursor || start == cursor && !skip_current)
+                .or(Some(first))
+        } else {
+            let matches = matches.collect::<Vec<_>>();
+            matches
+                .iter()
+                .rev()
+                .find(|&&start| start < cursor)
+                .or(matches.last())
+                .copied()
+        }
+    }
+
+    /// Returns the number of the match at the cursor, starting at `1`, and the number of all
+    /// matches of the search
+    ///
+    /// At most [`MAX_COUNTED_MATCHES`] are counted, the number of a match behind them is unknown.
+    pub fn match_count(&self) -> (Option<usize>, usize) {
+        let Some(search) = self.search.as_ref() else {
+            return (None, 0);
+        };
+        let cursor = self.cursor.byte_offset;
+        let mut current = None;
+        let mut total = 0;
+        for found in search.matches(&self.content).take(MAX_COUNTED_MATCHES) {
+            total += 1;
+            if found.start == cursor {
+                current = Some(total);
+            }
+        }
+        (current, total)
+    }
+
+    /// Returns the byte ranges of the matches of the search inside of a row
+    fn search_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let Some(search) = self.search.as_ref() else {
+            return Vec::new();
+        };
+        search.matches(text).collect()
+    }
+
+    /// Moves the cursor to the start of the given row.
+    pub fn set_cursor_row(&mut self, row: usize) -> Callback {
+        // Need to refresh layout, content could have been changed.
+        self.layout(self.scroll_core.last_outer_size());
+
+        let row = min(row, self.rows.len() - 1);
+        self.set_curser_from_byte_offset(self.rows[row].start)
+    }
+
+    /// Moves the cursor to the column of the given row, both starting at `0` and clamped to
+    /// the content.
+    pub fn set_cursor_row_column(&mut self, row: usize, column: usize) -> Callback {
+        // Need to refresh layout, content could have been changed.
+        self.layout(self.scroll_core.last_outer_size());
+
+        let row = self.rows[min(row, self.rows.len() - 1)];
+        let text = &self.content[row.start..row.end];
+        let offset = text
+            .char_indices()
+            .nth(column)
+            .map_or(text.len(), |(offset, _)| offset);
+        self.set_curser_from_byte_offset(row.start + offset)
+    }
+
+    /// Moves the cursor to the given byte offset, clamped to the content.
+    ///
+    /// An offset inside of a char moves the cursor to the start of its row.
+    pub fn set_cursor_byte_offset(&mut self, byte_offset: usize) -> Callback {
+        // Need to refresh layout, content could have been changed.
+        self.layout(self.scroll_core.last_outer_size());
+
+        let mut byte_offset = min(byte_offset, self.content.len());
+        if !self.content.is_char_boundary(byte_offset) {
+            byte_offset = self.rows[self.row_at(byte_offset)].start;
+        }
+        self.set_curser_from_byte_offset(byte_offset)
+    }
+
+    /// Returns the rows which have a column cursor, if there are any.
+    fn column_rows(&self) -> Option<Range<usize>> {
+        let row = self.selected_row();
+        self.column_end.map(|end| min(row, end)..max(row, end) + 1)
+    }
+
+    /// Extends the column cursors by one row in the given direction.
+    fn extend_column(&mut self, direction: Key) {
+        let end = self.column_end.unwrap_or(self.selected_row());
+        let end = match direction {
+            Key::Up => end.saturating_sub(1),
+            Key::Down => min(end + 1, self.rows.len() - 1),
+            _ => end,
+        };
+        self.column_end = Some(end).filter(|&end| end != self.selected_row());
+    }
+
+    /// Applies `edit` on every row with a column cursor.
+    ///
+    /// `edit` gets the line and the byte offset of the column within it and
+    /// returns the new cursor offset within the line, which is used for the row of the cursor.
+    /// Lines which are shorter than the column are padded with spaces if `pad` is set,
+    /// otherwise they are skipped.
+    fn edit_column<F>(&mut self, pad: bool, mut edit: F) -> Callback
+    where
+        F: FnMut(&mut String, usize) -> usize,
+    {
+        let Some(column_rows) = self.column_rows() else {
+            return Callback::dummy();
+        };
+        let column = self.selected_col();
+        let cursor_row = self.selected_row();
+
+        let mut cursor_in_line = 0;
+        let mut changed = false;
+        for i in column_rows.rev() {
+            let row = self.rows[i];
+            let mut line = self.row_text(i).to_string();
+            let width = self.text_width(&line);
+            if width < column && !pad {
+                continue;
+            }
+            if width < column {
+                line.push_str(&" ".repeat(column - width));
+            }
+            let offset = self.column_offset(&line, column);
+            let new_offset = edit(&mut line, offset);
+            if i == cursor_row {
+                cursor_in_line = new_offset;
+            }
+            if line != self.row_text(i) {
+                self.replace_range(row.start..row.end, &line);
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.set_curser_from_byte_offset(self.rows[cursor_row].start + cursor_in_line);
+            // changed stuff soooo, needing this
+            self.on_edit_callback().unwrap_or(Callback::dummy())
+        } else {
+            Callback::dummy()
+        }
+    }
+
+    /// Inserts a char at every column cursor
+    fn insert_column(&mut self, ch: char) -> Callback {
+        self.edit_column(true, |line, offset| {
+            line.insert(offset, ch);
+            offset + ch.len_utf8()
+        })
+    }
+
+    /// Removes the char before every column cursor
+    ///
+    /// Rows which are shorter than the column are skipped.
+    fn backspace_column(&mut self) -> Callback {
+        let column = self.selected_col();
+        if column == 0 {
+            return Callback::dummy();
+        }
+        self.edit_column(false, |line, offset| {
+            let start = line[..offset]
+                .grapheme_indices(true)
+                .next_back()
+                .map_or(offset, |(start, _)| start);
+            line.replace_range(start..offset, "");
+            start
+        })
+    }
+
+    /// Returns the rows which should be visible around the given row.
+    ///
+    /// The margin shrinks if the view is too small and is clamped to the content.
+    fn scroll_off_rows(&self, row: usize) -> (usize, usize) {
+        let height = self.scroll_core.last_available_size().y;
+        let margin = min(self.scroll_off, height.saturating_sub(1) / 2);
+
+        (
+            row.saturating_sub(margin),
+            min(row + margin, self.rows.len().saturating_sub(1)),
+        )
+    }
+
+    /// Sets a callback to be called whenever the cursor is modified.
+    ///
+    /// `callback` will be called with the scroll offset and the current cursor position.
+    ///
+    /// This callback can safely trigger itself recursively if needed
+    /// (for instance if you call `on_event` on this view from the callback).
+    ///
+    /// If you need a mutable closure and don't care about the recursive
+    /// aspect, see [`set_on_interact_mut`](#method.set_on_interact_mut).
+    pub fn set_on_interact<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut Cursive, Vec2, Cursor) + 'static,
+    {
+        self.on_interact = Some(Rc::new(callback));
+    }
+
+    /// Sets a callback to be called whenever the view is scrolled.
+    ///
+    /// `callback` will be called with the scroll offset and the current cursor position.
+    ///
+    /// This callback can safely trigger itself recursively if needed
+    /// (for instance if you call `on_event` on this view from the callback).
+    ///
+    /// If you need a mutable closure and don't care about the recursive
+    /// aspect, see [`set_on_scroll_mut`](#method.set_on_scroll_mut).
+    pub fn set_on_scroll<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut Cursive, Vec2, Cursor) + 'static,
+    {
+        self.on_scroll = Some(Rc::new(callback));
+    }
+
+    /// Sets a callback to be called whenever the content is modified.
+    ///
+    /// `callback` will be called with the edits since its last call, the scroll offset and the
+    /// current cursor position. Applying the edits to a copy of the content keeps it in sync.
+    ///
+    /// This callback can safely trigger itself recursively if needed
+    /// (for instance if you call `on_event` on this view from the callback).
+    ///
+    /// If you need a mutable closure and don't care about the recursive
+    /// aspect, see [`set_on_edit_mut`](#method.set_on_edit_mut).
+    pub fn set_on_edit<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut Cursive, &[Edit], Vec2, Cursor) + 'static,
+    {
+        self.on_edit = Some(Rc::new(callback));
+    }
+
+    /// Sets a callback to be called when a text larger than the limit is pasted.
+    ///
+    /// The text isn't inserted then, so the callback can ask first and insert it via
+    /// [`insert_str`](#method.insert_str). Without a limit all texts are inserted.
+    pub fn set_on_large_paste<F>(&mut self, limit: Option<usize>, callback: F)
+    where
+        F: Fn(&mut Cursive, String) + 'static,
+    {
+        self.paste_limit = limit;
+        self.on_large_paste = Some(Rc::new(callback));
+    }
+
+    /// Finds the row containing the grapheme at the given offset
+    fn row_at(&self, byte_offset: usize) -> usize {
+        assert!(!self.rows.is_empty());
+        assert!(byte_offset >= self.rows[0].start);
+
+        self.rows
+            .iter()
+            .enumerate()
+            .take_while(|&(_, row)| row.start <= byte_offset)
+            .map(|(i, _)| i)
+            .last()
+            .unwrap()
+    }
+
+    fn col_at(&self, byte_offset: usize) -> usize {
+        let row_id = self.row_at(byte_offset);
+        let row = self.rows[row_id];
+        // Number of cells to the left of the cursor
+        self.text_width(&self.content[row.start..byte_offset])
+    }
+
+    /// Finds the row containing the cursor
+    pub fn selected_row(&self) -> usize {
+        assert!(!self.rows.is_empty(), "Rows should never be empty.");
+        self.row_at(self.cursor.byte_offset)
+    }
+
+    fn selected_col(&self) -> usize {
+        self.col_at(self.cursor.byte_offset)
+    }
+
+    /// Scrolls the view by the given amount of rows, moving the cursor along.
+    ///
+    /// At the start/end of the content the view stops, but the cursor still moves.
+    fn scroll_page(&mut self, direction: Key, amount: usize) -> Callback {
+        let offset = self.scroll();
+        let row_id = self.selected_row();
+        let (offset_y, row_id) = match direction {
+            Key::Up => (
+                offset.y.saturating_sub(amount),
+                row_id.saturating_sub(amount),
+            ),
+            Key::Down => (offset.y + amount, min(row_id + amount, self.rows.len() - 1)),
+            _ => return Callback::dummy(),
+        };
+
+        self.set_byte_offset(self.offset_at_column(row_id, self.cursor.column));
+        self.scroll_core.set_offset((offset.x, offset_y));
+
+        self.on_scroll_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Returns the height of the visible content
+    fn page_height(&self) -> usize {
+        max(self.scroll_core.last_available_size().y, 1)
+    }
+
+    /// Scrolls the view so the cursor is in the center, without moving the cursor.
+    ///
+    /// The offset is applied again once the event is handled, as scrolling to the cursor
+    /// afterwards would move the view if the lines of `scroll_off` don't fit around it.
+    fn center_cursor(&mut self) -> Callback {
+        let offset = self.scroll();
+        let offset_y = self.selected_row().saturating_sub(self.page_height() / 2);
+        self.scroll_core.set_offset((offset.x, offset_y));
+        self.centered = Some(self.scroll());
+
+        self.on_scroll_callback().unwrap_or(Callback::dummy())
+    }
+
+    fn move_up(&mut self) -> Callback {
+        let row_id = self.selected_row();
+        if row_id == 0 {
+            return Callback::dummy();
+        }
+
+        self.set_byte_offset(self.offset_at_column(row_id - 1, self.cursor.column));
+
+        self.on_interact_callback().unwrap_or(Callback::dummy())
+    }
+
+    fn move_down(&mut self) -> Callback {
+        let row_id = self.selected_row();
+        if row_id + 1 == self.rows.len() {
+            return Callback::dummy();
+        }
+
+        self.set_byte_offset(self.offset_at_column(row_id + 1, self.cursor.column));
+
+        self.on_interact_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Returns the byte offset of the grapheme at the given display column of a row
+    fn offset_at_column(&self, row_id: usize, column: usize) -> usize {
+        self.rows[row_id].start + self.column_offset(self.row_text(row_id), column)
+    }
+
+    /// Moves the cursor to the left by one grapheme.
+    fn move_left(&mut self) -> Callback {
+        let len = self.content[..self.cursor.byte_offset]
+            .graphemes(true)
+            .next_back()
+            .map_or(0, str::len);
+        self.set_curser_from_byte_offset(self.cursor.byte_offset - len);
+
+        self.on_interact_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Moves the cursor to the right by one grapheme.
+    fn move_right(&mut self) -> Callback {
+        let len = self.content[self.cursor.byte_offset..]
+            .graphemes(true)
+            .next()
+            .map_or(0, str::len);
+        self.set_curser_from_byte_offset(self.cursor.byte_offset + len);
+
+        self.on_interact_callback().unwrap_or(Callback::dummy())
+    }
+
+    fn is_cache_valid(&self, size: Vec2) -> bool {
+        match self.size_cache {
+            None => false,
+            Some(ref last) => last.x.accept(size.x) && last.y.accept(size.y),
+        }
+    }
+
+    // If we are editing the text, we add a fake "space" character for the
+    // cursor to indicate where the next character will appear.
+    // If the current line is full, adding a character will overflow into the
+    // next line. To show that, we need to add a fake "ghost" row, just for
+    // the cursor.
+    fn fix_ghost_row(&mut self) {
+        if self.rows.is_empty() || self.rows.last().unwrap().end != self.content.len() {
+            // Add a fake, empty row at the end.
+            self.rows.push(Row {
+                start: self.content.len(),
+                end: self.content.len(),
+                width: 0,
+                is_wrapped: false,
+            });
+        }
+    }
+
+    fn compute_max_content_length(&mut self) {
+        let width = |row: &Row| {
+            let text = &self.content[row.start..row.end];
+            // only rows with tabs differ from their width
+            if text.contains('\t') {
+                self.text_width(text)
+            } else {
+                row.width
+            }
+        };
+        self.max_content_width =
+            self.rows.iter().map(width).max().unwrap_or(1) + self.rows.len().to_string().len() + 1;
     }
 
     fn compute_rows(&mut self, size: Vec2) {
@@ -465,6 +1650,7 @@ impl EditArea {
 
         self.rows = make_rows(&self.content);
         self.fix_ghost_row();
+        self.highlight_cache.borrow_mut().clear();
 
         // also compute here the max content length
         self.compute_max_content_length();
@@ -479,6 +1665,419 @@ impl EditArea {
         self.delete()
     }
 
+    /// Returns the char directly before the cursor
+    fn prev_char(&self) -> Option<char> {
+        self.content[..self.cursor.byte_offset].chars().next_back()
+    }
+
+    /// Returns the char directly after the cursor
+    fn next_char(&self) -> Option<char> {
+        self.content[self.cursor.byte_offset..].chars().next()
+    }
+
+    /// Checks via the current highlighting if the cursor is inside a string or comment.
+    ///
+    /// Only the current line is parsed, so multi-line strings/comments aren't detected.
+    /// Of long rows only the last `LONG_ROW` bytes in front of the cursor are parsed.
+    fn in_string_or_comment(&self) -> bool {
+        let row = self.rows[self.selected_row()];
+        let mut start = max(row.start, self.cursor.byte_offset.saturating_sub(LONG_ROW));
+        while !self.content.is_char_boundary(start) {
+            start += 1;
+        }
+        let line = &self.content[start..self.cursor.byte_offset];
+
+        let mut parse_state = ParseState::new(&self.synref);
+        let mut stack = ScopeStack::new();
+        if let Ok(ops) = parse_state.parse_line(line, &self.syntax) {
+            for (_, op) in ops {
+                if stack.apply(&op).is_err() {
+                    return false;
+                }
+            }
+        }
+
+        stack.as_slice().iter().any(|scope| {
+            let scope = scope.build_string();
+            scope.starts_with("string") || scope.starts_with("comment")
+        })
+    }
+
+    /// Parses a single line and returns the byte ranges which are inside of one of the scopes
+    ///
+    /// Scopes are matched by their prefix, like `comment` or `string`.
+    fn scope_ranges(&self, line: &str, scopes: &[&str]) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+
+        let mut parse_state = ParseState::new(&self.synref);
+        let Ok(ops) = parse_state.parse_line(line, &self.syntax) else {
+            return ranges;
+        };
+
+        let mut stack = ScopeStack::new();
+        let mut last = 0;
+        for (pos, op) in ops
+            .into_iter()
+            .chain(iter::once((line.len(), ScopeStackOp::Noop)))
+        {
+            let in_scope = stack.as_slice().iter().any(|scope| {
+                let scope = scope.build_string();
+                scopes.iter().any(|prefix| scope.starts_with(prefix))
+            });
+            if pos > last && in_scope {
+                match ranges.last_mut() {
+                    Some(range) if range.end == last => range.end = pos,
+                    _ => ranges.push(last..pos),
+                }
+            }
+            last = pos;
+            if stack.apply(&op).is_err() {
+                break;
+            }
+        }
+
+        ranges
+    }
+
+    /// Returns the highlighted text of a row, using the cache if possible
+    ///
+    /// The rows in front of it are highlighted first if needed, so multi-line
+    /// strings and comments are highlighted correctly. If there are too many of them, the row
+    /// is shown as plain text until [`Self::highlight_ahead`] reached it.
+    fn highlight_row(&self, row_id: usize) -> StyledString {
+        let mut cache = self.highlight_cache.borrow_mut();
+        let cache = &mut *cache;
+        if self.cb_sink.is_some() && row_id >= cache.valid.max(1) + BLOCKING_ROWS {
+            let row = self.rows[row_id];
+            return StyledString::plain(&self.content[row.start..row.end]);
+        }
+        self.highlight_until(cache, row_id, None);
+
+        if let Some(Some(line)) = cache.lines.get(row_id) {
+            return line.clone();
+        }
+        let state = cache.states[row_id].clone().unwrap();
+        let (line, _) = self.highlight_line(row_id, state, &mut cache.by_text);
+        set_at(&mut cache.lines, row_id, line.clone());
+        line.unwrap_or_default()
+    }
+
+    /// Highlights the rows in front of the last shown one for a step, if there are too many to
+    /// highlight them at once
+    ///
+    /// Every step redraws the editor which takes the next one, so events are handled in between
+    /// and the editor doesn't block.
+    fn highlight_ahead(&self, last_row: usize) {
+        let Some(cb_sink) = &self.cb_sink else {
+            return;
+        };
+        let mut cache = self.highlight_cache.borrow_mut();
+        if !self.highlighted || last_row < cache.valid.max(1) + BLOCKING_ROWS {
+            return;
+        }
+        let until = last_row.saturating_sub(BLOCKING_ROWS);
+        self.highlight_until(&mut cache, until, Some(Instant::now() + HIGHLIGHT_STEP));
+        let _ = cb_sink.send(Box::new(|_| {}));
+    }
+
+    /// Computes the states at the start of the rows up to the given one, or until the deadline
+    ///
+    /// With a deadline only the states are computed, the rows are highlighted once shown.
+    fn highlight_until(
+        &self,
+        cache: &mut HighlightCache,
+        row_id: usize,
+        deadline: Option<Instant>,
+    ) {
+        // edits before the first row was highlighted may have added unknown states already
+        if cache.valid == 0 {
+            let highlighter = Highlighter::new(&self.theme);
+            let state = (
+                ParseState::new(&self.synref),
+                HighlightState::new(&highlighter, ScopeStack::new()),
+            );
+            set_at(&mut cache.states, 0, Some(state));
+            cache.valid = 1;
+            cache.converged = 1;
+        }
+
+        while cache.valid <= row_id {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return;
+            }
+            let k = cache.valid - 1;
+            let state = cache.states[k].clone().unwrap();
+            let next = if deadline.is_some() {
+                self.next_state(k, state)
+            } else {
+                let (line, next) = self.highlight_line(k, state, &mut cache.by_text);
+                set_at(&mut cache.lines, k, line);
+                next
+            };
+
+            let next = Some(next);
+            if k + 1 < cache.converged && cache.states.get(k + 1) == Some(&next) {
+                // the following rows are highlighted like before
+                cache.valid = cache.converged;
+            } else {
+                set_at(&mut cache.states, k + 1, next);
+                set_at(&mut cache.lines, k + 1, None);
+                cache.valid += 1;
+            }
+        }
+    }
+
+    /// Highlights a row starting with the given state, returning the state for the next row
+    ///
+    /// Rows with the same text and state as one highlighted before are taken from `by_text`.
+    /// Long rows are skipped, they are highlighted on their own via [`Self::highlight_window`].
+    fn highlight_line(
+        &self,
+        row_id: usize,
+        state: LineState,
+        by_text: &mut HashMap<u64, HighlightedLine>,
+    ) -> (Option<StyledString>, LineState) {
+        let row = self.rows[row_id];
+        if row.end - row.start > LONG_ROW {
+            return (None, state);
+        }
+
+        // the newline is needed for the syntax to end single-line comments etc.
+        let end = self.rows.get(row_id + 1).map_or(row.end, |next| next.start);
+        let text = &self.content[row.start..end];
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let key = hasher.finish();
+        if let Some((start, line, next)) = by_text.get(&key) {
+            if *start == state {
+                return (line.clone(), next.clone());
+            }
+        }
+
+        let (parse_state, highlight_state) = state.clone();
+        let mut highlighter = HighlightLines::from_state(&self.theme, highlight_state, parse_state);
+        let mut styled = StyledString::new();
+        match highlighter.highlight_line(text, &self.syntax) {
+            Ok(ranges) => {
+                for (style, part) in ranges {
+                    let part = part.trim_end_matches('\n');
+                    styled.append_styled(part, cursive_syntect::translate_style(style));
+                }
+            }
+            Err(_) => styled.append_plain(&self.content[row.start..row.end]),
+        }
+        let (highlight_state, parse_state) = highlighter.state();
+
+        let text = &self.content[row.start..row.end];
+        let styled = self.highlight_misspelled(text, self.highlight_markers(text, styled));
+        let next = (parse_state, highlight_state);
+        if by_text.len() >= HIGHLIGHT_CACHE_SIZE {
+            by_text.clear();
+        }
+        by_text.insert(key, (state, Some(styled.clone()), next.clone()));
+        (Some(styled), next)
+    }
+
+    /// Returns the state for the next row, without highlighting the row
+    fn next_state(&self, row_id: usize, state: LineState) -> LineState {
+        let row = self.rows[row_id];
+        if row.end - row.start > LONG_ROW {
+            return state;
+        }
+        let end = self.rows.get(row_id + 1).map_or(row.end, |next| next.start);
+        let (parse_state, highlight_state) = state;
+        let mut highlighter = HighlightLines::from_state(&self.theme, highlight_state, parse_state);
+        let _ = highlighter.highlight_line(&self.content[row.start..end], &self.syntax);
+        let (highlight_state, parse_state) = highlighter.state();
+        (parse_state, highlight_state)
+    }
+
+    /// Highlights a part of a long row on its own
+    fn highlight_window(&self, window: &str) -> StyledString {
+        let mut highlighter = HighlightLines::new(&self.synref, &self.theme);
+        let styled =
+            cursive_syntect::parse(window, &mut highlighter, &self.syntax).unwrap_or_default();
+        self.highlight_misspelled(window, self.highlight_markers(window, styled))
+    }
+
+    /// Highlights the markers like `TODO` which are inside of comments
+    fn highlight_markers(&self, line: &str, styled: StyledString) -> StyledString {
+        let mut found = self
+            .markers
+            .iter()
+            .flat_map(|marker| {
+                line.match_indices(marker.as_str())
+                    .map(|(start, marker)| start..start + marker.len())
+            })
+            .filter(|range| {
+                !line[..range.start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| self.is_word(c))
+                    && !line[range.end..]
+                        .chars()
+                        .next()
+                        .is_some_and(|c| self.is_word(c))
+            })
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            return styled;
+        }
+
+        let comments = self.scope_ranges(line, &["comment"]);
+        found.retain(|range| {
+            comments
+                .iter()
+                .any(|comment| comment.start <= range.start && range.end <= comment.end)
+        });
+        found.sort_by_key(|range| range.start);
+        if found.is_empty() {
+            return styled;
+        }
+
+        let marker_style = Style::from(self.marker_color);
+        restyle(line, &styled, &found, |_| marker_style)
+    }
+
+    /// Returns the byte ranges of the misspelled words of a line
+    ///
+    /// For prose like markdown or plain text all words are checked, otherwise only the ones
+    /// inside of comments and strings.
+    fn misspelled_ranges(&self, line: &str) -> Vec<Range<usize>> {
+        let Some(dictionary) = &self.dictionary else {
+            return Vec::new();
+        };
+        let mut found = dictionary.misspelled(line);
+        if found.is_empty() || matches!(self.synref.name.as_str(), "Plain Text" | "Markdown") {
+            return found;
+        }
+
+        let checked = self.scope_ranges(line, &["comment", "string"]);
+        found.retain(|range| {
+            checked
+                .iter()
+                .any(|checked| checked.start <= range.start && range.end <= checked.end)
+        });
+        found
+    }
+
+    /// Underlines the misspelled words
+    fn highlight_misspelled(&self, line: &str, styled: StyledString) -> StyledString {
+        let found = self.misspelled_ranges(line);
+        if found.is_empty() {
+            return styled;
+        }
+        restyle(line, &styled, &found, |style| {
+            style.combine(Effect::Underline)
+        })
+    }
+
+    /// Returns the byte range and text of the misspelled word at the cursor, or directly in
+    /// front of it
+    pub fn misspelled_at_cursor(&self) -> Option<(Range<usize>, String)> {
+        let row = self.rows[self.selected_row()];
+        let cursor_in_line = self.cursor.byte_offset - row.start;
+        let found = self.misspelled_ranges(self.current_line());
+        let word = found
+            .iter()
+            .find(|word| word.contains(&cursor_in_line))
+            .or_else(|| found.iter().find(|word| word.end == cursor_in_line))?;
+        let range = row.start + word.start..row.start + word.end;
+        Some((range.clone(), self.content[range].to_string()))
+    }
+
+    /// Replaces a byte range of the content with the text, placing the cursor behind it
+    pub fn replace_text(&mut self, range: Range<usize>, text: &str) -> Callback {
+        let start = range.start;
+        self.replace_range(range, text);
+        self.set_curser_from_byte_offset(start + text.len());
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Inserts a char, respecting auto-pairs
+    ///
+    /// Typing an existing closing char steps over it, typing an opening char
+    /// also inserts the closing char and places the cursor between them.
+    /// Inside of HTML and XML typing the `>` of an opening tag also inserts its closing tag.
+    fn insert_paired(&mut self, ch: char) -> Callback {
+        // pasted text is inserted verbatim
+        if !self.auto_pairs || paste::is_pasting() {
+            return self.insert(ch);
+        }
+
+        if self.pairs.iter().any(|&(_, close)| close == ch) && self.next_char() == Some(ch) {
+            let callback = self.move_right();
+            return self.complete_tag(ch).unwrap_or(callback);
+        }
+
+        if let Some(&(_, close)) = self.pairs.iter().find(|&&(open, _)| open == ch) {
+            // quotes directly after a word are most likely apostrophes
+            let is_quote = ch == close;
+            let after_word = self.prev_char().is_some_and(|c| self.is_word(c));
+            if !(is_quote && after_word || self.in_string_or_comment()) {
+                self.insert(ch);
+                self.insert(close);
+                self.move_left();
+                return self.on_edit_callback().unwrap_or_else(Callback::dummy);
+            }
+        }
+
+        let callback = self.insert(ch);
+        self.complete_tag(ch).unwrap_or(callback)
+    }
+
+    /// Inserts the closing tag behind the cursor if the typed char finished an opening tag
+    ///
+    /// Only done inside of HTML and XML, void elements like `<br>` and self-closing tags like
+    /// `<br/>` aren't closed.
+    fn complete_tag(&mut self, typed: char) -> Option<Callback> {
+        let scope = self.synref.scope.build_string();
+        if typed != '>' || !(scope.starts_with("text.html") || scope.starts_with("text.xml")) {
+            return None;
+        }
+
+        let row = self.rows[self.selected_row()];
+        let before = self.content[row.start..self.cursor.byte_offset].strip_suffix('>')?;
+        let tag = &before[before.rfind('<')? + 1..];
+        if tag.ends_with('/') || tag.contains('>') {
+            return None;
+        }
+        let name = tag
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .filter(|name| {
+                name.starts_with(char::is_alphabetic)
+                    && name
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || matches!(c, '-' | ':' | '.' | '_'))
+            })?
+            .to_string();
+        if VOID_ELEMENTS.contains(&name.to_lowercase().as_str()) {
+            return None;
+        }
+
+        let offset = self.cursor.byte_offset;
+        self.insert_str(&format!("</{name}>"));
+        self.set_curser_from_byte_offset(offset);
+        self.on_edit_callback()
+    }
+
+    /// Removes the char before the cursor, also removing the closing char of an empty pair
+    fn backspace_paired(&mut self) -> Callback {
+        if self.auto_pairs {
+            if let (Some(prev), Some(next)) = (self.prev_char(), self.next_char()) {
+                if self.pairs.contains(&(prev, next)) {
+                    self.move_left();
+                    self.delete();
+                    return self.delete();
+                }
+            }
+        }
+
+        self.backspace()
+    }
+
     fn delete(&mut self) -> Callback {
         if self.cursor.byte_offset == self.content.len() {
             return Callback::dummy();
@@ -491,6 +2090,10 @@ impl EditArea {
         let start = self.cursor.byte_offset;
         let end = start + len;
         for _ in self.content.drain(start..end) {}
+        self.edits.borrow_mut().push(Edit {
+            range: start..end,
+            text: String::new(),
+        });
 
         let selected_row = self.selected_row();
         if self.cursor.byte_offset == self.rows[selected_row].end {
@@ -499,6 +2102,9 @@ impl EditArea {
             let new_end = self.rows[selected_row + 1].end;
             self.rows[selected_row].end = new_end;
             self.rows.remove(selected_row + 1);
+            self.highlight_cache.borrow_mut().splice(selected_row, 2, 1);
+        } else {
+            self.highlight_cache.borrow_mut().splice(selected_row, 1, 1);
         }
         self.rows[selected_row].end -= len;
 
@@ -515,6 +2121,10 @@ impl EditArea {
         // First, we inject the data, but keep the cursor unmoved
         // (So the cursor is to the left of the injected char)
         self.content.insert(self.cursor.byte_offset, ch);
+        self.edits.borrow_mut().push(Edit {
+            range: self.cursor.byte_offset..self.cursor.byte_offset,
+            text: ch.to_string(),
+        });
 
         // Then, we shift the indexes of every row after this one.
         let shift = ch.len_utf8();
@@ -522,6 +2132,7 @@ impl EditArea {
         // The current row grows, every other is just shifted.
         let selected_row = self.selected_row();
         self.rows[selected_row].end += shift;
+        self.highlight_cache.borrow_mut().splice(selected_row, 1, 1);
 
         for row in &mut self.rows.iter_mut().skip(1 + selected_row) {
             row.shift(shift);
@@ -540,220 +2151,623 @@ impl EditArea {
         self.on_edit_callback().unwrap_or_else(Callback::dummy)
     }
 
-    /// Copies the line where the cursor currently is
-    fn copy(&mut self) {
-        let content = self.get_content().to_string();
-        let cursor_pos = self.cursor().byte_offset;
+    /// Inserts a newline, indenting the new line like the line of the cursor
+    ///
+    /// After a line matching the increase rule, like one ending with `{`, the new line is
+    /// indented one level deeper. If the text behind the cursor matches the decrease rule, like
+    /// the `}` closing this `{`, it's moved onto its own line below the cursor.
+    fn new_line(&mut self) -> Callback {
+        // pasted text is inserted verbatim
+        if !self.auto_indent || paste::is_pasting() {
+            return self.insert('\n');
+        }
+
+        let cursor_pos = self.cursor.byte_offset;
+        let row = self.rows[self.selected_row()];
+        let before = &self.content[row.start..cursor_pos];
+        let after = &self.content[cursor_pos..row.end];
+        let indent = &before[..before.len() - before.trim_start().len()];
+
+        let mut text = format!("\n{indent}");
+        let mut cursor_end = cursor_pos + text.len();
+        if matches_rule(&self.indent_increase, before) {
+            if indent.contains('\t') {
+                text.push('\t');
+            } else {
+                text.push_str(&" ".repeat(self.tab_size));
+            }
+            cursor_end = cursor_pos + text.len();
+            if matches_rule(&self.indent_decrease, after) {
+                text.push('\n');
+                text.push_str(indent);
+            }
+        }
+        self.replace_range(cursor_pos..cursor_pos, &text);
+        self.set_curser_from_byte_offset(cursor_end);
+        self.on_edit_callback().unwrap_or_else(Callback::dummy)
+    }
 
-        let (current_line, _) = Self::get_cursor_line_info(&content, cursor_pos);
+    /// Indents the line of the cursor one level less if the typed char made it match the
+    /// decrease rule, like a `}` or the `:` of an `else:`
+    ///
+    /// Only lines indented like expected from the line above are changed, so lines which were
+    /// indented by hand keep their indentation.
+    fn dedent_typed(&mut self, matched_before: bool) -> Option<Callback> {
+        if matched_before || !self.auto_indent || paste::is_pasting() {
+            return None;
+        }
+        let row_id = self.selected_row();
+        let line = self.current_line();
+        if !matches_rule(&self.indent_decrease, line) {
+            return None;
+        }
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let width = self.text_width(indent);
+        let uses_tabs = indent.contains('\t');
+        let indent_len = indent.len();
+
+        let above = (0..row_id).rev().find(|&i| self.indentation(i).is_some())?;
+        let mut expected = self.indentation(above)?;
+        if matches_rule(&self.indent_increase, self.row_text(above)) {
+            expected += self.tab_size;
+        }
+        if width == 0 || width != expected {
+            return None;
+        }
 
-        let lines: Vec<&str> = content.split('\n').collect();
+        let level = width.saturating_sub(self.tab_size);
+        let new_indent = if uses_tabs {
+            "\t".repeat(level / self.tab_size)
+        } else {
+            " ".repeat(level)
+        };
+        let start = self.rows[row_id].start;
+        let cursor_pos = max(self.cursor.byte_offset, start + indent_len);
+        self.replace_range(start..start + indent_len, &new_indent);
+        self.set_curser_from_byte_offset(cursor_pos - indent_len + new_indent.len());
+        Some(self.on_edit_callback().unwrap_or_else(Callback::dummy))
+    }
 
-        crate::clipboard::set_content(lines[current_line].to_string() + "\n")
-            .unwrap_or_else(|e| error!("{e}"));
+    /// Copies the line where the cursor currently is
+    fn copy(&mut self) {
+        let line = self.current_line();
+
+        crate::clipboard::set_content(line.to_string() + "\n").unwrap_or_else(|e| error!("{e}"));
     }
 
     /// Pasts the current clipboard
     fn paste(&mut self) -> Callback {
-        let content = self.get_content().to_string();
-        let cursor_pos = self.cursor().byte_offset;
-
-        let (current_line, cursor_in_line) = Self::get_cursor_line_info(&content, cursor_pos);
-
-        let mut lines: Vec<&str> = content.split('\n').collect();
-        if let Ok(text) = crate::clipboard::get_content() {
-            let split = lines[current_line].split_at(cursor_in_line);
-            let inserted_line = split.0.to_string() + text.as_str() + split.1;
-            lines[current_line] = inserted_line.as_str();
-
-            let new_content: String = lines.join("\n");
-            if new_content != content {
-                self.set_content(new_content);
-                self.set_curser_from_byte_offset(cursor_pos + text.to_string().len());
-                // changed stuff soooo, needing this
-                self.on_edit_callback().unwrap_or(Callback::dummy())
-            } else {
-                Callback::dummy()
-            }
-        } else {
-            Callback::dummy()
+        match crate::clipboard::get_content() {
+            Ok(text) => self.paste_text(text),
+            _ => Callback::dummy(),
         }
     }
 
-    /// Cuts the line where the cursor currently is
-    fn cut(&mut self) -> Callback {
-        let content = self.get_content().to_string();
-        let cursor_pos = self.cursor().byte_offset;
+    /// Inserts the pasted text at the cursor, a text above the paste limit is passed to the
+    /// large paste callback instead.
+    fn paste_text(&mut self, text: String) -> Callback {
+        let large = self.paste_limit.is_some_and(|limit| text.len() > limit);
+        match self.on_large_paste.clone().filter(|_| large) {
+            Some(on_large_paste) => Callback::from_fn(move |siv| on_large_paste(siv, text.clone())),
+            None => self.insert_str(&text),
+        }
+    }
+
+    /// Inserts the text at the cursor, placing the cursor behind it.
+    pub fn insert_str(&mut self, text: &str) -> Callback {
+        if text.is_empty() {
+            return Callback::dummy();
+        }
+        let cursor_pos = self.cursor.byte_offset;
+        self.replace_range(cursor_pos..cursor_pos, text);
+        self.set_curser_from_byte_offset(cursor_pos + text.len());
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
 
-        let (current_line, current_line_pos) = Self::get_cursor_line_info(&content, cursor_pos);
+    /// Appends the text at the end, placing the cursor behind it.
+    ///
+    /// This isn't an edit, like new content of the file on disk, so only the
+    /// interact callback is run.
+    pub fn append(&mut self, text: &str) -> Callback {
+        // Need to refresh layout, content could have been changed.
+        self.layout(self.scroll_core.last_outer_size());
+
+        let end = self.content.len();
+        self.replace_range(end..end, text);
+        // the text is already part of the stored content
+        self.edits.borrow_mut().pop();
+        self.set_curser_from_byte_offset(self.content.len())
+    }
 
-        let mut lines: Vec<&str> = content.split('\n').collect();
-        crate::clipboard::set_content(lines[current_line].to_string() + "\n")
+    /// Cuts the line where the cursor currently is
+    fn cut(&mut self) -> Callback {
+        let row_id = self.selected_row();
+        let row = self.rows[row_id];
+        crate::clipboard::set_content(self.row_text(row_id).to_string() + "\n")
             .unwrap_or_else(|e| error!("{e}"));
-        lines.remove(current_line);
 
-        let new_content: String = lines.join("\n");
-        if new_content != content {
-            self.set_curser_from_byte_offset(cursor_pos - current_line_pos);
-            self.set_content(new_content);
-            // changed stuff soooo, needing this
-            self.on_edit_callback().unwrap_or(Callback::dummy())
+        // the line is removed with its newline, for the last line the one in front of it
+        let range = if row_id + 1 < self.rows.len() {
+            row.start..self.rows[row_id + 1].start
         } else {
-            Callback::dummy()
+            row.start.saturating_sub(1)..row.end
+        };
+        if range.is_empty() {
+            return Callback::dummy();
         }
+
+        self.replace_range(range, "");
+        self.set_curser_from_byte_offset(min(row.start, self.content.len()));
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
     }
 
     /// Implements the tabulator
     fn tabulator(&mut self, ident: bool) -> Callback {
-        let content = self.get_content().to_string();
-        let cursor_pos = self.cursor().byte_offset;
-
-        let (current_line, current_line_position) =
-            Self::get_cursor_line_info(&content, cursor_pos);
-        let mut lines: Vec<&str> = content.split('\n').collect();
-        let tab_size = 4;
+        let cursor_pos = self.cursor.byte_offset;
+        let row = self.rows[self.selected_row()];
+        let tab_size = self.tab_size;
 
         let str_to_add = " ".repeat(tab_size);
 
-        let new_content = if ident {
-            let new_line = str_to_add + lines[current_line];
+        if ident {
+            self.replace_range(row.start..row.start, &str_to_add);
+            self.set_curser_from_byte_offset(cursor_pos + tab_size);
+        } else {
+            let Some(pos) = self.content[row.start..row.end].find(&str_to_add) else {
+                return Callback::dummy();
+            };
+            let (start, end) = (row.start + pos, row.start + pos + tab_size);
+            self.replace_range(start..end, "");
+            // the cursor stays on the same char
+            let cursor_pos = if cursor_pos >= end {
+                cursor_pos - tab_size
+            } else {
+                min(cursor_pos, start)
+            };
+            self.set_curser_from_byte_offset(cursor_pos);
+        }
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Expands the snippet whose trigger word stands right before the cursor
+    ///
+    /// The cursor is placed at the first tab-stop, the others are visited by `Tab`.
+    fn expand_snippet(&mut self) -> Option<Callback> {
+        let cursor_pos = self.cursor.byte_offset;
+        let row = self.rows[self.selected_row()];
+        let before = &self.content[row.start..cursor_pos];
+        let start = before
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| self.is_word(c))
+            .last()
+            .map(|(i, _)| row.start + i)?;
+        let body = self.snippets.get(&self.content[start..cursor_pos])?;
+
+        let line = &self.content[row.start..row.end];
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let snippet = snippet::expand(body, indent, &self.date_format);
+        self.replace_range(start..cursor_pos, &snippet.text);
+
+        let mut stops = snippet
+            .stops
+            .into_iter()
+            .map(|stop| start + stop.start..start + stop.end)
+            .collect::<VecDeque<_>>();
+        let first = stops.pop_front().unwrap_or_else(|| {
+            let end = start + snippet.text.len();
+            end..end
+        });
+        self.set_curser_from_byte_offset(first.end);
+        self.snippet_stops = Some(SnippetStops {
+            stops,
+            placeholder: first,
+            content_len: self.content.len(),
+        });
+        self.on_edit_callback()
+    }
+
+    /// Moves the cursor to the end of the next tab-stop of the last expanded snippet
+    fn next_snippet_stop(&mut self) -> Option<Callback> {
+        let snippet_stops = self.snippet_stops.as_mut()?;
+        snippet_stops.update(self.content.len());
+        let stop = snippet_stops.stops.pop_front().filter(|stop| {
+            stop.end <= self.content.len()
+                && self.content.is_char_boundary(stop.start)
+                && self.content.is_char_boundary(stop.end)
+        });
+        let Some(stop) = stop else {
+            self.snippet_stops = None;
+            return None;
+        };
+        snippet_stops.placeholder = stop.clone();
+        self.set_curser_from_byte_offset(stop.end);
+        self.on_interact_callback()
+            .or_else(|| Some(Callback::dummy()))
+    }
+
+    /// Removes the placeholder of the current tab-stop, if nothing was typed there yet
+    fn remove_placeholder(&mut self) {
+        let Some(snippet_stops) = self.snippet_stops.as_mut() else {
+            return;
+        };
+        let placeholder = mem::take(&mut snippet_stops.placeholder);
+        if !placeholder.is_empty()
+            && snippet_stops.content_len == self.content.len()
+            && self.cursor.byte_offset == placeholder.end
+        {
+            self.replace_range(placeholder.clone(), "");
+            self.set_curser_from_byte_offset(placeholder.start);
+        }
+    }
+
+    /// Joins the line of the cursor with the next one, or all lines of the column cursors
+    ///
+    /// The newlines and the leading whitespace of the joined lines are replaced by a single
+    /// space. The cursor is placed at the first join point.
+    fn join_lines(&mut self) -> Callback {
+        // the empty row after a trailing newline isn't a line of its own
+        let last_line = if self.content.ends_with('\n') {
+            self.rows.len().saturating_sub(2)
+        } else {
+            self.rows.len() - 1
+        };
+        let row_id = self.selected_row();
+        let rows = self.column_rows().unwrap_or(row_id..row_id + 2);
+        let (first, last) = (
+            rows.start,
+            min(max(rows.end - 1, rows.start + 1), last_line),
+        );
+        if first >= last {
+            return Callback::dummy();
+        }
+
+        let mut joined = self.row_text(first).to_string();
+        let mut join_point = None;
+        for i in first + 1..=last {
+            let next = self.row_text(i).trim_start();
+            join_point.get_or_insert(joined.len());
+            if next.is_empty() {
+                continue;
+            }
+            if !joined.is_empty() && !joined.ends_with(char::is_whitespace) {
+                joined.push(' ');
+            }
+            joined.push_str(next);
+        }
+
+        let start = self.rows[first].start;
+        self.replace_range(start..self.rows[last].end, &joined);
+        self.set_curser_from_byte_offset(start + join_point.unwrap_or_default());
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Swaps the graphemes before and at the cursor, moving the cursor behind both
+    ///
+    /// At the end of a line the two graphemes before the cursor are swapped. Graphemes
+    /// of different lines are never swapped.
+    fn transpose_chars(&mut self) -> Callback {
+        let row = self.rows[self.selected_row()];
+        let text = self.current_line();
+        let cursor_in_line = self.cursor.byte_offset - row.start;
+        let graphemes = text.grapheme_indices(true).collect::<Vec<_>>();
+        if graphemes.len() < 2 {
+            return Callback::dummy();
+        }
+        let next = graphemes.partition_point(|&(i, _)| i < cursor_in_line);
+        // at the end of the line the last two are swapped
+        let next = if next == graphemes.len() {
+            next - 1
+        } else {
+            next
+        };
+        if next == 0 {
+            return Callback::dummy();
+        }
+
+        let (start, first) = graphemes[next - 1];
+        let (_, second) = graphemes[next];
+        let end = start + first.len() + second.len();
+        let swapped = format!("{second}{first}");
+        self.replace_range(row.start + start..row.start + end, &swapped);
+        self.set_curser_from_byte_offset(row.start + end);
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Swaps the word before or at the cursor with the next one, moving the cursor behind both
+    ///
+    /// Behind the last word of a line, it's swapped with the word in front of it.
+    fn transpose_words(&mut self) -> Callback {
+        let row = self.rows[self.selected_row()];
+        let text = self.current_line();
+        let cursor_in_line = self.cursor.byte_offset - row.start;
+        let words = self.word_ranges(text);
+        if words.len() < 2 {
+            return Callback::dummy();
+        }
+        let first = words
+            .iter()
+            .rposition(|word| word.start < cursor_in_line)
+            .unwrap_or_default()
+            .min(words.len() - 2);
+
+        let (first, second) = (words[first].clone(), words[first + 1].clone());
+        let swapped = format!(
+            "{}{}{}",
+            &text[second.clone()],
+            &text[first.end..second.start],
+            &text[first.clone()]
+        );
+        self.replace_range(row.start + first.start..row.start + second.end, &swapped);
+        self.set_curser_from_byte_offset(row.start + second.end);
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Converts the case of the word at the cursor, or directly in front of it
+    ///
+    /// The cursor stays at its position, if the length of the word changes it's placed
+    /// behind the word.
+    fn change_case(&mut self, case: Case) -> Callback {
+        let row = self.rows[self.selected_row()];
+        let cursor_in_line = self.cursor.byte_offset - row.start;
+        let words = self.word_ranges(self.current_line());
+        let Some(word) = words
+            .iter()
+            .find(|word| word.contains(&cursor_in_line))
+            .or_else(|| words.iter().find(|word| word.end == cursor_in_line))
+        else {
+            return Callback::dummy();
+        };
+
+        let range = row.start + word.start..row.start + word.end;
+        let converted = case.convert(&self.content[range.clone()]);
+        if converted == self.content[range.clone()] {
+            return Callback::dummy();
+        }
+        let cursor = if converted.len() == range.len() {
+            self.cursor.byte_offset
+        } else {
+            range.start + converted.len()
+        };
+        self.replace_range(range, &converted);
+        self.set_curser_from_byte_offset(cursor);
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
+
+    /// Converts the case of all words of the lines of the column cursors
+    ///
+    /// The column cursors are kept, so the lines can be converted again. The cursor stays at
+    /// the same char of its line as far as possible.
+    fn change_case_column(&mut self, case: Case) -> Callback {
+        let Some(rows) = self.column_rows() else {
+            return Callback::dummy();
+        };
+        let range = self.rows[rows.start].start..self.rows[rows.end - 1].end;
+        let text = &self.content[range.clone()];
+        let mut converted = String::with_capacity(text.len());
+        let mut last = 0;
+        for word in self.word_ranges(text) {
+            converted.push_str(&text[last..word.start]);
+            converted.push_str(&case.convert(&text[word.clone()]));
+            last = word.end;
+        }
+        converted.push_str(&text[last..]);
+        if converted == text {
+            return Callback::dummy();
+        }
 
-            self.set_curser_from_byte_offset(cursor_pos + tab_size);
+        let row_id = self.selected_row();
+        let char_index = self.cursor.char_index;
+        self.replace_range(range, &converted);
+        let row = self.rows[row_id];
+        let cursor = self
+            .row_text(row_id)
+            .char_indices()
+            .nth(char_index)
+            .map_or(row.end, |(i, _)| row.start + i);
+        self.set_curser_from_byte_offset(cursor);
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
 
-            lines[current_line] = &new_line;
-            lines.join("\n")
-        } else {
-            let new_line = lines[current_line].replacen(&str_to_add, "", 1);
+    /// Adds the step to the number at or behind the cursor, like `41` becoming `42`
+    ///
+    /// The cursor is moved onto the last digit of the number.
+    fn increment(&mut self, step: i64) -> Callback {
+        let row = self.rows[self.selected_row()];
+        let cursor_in_line = self.cursor.byte_offset - row.start;
+        let Some((range, number)) = add_to_number(self.current_line(), cursor_in_line, step) else {
+            return Callback::dummy();
+        };
 
-            if lines[current_line] != new_line {
-                self.set_curser_from_byte_offset(cursor_pos - min(current_line_position, tab_size));
-            }
+        let range = row.start + range.start..row.start + range.end;
+        let last_digit = range.start + number.len() - 1;
+        self.replace_range(range, &number);
+        self.set_curser_from_byte_offset(last_digit);
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
 
-            lines[current_line] = &new_line;
-            lines.join("\n")
+    /// Adds the step to the number at or behind the column cursor of each of its lines
+    ///
+    /// The column cursors are kept, so the numbers can be changed again.
+    fn increment_column(&mut self, step: i64) -> Callback {
+        let Some(rows) = self.column_rows() else {
+            return Callback::dummy();
         };
-        if new_content != content {
-            self.set_content(new_content);
-            // changed stuff soooo, needing this
-            self.on_edit_callback().unwrap_or(Callback::dummy())
-        } else {
-            Callback::dummy()
+        let row_id = self.selected_row();
+        let char_index = self.cursor.char_index;
+        let mut changed = false;
+        // from the last line on, so the lines in front of it don't move
+        for other_id in rows.rev() {
+            let row = self.rows[other_id];
+            let text = self.row_text(other_id);
+            let column = text
+                .char_indices()
+                .nth(char_index)
+                .map_or(text.len(), |(i, _)| i);
+            if let Some((range, number)) = add_to_number(text, column, step) {
+                self.replace_range(row.start + range.start..row.start + range.end, &number);
+                changed = true;
+            }
+        }
+        if !changed {
+            return Callback::dummy();
         }
+
+        let row = self.rows[row_id];
+        let cursor = self
+            .row_text(row_id)
+            .char_indices()
+            .nth(char_index)
+            .map_or(row.end, |(i, _)| row.start + i);
+        self.set_curser_from_byte_offset(cursor);
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
     }
 
     /// Moves the line withing the cursor in the specified direction
     fn move_line(&mut self, direction: Key) -> Callback {
-        let content = self.get_content().to_string();
-        let cursor_pos = self.cursor().byte_offset;
-
-        let (current_line, cursor_in_line) = Self::get_cursor_line_info(&content, cursor_pos);
-
-        let mut lines: Vec<&str> = content.split('\n').collect();
-
-        if (current_line == 0 && direction == Key::Up)
-            || (current_line == lines.len() - 1 && direction == Key::Down)
-        {
+        let row_id = self.selected_row();
+        let other_id = match direction {
+            Key::Up if row_id > 0 => row_id - 1,
+            Key::Down if row_id + 1 < self.rows.len() => row_id + 1,
+            _ => return Callback::dummy(),
+        };
+        let cursor_in_line = self.cursor.byte_offset - self.rows[row_id].start;
+
+        let (upper, lower) = (
+            self.rows[min(row_id, other_id)],
+            self.rows[max(row_id, other_id)],
+        );
+        let upper_text = self.content[upper.start..upper.end].to_string();
+        let lower_text = self.content[lower.start..lower.end].to_string();
+        if upper_text == lower_text {
             return Callback::dummy();
         }
+        self.replace_range(
+            upper.start..lower.end,
+            &format!("{lower_text}\n{upper_text}"),
+        );
+
+        let new_row = self.rows[other_id];
+        self.set_curser_from_byte_offset(new_row.start + cursor_in_line);
+        // changed stuff soooo, needing this
+        self.on_edit_callback().unwrap_or(Callback::dummy())
+    }
 
-        let line_to_move = lines.remove(current_line);
+    /// Move cursor to the start or end of the current line
+    fn move_cursor_end(&mut self, direction: Key) -> Callback {
+        let row = self.rows[self.selected_row()];
         match direction {
-            Key::Up => lines.insert(current_line - 1, line_to_move),
-            Key::Down => lines.insert(current_line + 1, line_to_move),
-            _ => {}
+            Key::Left => self.set_curser_from_byte_offset(row.start),
+            Key::Right => self.set_curser_from_byte_offset(row.end),
+            _ => Callback::dummy(),
         }
+    }
 
-        let new_cursor_pos = if direction == Key::Up && current_line > 0 {
-            lines
+    /// Moves the cursor to the end of the next word or to the start of the previous one
+    ///
+    /// Without a word in that direction the cursor moves to the end or start of the line,
+    /// and from there onto the next or previous line.
+    fn move_word(&mut self, direction: Key) -> Callback {
+        let row = self.rows[self.selected_row()];
+        let cursor_in_line = self.cursor.byte_offset - row.start;
+        let words = self.word_ranges(self.current_line());
+        let target = match direction {
+            Key::Left => words
                 .iter()
-                .take(current_line - 1)
-                .map(|line| line.len() + 1)
-                .sum::<usize>()
-                + cursor_in_line
-        } else {
-            lines
+                .rev()
+                .find(|word| word.start < cursor_in_line)
+                .map(|word| row.start + word.start),
+            _ => words
                 .iter()
-                .take(current_line + (if direction == Key::Down { 1 } else { 0 }))
-                .map(|line| line.len() + 1)
-                .sum::<usize>()
-                + cursor_in_line
+                .find(|word| word.end > cursor_in_line)
+                .map(|word| row.start + word.end),
         };
-
-        self.set_curser_from_byte_offset(new_cursor_pos);
-
-        let new_content: String = lines.join("\n");
-        if new_content != content {
-            self.set_content(new_content);
-            // changed stuff soooo, needing this
-            self.on_edit_callback().unwrap_or(Callback::dummy())
-        } else {
-            Callback::dummy()
+        match (target, direction) {
+            (Some(target), _) => self.set_curser_from_byte_offset(target),
+            (None, Key::Left) if self.cursor.byte_offset > row.start => {
+                self.set_curser_from_byte_offset(row.start)
+            }
+            (None, Key::Left) => self.move_left(),
+            (None, _) if self.cursor.byte_offset < row.end => {
+                self.set_curser_from_byte_offset(row.end)
+            }
+            (None, _) => self.move_right(),
         }
     }
 
-    /// Move cursor to the start or end of the current line
-    fn move_cursor_end(&mut self, direction: Key) -> Callback {
-        let content = self.get_content().to_string();
-        let cursor_pos = self.cursor().byte_offset;
-
-        let (current_line, _) = Self::get_cursor_line_info(&content, cursor_pos);
-
-        let lines: Vec<&str> = content.split('\n').collect();
-        match direction {
-            Key::Left => {
-                let new_cursor_pos = lines
-                    .iter()
-                    .take(current_line)
-                    .map(|line| line.len() + 1)
-                    .sum::<usize>();
-                self.set_curser_from_byte_offset(new_cursor_pos)
-            }
-            Key::Right => {
-                let new_cursor_pos = if current_line < lines.len() {
-                    lines
-                        .iter()
-                        .take(current_line + 1)
-                        .map(|line| line.len() + 1)
-                        .sum::<usize>()
-                        - 1
-                } else {
-                    content.len()
-                };
-                self.set_curser_from_byte_offset(new_cursor_pos)
-            }
-            _ => Callback::dummy(),
-        }
+    /// Returns the text of a row, without its newline
+    fn row_text(&self, row_id: usize) -> &str {
+        let row = self.rows[row_id];
+        &self.content[row.start..row.end]
     }
 
-    /// Returns the current line number and the cursor's position within that line
-    fn get_cursor_line_info(content: &str, cursor_pos: usize) -> (usize, usize) {
-        let lines: Vec<&str> = content.split('\n').collect();
-        let mut current_line = 0;
-        let mut cursor_in_line = 0;
-        let mut count = 0;
+    /// Replaces a byte range of the content, only re-computing the affected rows
+    ///
+    /// The cursor isn't moved, so it needs to be set afterwards.
+    fn replace_range(&mut self, range: Range<usize>, text: &str) {
+        let first_row = self.row_at(range.start);
+        let last_row = self.row_at(range.end);
+        let start = self.rows[first_row].start;
+        let end = self.rows[last_row].end;
+
+        self.content.replace_range(range.clone(), text);
+        self.edits.borrow_mut().push(Edit {
+            range: range.clone(),
+            text: text.to_string(),
+        });
+        let new_end = end - range.len() + text.len();
+
+        let replaced = &self.content[start..new_end];
+        let mut new_rows = make_rows(replaced)
+            .into_iter()
+            .map(|row| row.shifted(start))
+            .collect::<Vec<_>>();
+        if replaced.is_empty() || replaced.ends_with('\n') {
+            // an empty last line isn't a row on its own
+            new_rows.push(Row {
+                start: new_end,
+                end: new_end,
+                width: 0,
+                is_wrapped: false,
+            });
+        }
 
-        for (i, line) in lines.iter().enumerate() {
-            let line_len = line.len() + 1;
-            if count + line_len > cursor_pos {
-                current_line = i;
-                cursor_in_line = cursor_pos - count;
-                break;
+        // update all the rows downstream
+        for row in self.rows.iter_mut().skip(last_row + 1) {
+            if new_end >= end {
+                row.shift(new_end - end);
+            } else {
+                row.rev_shift(end - new_end);
             }
-            count += line_len;
         }
+        self.highlight_cache.borrow_mut().splice(
+            first_row,
+            last_row - first_row + 1,
+            new_rows.len(),
+        );
+        self.rows.splice(first_row..=last_row, new_rows);
 
-        (current_line, cursor_in_line)
+        self.fix_ghost_row();
+        self.compute_max_content_length();
     }
 
     fn on_interact_callback(&self) -> Option<Callback> {
         self.on_interact.clone().map(|cb| {
-            // Get a new Rc on the content
-            let content = self.content.clone();
             let scroll_offset = self.scroll_core.content_viewport().top_left();
             let cursor = self.cursor;
 
             Callback::from_fn(move |s| {
-                cb(s, &content, scroll_offset, cursor);
+                cb(s, scroll_offset, cursor);
             })
         })
     }
@@ -761,26 +2775,24 @@ impl EditArea {
     /// Run any callback after scrolling.
     fn on_scroll_callback(&mut self) -> Option<Callback> {
         self.on_scroll.clone().map(|cb| {
-            // Get a new Rc on the content
-            let content = self.content.clone();
             let scroll_offset = self.scroll_core.content_viewport().top_left();
             let cursor = self.cursor;
 
             Callback::from_fn(move |s| {
-                cb(s, &content, scroll_offset, cursor);
+                cb(s, scroll_offset, cursor);
             })
         })
     }
 
     fn on_edit_callback(&self) -> Option<Callback> {
         self.on_edit.clone().map(|cb| {
-            // Get a new Rc on the content
-            let content = self.content.clone();
+            let edits = self.edits.clone();
             let scroll_offset = self.scroll_core.content_viewport().top_left();
             let cursor = self.cursor;
 
             Callback::from_fn(move |s| {
-                cb(s, &content, scroll_offset, cursor);
+                let edits = mem::take(&mut *edits.borrow_mut());
+                cb(s, &edits, scroll_offset, cursor);
             })
         })
     }
@@ -835,6 +2847,9 @@ impl EditArea {
 
         // Otherwise, replace stuff.
         let affected_rows = first_row..last_row;
+        self.highlight_cache
+            .borrow_mut()
+            .splice(first_row, last_row - first_row, new_rows.len());
         let replacement_rows = new_rows.into_iter().map(|row| row.shifted(first_byte));
         self.rows.splice(affected_rows, replacement_rows);
         // other fix
@@ -845,19 +2860,80 @@ impl EditArea {
 
     // Events inside the text field
     fn inner_on_event(&mut self, event: Event) -> EventResult {
-        if !self.enabled {
+        if !self.enabled || self.read_only && !is_navigation(&event) {
             return EventResult::Ignored;
         }
 
+        // the highlighted matches are cleared before the global `Esc` shows the infos
+        if event == Event::Key(Key::Esc) && self.search.is_some() {
+            self.search = None;
+            return EventResult::consumed();
+        }
+
+        if matches!(event, Event::Char(_)) && self.column_end.is_none() {
+            self.remove_placeholder();
+        }
+
+        if self.column_end.is_some() {
+            match event {
+                Event::Char(ch) => {
+                    return EventResult::Consumed(Some(self.insert_column(ch)));
+                }
+                Event::Key(Key::Backspace) => {
+                    return EventResult::Consumed(Some(self.backspace_column()));
+                }
+                Event::Key(Key::F6) => {
+                    return EventResult::Consumed(Some(self.change_case_column(Case::Upper)));
+                }
+                Event::Key(Key::F7) => {
+                    return EventResult::Consumed(Some(self.change_case_column(Case::Lower)));
+                }
+                Event::Key(Key::F8) => {
+                    return EventResult::Consumed(Some(self.change_case_column(Case::Title)));
+                }
+                Event::Ctrl(Key::PageUp) => {
+                    return EventResult::Consumed(Some(self.increment_column(1)));
+                }
+                Event::Ctrl(Key::PageDown) => {
+                    return EventResult::Consumed(Some(self.increment_column(-1)));
+                }
+                _ => {}
+            }
+        }
+
+        let result = self.edit_event(event.clone());
+        // typing inside of a snippet keeps its remaining tab-stops
+        if result.is_consumed() {
+            match event {
+                Event::Char(_) | Event::Key(Key::Tab) => {}
+                Event::Key(Key::Backspace | Key::Del | Key::Enter) => {
+                    if let Some(snippet_stops) = self.snippet_stops.as_mut() {
+                        snippet_stops.placeholder = 0..0;
+                    }
+                }
+                _ => self.snippet_stops = None,
+            }
+        }
+        // every other handled event removes the column cursors
+        if result.is_consumed() && !matches!(event, Event::Ctrl(Key::Up | Key::Down)) {
+            self.column_end = None;
+        }
+        result
+    }
+
+    // Editing events without column cursors
+    fn edit_event(&mut self, event: Event) -> EventResult {
         match event {
             Event::Char(ch) => {
-                return EventResult::Consumed(Some(self.insert(ch)));
+                let matched = matches_rule(&self.indent_decrease, self.current_line());
+                let callback = self.insert_paired(ch);
+                return EventResult::Consumed(Some(self.dedent_typed(matched).unwrap_or(callback)));
             }
             Event::Key(Key::Enter) => {
-                return EventResult::Consumed(Some(self.insert('\n')));
+                return EventResult::Consumed(Some(self.new_line()));
             }
             Event::Key(Key::Backspace) if self.cursor.byte_offset > 0 => {
-                return EventResult::Consumed(Some(self.backspace()));
+                return EventResult::Consumed(Some(self.backspace_paired()));
             }
             Event::Key(Key::Del) if self.cursor.byte_offset < self.content.len() => {
                 return EventResult::Consumed(Some(self.delete()));
@@ -890,10 +2966,31 @@ impl EditArea {
                 }
             }
             Event::Key(Key::PageUp) => {
-                return EventResult::Consumed(Some(self.page_up()));
+                let height = self.page_height();
+                return EventResult::Consumed(Some(self.scroll_page(Key::Up, height)));
             }
             Event::Key(Key::PageDown) => {
-                return EventResult::Consumed(Some(self.page_down()));
+                let height = self.page_height();
+                return EventResult::Consumed(Some(self.scroll_page(Key::Down, height)));
+            }
+            Event::Shift(Key::PageUp) => {
+                let height = self.page_height() / 2;
+                return EventResult::Consumed(Some(self.scroll_page(Key::Up, height)));
+            }
+            Event::Shift(Key::PageDown) => {
+                let height = self.page_height() / 2;
+                return EventResult::Consumed(Some(self.scroll_page(Key::Down, height)));
+            }
+            Event::CtrlChar('l') => {
+                return EventResult::Consumed(Some(self.center_cursor()));
+            }
+            Event::Ctrl(Key::Up) => {
+                self.extend_column(Key::Up);
+                return EventResult::Consumed(self.on_interact_callback());
+            }
+            Event::Ctrl(Key::Down) => {
+                self.extend_column(Key::Down);
+                return EventResult::Consumed(self.on_interact_callback());
             }
             Event::Key(Key::Left) => {
                 if self.cursor.byte_offset > 0 {
@@ -919,11 +3016,9 @@ impl EditArea {
                         let x = position
                             .x
                             .saturating_sub(self.rows.len().to_string().len() + 1);
-                        let row = &self.rows[y];
-                        let content = &self.content[row.start..row.end];
-                        return EventResult::Consumed(Some(self.set_curser_from_byte_offset(
-                            row.start + simple_prefix(content, x).length,
-                        )));
+                        return EventResult::Consumed(Some(
+                            self.set_curser_from_byte_offset(self.offset_at_column(y, x)),
+                        ));
                     }
                 }
             }
@@ -934,6 +3029,30 @@ impl EditArea {
             Event::CtrlChar('x') => {
                 return EventResult::Consumed(Some(self.cut()));
             }
+            Event::CtrlChar('k') => {
+                return EventResult::Consumed(Some(self.join_lines()));
+            }
+            Event::CtrlChar('e') => {
+                return EventResult::Consumed(Some(self.transpose_chars()));
+            }
+            Event::CtrlChar('y') => {
+                return EventResult::Consumed(Some(self.transpose_words()));
+            }
+            Event::Key(Key::F6) => {
+                return EventResult::Consumed(Some(self.change_case(Case::Upper)));
+            }
+            Event::Key(Key::F7) => {
+                return EventResult::Consumed(Some(self.change_case(Case::Lower)));
+            }
+            Event::Key(Key::F8) => {
+                return EventResult::Consumed(Some(self.change_case(Case::Title)));
+            }
+            Event::Ctrl(Key::PageUp) => {
+                return EventResult::Consumed(Some(self.increment(1)));
+            }
+            Event::Ctrl(Key::PageDown) => {
+                return EventResult::Consumed(Some(self.increment(-1)));
+            }
             Event::Shift(Key::Up) => {
                 return EventResult::Consumed(Some(self.move_line(Key::Up)));
             }
@@ -946,12 +3065,31 @@ impl EditArea {
             Event::Shift(Key::Right) => {
                 return EventResult::Consumed(Some(self.move_cursor_end(Key::Right)));
             }
+            Event::Ctrl(Key::Left) => {
+                return EventResult::Consumed(Some(self.move_word(Key::Left)));
+            }
+            Event::Ctrl(Key::Right) => {
+                return EventResult::Consumed(Some(self.move_word(Key::Right)));
+            }
+            Event::Key(Key::Tab) if paste::is_pasting() => {
+                return EventResult::Consumed(Some(self.insert('\t')));
+            }
             Event::Key(Key::Tab) => {
-                return EventResult::Consumed(Some(self.tabulator(true)));
+                let callback = self.next_snippet_stop().or_else(|| self.expand_snippet());
+                return EventResult::Consumed(Some(
+                    callback.unwrap_or_else(|| self.tabulator(true)),
+                ));
             }
             Event::Shift(Key::Tab) => {
                 return EventResult::Consumed(Some(self.tabulator(false)));
             }
+            // a whole bracketed paste is inserted at once
+            Event::Unknown(_) => {
+                return match paste::pasted_text(&event) {
+                    Some(text) => EventResult::Consumed(Some(self.paste_text(text))),
+                    None => EventResult::Ignored,
+                };
+            }
             _ => return EventResult::Ignored,
         }
 
@@ -968,87 +3106,227 @@ impl EditArea {
     }
 
     fn inner_important_area(&self, _: Vec2) -> Rect {
+        self.cursor_area()
+    }
+
+    /// The area around the cursor which has to stay visible, including the rows of `scroll_off`
+    ///
+    /// The line numbers are drawn in front of the text, so the cell of the cursor is behind them.
+    fn cursor_area(&self) -> Rect {
         // The important area is a single character
         let char_width = if self.cursor.byte_offset >= self.content.len() {
             // If we're are the end of the content, it'll be a space
             1
         } else {
             // Otherwise it's the selected grapheme
-            self.content[self.cursor.byte_offset..]
+            let grapheme = self.content[self.cursor.byte_offset..]
                 .graphemes(true)
                 .next()
-                .unwrap()
-                .width()
+                .unwrap();
+            max(grapheme.width(), 1)
         };
 
+        let (top, bottom) = self.scroll_off_rows(self.selected_row());
         Rect::from_size(
-            Vec2::new(self.selected_col(), self.selected_row()),
-            (char_width + self.rows.len().to_string().len() + 2, 1),
+            Vec2::new(self.selected_col(), top),
+            (
+                char_width + self.rows.len().to_string().len() + 2,
+                bottom - top + 1,
+            ),
         )
     }
-}
 
-impl View for EditArea {
-    fn draw(&self, printer: &Printer) {
-        printer.with_style(PaletteStyle::Primary, |printer| {
-            scroll::draw_lines(self, printer, |edit_area, printer, i| {
-                let row = &edit_area.rows[i];
-                let text = edit_area.content[row.start..row.end].to_string();
+    /// Draws a single row, the printer needs to be cropped to it
+    ///
+    /// Sticky rows are drawn without cursors.
+    fn draw_row(&self, printer: &Printer, i: usize, sticky: bool) {
+        let row = &self.rows[i];
+        let text = &self.content[row.start..row.end];
+
+        // Check if file needs to be numbered.
+        let numbering = if printer.enabled && self.enabled {
+            // Calculate max digits for better visual representation.
+            let max_lines_count_digits = self.rows.len().to_string().len();
+
+            let cursor_row = self.selected_row();
+            let number = match self.line_numbers {
+                LineNumbers::Relative => i.abs_diff(cursor_row),
+                LineNumbers::Hybrid if i != cursor_row => i.abs_diff(cursor_row),
+                _ => i + 1,
+            };
+            let line_number = format!("{number:max_lines_count_digits$}");
+
+            let number_style = if i == cursor_row {
+                Style::default()
+            } else {
+                Color::Light(BaseColor::Black).into()
+            };
+            let mut numbering = SpannedString::styled(line_number, number_style);
+            // a note is marked in the space behind the number
+            if self.notes.contains(&i) {
+                numbering.append_styled("•", Color::Dark(BaseColor::Yellow));
+            } else {
+                numbering.append_plain(" ");
+            }
+            numbering
+        } else {
+            SpannedString::default()
+        };
+
+        // only the visible part of long lines is highlighted
+        let (window_start, window) = self.visible_window(text, printer, numbering.width());
+        let styled = if !self.highlighted {
+            StyledString::plain(window)
+        } else if window.len() == text.len() {
+            self.highlight_row(i)
+        } else {
+            self.highlight_window(window)
+        };
+
+        // tabs are expanded to the next multiple of the tab size, counted from the
+        // start of the text, the part behind the `overflow` column is highlighted
+        let tab_size = self.tab_size;
+        let print_spans = |mut column: usize, x: usize, line: &StyledString, overflow: usize| {
+            for span in line.spans() {
+                let front = span.attr.color.front;
+                // underlines like the ones of misspelled words are kept
+                let effect = if span.attr.effects.contains(Effect::Underline) {
+                    Effect::Underline
+                } else {
+                    Effect::Simple
+                };
+                printer.with_effect(effect, |printer| {
+                    for (i, part) in span.content.split('\t').enumerate() {
+                        if i > 0 {
+                            column = (column / tab_size + 1) * tab_size;
+                        }
+                        let split = self.column_offset(part, overflow.saturating_sub(column));
+                        let (before, after) = part.split_at(split);
+                        printer.with_style(
+                            ColorStyle::new(front, PaletteColor::Background),
+                            |printer| {
+                                printer.print((x + column, 0), before);
+                            },
+                        );
+                        printer.with_style(
+                            ColorStyle::new(front, Color::Dark(BaseColor::Red)),
+                            |printer| {
+                                printer.print((x + column + before.width(), 0), after);
+                            },
+                        );
+                        column += part.width();
+                    }
+                });
+            }
+        };
+        print_spans(0, 0, &numbering, usize::MAX);
+        let window_column = self.text_width(&text[..window_start]);
+        let overflow = match self.rulers.iter().max() {
+            Some(&ruler) if self.highlight_overflow => ruler,
+            _ => usize::MAX,
+        };
+        print_spans(window_column, numbering.width(), &styled, overflow);
 
-                let mut highlighter =
-                    syntect::easy::HighlightLines::new(&edit_area.synref, &edit_area.theme);
+        // the rulers are only visible behind the end of the line
+        let width = if text.contains('\t') {
+            self.text_width(text)
+        } else {
+            row.width
+        };
+        printer.with_color(Color::Light(BaseColor::Black).into(), |printer| {
+            for &ruler in self.rulers.iter().filter(|&&ruler| ruler >= width) {
+                printer.print((numbering.width() + ruler, 0), "│");
+            }
+        });
 
-                let styled = cursive_syntect::parse(&text, &mut highlighter, &edit_area.syntax)
-                    .unwrap_or_default();
+        // all matches of the search are highlighted, the one at the cursor differently
+        for range in self.search_ranges(text) {
+            let start = row.start + range.start;
+            let back = if (start..row.start + range.end).contains(&self.cursor.byte_offset) {
+                Color::Light(BaseColor::Yellow)
+            } else {
+                Color::Dark(BaseColor::Yellow)
+            };
+            let offset = self.text_width(&text[..range.start]) + numbering.width();
+            printer.with_color(
+                ColorStyle::new(Color::Dark(BaseColor::Black), back),
+                |printer| printer.print((offset, 0), &text[range].replace('\t', " ")),
+            );
+        }
 
-                // Check if file needs to be numbered.
-                let numbering = if printer.enabled && edit_area.enabled {
-                    // Calculate max digits for better visual representation.
-                    let max_lines_count_digits = edit_area.rows.len().to_string().len();
+        if printer.focused
+            && !sticky
+            && printer.enabled
+            && self.enabled
+            && i != self.selected_row()
+            && self.column_rows().is_some_and(|rows| rows.contains(&i))
+        {
+            // column cursors on shorter lines are drawn at the end of the line
+            let column = self.selected_col();
+            let cursor_offset = self.column_offset(text, column);
+            let selected_char = selected_grapheme(&text[cursor_offset..]);
+            let offset = self.text_width(&text[..cursor_offset]) + numbering.width();
+            printer.with_style(Style::primary().combine(Effect::Reverse), |printer| {
+                printer.print((offset, 0), selected_char);
+            });
+        }
 
-                    let line_number = format!("{:width$} ", i + 1, width = max_lines_count_digits);
+        if printer.focused && !sticky && i == self.selected_row() && printer.enabled && self.enabled
+        {
+            // the terminal draws the cursor itself, in the configured shape
+            let cursor_offset = self.cursor.byte_offset - row.start;
+            let offset = self.text_width(&text[..cursor_offset]) + numbering.width();
+            cursor::show(printer, Vec2::new(offset, 0));
+        }
+    }
+}
 
-                    let number_style = if i == edit_area.selected_row() {
-                        Style::default()
-                    } else {
-                        Color::Light(BaseColor::Black).into()
-                    };
-                    SpannedString::styled(line_number, number_style)
-                } else {
-                    SpannedString::default()
-                };
+impl View for EditArea {
+    fn draw(&self, printer: &Printer) {
+        if !self.enabled && !self.placeholder.is_empty() {
+            let lines: Vec<&str> = self.placeholder.lines().collect();
+            let top = printer.size.y.saturating_sub(lines.len()) / 2;
+            let width = lines
+                .iter()
+                .map(|line| line.width())
+                .max()
+                .unwrap_or_default();
+            let left = printer.size.x.saturating_sub(width) / 2;
+            printer.with_color(Color::Light(BaseColor::Black).into(), |printer| {
+                for (i, line) in lines.iter().enumerate() {
+                    printer.print((left, top + i), line);
+                }
+            });
+            return;
+        }
 
-                let line = StyledString::concatenate(vec![numbering.clone(), styled]);
-
-                let mut x = 0;
-                for span in line.spans() {
-                    printer.with_style(
-                        ColorStyle::new(span.attr.color.front, PaletteColor::Background),
-                        |printer| {
-                            printer.print((x, 0), span.content);
-                            x += span.content.width();
-                        },
-                    );
+        printer.with_style(PaletteStyle::Primary, |printer| {
+            scroll::draw(self, printer, |edit_area, printer| {
+                let top = printer.content_offset.y;
+                let bottom = top + printer.output_size.y;
+                edit_area.highlight_ahead(bottom.min(edit_area.rows.len()).saturating_sub(1));
+                for i in top..bottom {
+                    let printer = printer.offset((0, i)).cropped((printer.size.x, 1));
+                    edit_area.draw_row(&printer, i, false);
                 }
 
-                if printer.focused
-                    && i == edit_area.selected_row()
-                    && printer.enabled
-                    && edit_area.enabled
-                {
-                    let cursor_offset = edit_area.cursor.byte_offset - row.start;
-                    let mut c = StyledString::new();
-                    let selected_char = if cursor_offset == text.len() {
-                        " "
+                // the sticky rows are drawn above the content, the last one is underlined
+                let sticky = edit_area.sticky_rows(top);
+                for (k, &i) in sticky.iter().enumerate() {
+                    let printer = printer.offset((0, top + k)).cropped((printer.size.x, 1));
+                    let effect = if k + 1 == sticky.len() {
+                        Effect::Underline
                     } else {
-                        text[cursor_offset..]
-                            .graphemes(true)
-                            .next()
-                            .expect("Found no char!")
+                        Effect::Simple
                     };
-                    c.append_styled(selected_char, Style::primary().combine(Effect::Reverse));
-                    let offset = text[..cursor_offset].width() + numbering.width();
-                    printer.print_styled((offset, 0), &c);
+                    printer.with_effect(effect, |printer| {
+                        printer.print_hline(
+                            (printer.content_offset.x, 0),
+                            printer.output_size.x,
+                            " ",
+                        );
+                        edit_area.draw_row(printer, i, true);
+                    });
                 }
             });
         });
@@ -1059,12 +3337,16 @@ impl View for EditArea {
     }
 
     fn on_event(&mut self, event: Event) -> EventResult {
-        match scroll::on_event(
+        let result = scroll::on_event(
             self,
             event,
             Self::inner_on_event,
             Self::inner_important_area,
-        ) {
+        );
+        if let Some(centered) = self.centered.take() {
+            self.scroll_core.set_offset(centered);
+        }
+        match result {
             EventResult::Ignored => EventResult::Ignored,
             // If the event was consumed, then we may have scrolled.
             other => other.and(EventResult::Consumed(self.on_scroll_callback())),
@@ -1076,10 +3358,572 @@ impl View for EditArea {
     }
 
     fn layout(&mut self, size: Vec2) {
+        let resized = self.scroll_core.last_outer_size() != size;
         scroll::layout(self, size, true, |_s, _size| (), Self::inner_required_size);
+
+        // the cursor stays visible if the terminal is resized, scrolling away stays possible
+        if resized && self.enabled {
+            self.scroll_core.scroll_to_rect(self.cursor_area());
+        }
     }
 
     fn important_area(&self, size: Vec2) -> Rect {
         scroll::important_area(self, size, Self::inner_important_area)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cursive::{backends::puppet::observed::ObservedScreen, event::MouseButton};
+    use syntect::highlighting::ThemeSet;
+
+    use super::*;
+
+    /// Returns an editor showing the content with the cursor at the byte offset
+    fn with_content(content: &str, byte_offset: usize) -> EditArea {
+        let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+        let mut edit_area = EditArea::new(Arc::new(SyntaxSet::load_defaults_newlines()), theme);
+        edit_area.set_content(content);
+        edit_area.set_cursor_byte_offset(byte_offset);
+        edit_area
+    }
+
+    /// Draws the editor and returns the screen
+    fn screen(edit_area: &mut EditArea, size: Vec2) -> ObservedScreen {
+        let mut backend = cursive::backends::puppet::Backend::init(Some(size));
+        let screens = backend.stream();
+        edit_area.layout(size);
+        let theme = cursive::theme::Theme::default();
+        edit_area.draw(&Printer::new(size, &theme, &*backend));
+        cursive::backend::Backend::refresh(&mut *backend);
+        screens.try_iter().last().unwrap()
+    }
+
+    /// Draws the editor and returns the shown rows without trailing spaces
+    fn draw(edit_area: &mut EditArea, size: Vec2) -> Vec<String> {
+        let screen = screen(edit_area, size);
+        (0..size.y)
+            .map(|y| {
+                (0..size.x)
+                    // cells which weren't drawn are empty, the second halves of wide chars
+                    // have no letter
+                    .filter_map(|x| match &screen[Vec2::new(x, y)] {
+                        Some(cell) => cell.letter.as_option().cloned(),
+                        None => Some(" ".to_string()),
+                    })
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn long_rows_show_their_visible_part() {
+        let long = format!("let s = \"{}\"; // end", "ab".repeat(LONG_ROW));
+        let mut edit_area = highlighted(&format!("{long}\nfn main() {{}}"));
+        let size = Vec2::new(30, 4);
+        assert!(draw(&mut edit_area, size)[0].starts_with("1 let s = \"abab"));
+
+        // scrolled to the end of the long row, the line number takes 2 columns
+        edit_area.set_cursor_byte_offset(long.len());
+        let rows = draw(&mut edit_area, size);
+        let start = edit_area.scroll().x - 2;
+        assert!(start > LONG_ROW);
+        // the cell of the cursor behind the end is visible as well
+        assert!(long.len() - start < size.x);
+        assert_eq!(rows[0].trim_end(), &long[start..]);
+        assert_eq!(
+            edit_area.highlight_window(&long[start..]).source(),
+            &long[start..]
+        );
+    }
+
+    #[test]
+    fn rows_appended_before_the_first_draw_are_highlighted() {
+        let mut edit_area = highlighted("fn a() {}\n");
+        drop(edit_area.append("fn b() {}\n"));
+        let rows = draw(&mut edit_area, Vec2::new(20, 3));
+        assert_eq!(rows[1].trim_end(), "2 fn b() {}");
+    }
+
+    #[test]
+    fn edits_keep_a_copy_in_sync() {
+        let copy = Rc::new(RefCell::new(String::from("fn main() {\n}")));
+        let mut edit_area = with_content(&copy.borrow(), 11);
+        let synced = copy.clone();
+        edit_area.set_on_edit(move |_, edits, _, _| {
+            for edit in edits {
+                synced
+                    .borrow_mut()
+                    .replace_range(edit.range.clone(), &edit.text);
+            }
+        });
+
+        let mut siv = Cursive::new();
+        for event in [
+            Event::Char('a'),
+            Event::Key(Key::Enter),
+            Event::Char('ä'),
+            Event::Key(Key::Backspace),
+            Event::Key(Key::Del),
+            Event::CtrlChar('e'),
+            Event::CtrlChar('k'),
+        ] {
+            edit_area.on_event(event).process(&mut siv);
+            assert_eq!(*copy.borrow(), edit_area.get_content());
+        }
+
+        // the edits of a dropped callback are passed to the next one
+        drop(edit_area.insert('b'));
+        edit_area.insert('c')(&mut siv);
+        assert_eq!(*copy.borrow(), edit_area.get_content());
+
+        // appending isn't an edit, the text is appended to the copy by the caller
+        edit_area.append("tail")(&mut siv);
+        copy.borrow_mut().push_str("tail");
+        edit_area.insert('d')(&mut siv);
+        assert_eq!(*copy.borrow(), edit_area.get_content());
+    }
+
+    /// Moves the cursor via the key and returns its byte offset and column
+    fn press(edit_area: &mut EditArea, key: Key) -> (usize, usize) {
+        edit_area.on_event(Event::Key(key));
+        (edit_area.cursor().byte_offset, edit_area.cursor().column)
+    }
+
+    #[test]
+    fn cursor_moves_by_graphemes() {
+        // an emoji taking two cells and an `e` with a combining tilde taking one
+        let mut edit_area = with_content("a😀e\u{303}b", 0);
+        assert_eq!(press(&mut edit_area, Key::Right), (1, 1));
+        assert_eq!(press(&mut edit_area, Key::Right), (5, 3));
+        assert_eq!(press(&mut edit_area, Key::Right), (8, 4));
+        assert_eq!(press(&mut edit_area, Key::Left), (5, 3));
+        assert_eq!(press(&mut edit_area, Key::Left), (1, 1));
+
+        edit_area.on_event(Event::Key(Key::Del));
+        assert_eq!(edit_area.get_content(), "ae\u{303}b");
+        edit_area.set_cursor_byte_offset(4);
+        edit_area.on_event(Event::Key(Key::Backspace));
+        assert_eq!(edit_area.get_content(), "ab");
+    }
+
+    #[test]
+    fn vertical_moves_keep_the_cell_column() {
+        let mut edit_area = with_content("abcdef\n日本語\nabcd", 4);
+        assert_eq!(press(&mut edit_area, Key::Down), (13, 4));
+        assert_eq!(edit_area.cursor().char_index, 2);
+        assert_eq!(press(&mut edit_area, Key::Down), (21, 4));
+        assert_eq!(press(&mut edit_area, Key::Up), (13, 4));
+        assert_eq!(press(&mut edit_area, Key::Up), (4, 4));
+
+        // the fourth cell is the second half of `本`, which is selected, the column is kept for
+        // the next vertical move
+        edit_area.set_cursor_byte_offset(3);
+        assert_eq!(press(&mut edit_area, Key::Down), (10, 3));
+        assert_eq!(press(&mut edit_area, Key::Down), (20, 3));
+    }
+
+    #[test]
+    fn wide_chars_are_drawn_in_their_cells() {
+        let mut edit_area = highlighted("let s = \"日本\"; // 😀 x\nlet a = 1;");
+        let rows = draw(&mut edit_area, Vec2::new(40, 3));
+        // wide chars take two cells, their second cell has no letter of its own
+        assert_eq!(rows[0], "1 let s = \"日本\"; // 😀 x");
+        assert_eq!(rows[1], "2 let a = 1;");
+
+        // the highlighting stays aligned, the `x` behind the emoji belongs to the comment
+        let screen = screen(&mut edit_area, Vec2::new(40, 3));
+        let style = |x: usize| screen[Vec2::new(x, 0)].as_ref().unwrap().style.clone();
+        let letter = screen[Vec2::new(24, 0)].as_ref().unwrap().letter.clone();
+        assert_eq!(letter.as_option().map(String::as_str), Some("x"));
+        assert_eq!(style(24), style(18));
+        assert_ne!(style(24), style(11));
+    }
+
+    #[test]
+    fn cursor_columns_expand_tabs() {
+        // `b` behind a tab expanded to 4 columns and `a`
+        let edit_area = with_content("x\n\tab", 4);
+        let cursor = edit_area.cursor();
+        assert_eq!(
+            (cursor.row, cursor.char_index, cursor.display_column),
+            (1, 2, 5)
+        );
+
+        let mut edit_area = with_content("\tab", 2);
+        edit_area.set_tab_size(2);
+        edit_area.set_cursor_byte_offset(2);
+        assert_eq!(edit_area.cursor().display_column, 3);
+    }
+
+    /// Clicks into the editor at the position, the line numbers take the first columns
+    fn click(edit_area: &mut EditArea, position: Vec2) -> usize {
+        edit_area.layout(Vec2::new(20, 5));
+        edit_area.on_event(Event::Mouse {
+            offset: Vec2::zero(),
+            position,
+            event: MouseEvent::Press(MouseButton::Left),
+        });
+        edit_area.cursor().byte_offset
+    }
+
+    #[test]
+    fn clicks_and_moves_expand_tabs() {
+        let mut edit_area = with_content("abcdef\n\tab\n  \tc", 0);
+        // `b` behind the tab is in the sixth column, the line numbers take two
+        assert_eq!(click(&mut edit_area, Vec2::new(7, 1)), 9);
+        // clicking on the tab selects it
+        assert_eq!(click(&mut edit_area, Vec2::new(4, 1)), 7);
+        // the tab after two spaces only takes two columns
+        assert_eq!(click(&mut edit_area, Vec2::new(6, 2)), 14);
+        assert_eq!(edit_area.cursor().display_column, 4);
+
+        edit_area.set_cursor_byte_offset(5);
+        assert_eq!(press(&mut edit_area, Key::Down), (9, 5));
+        assert_eq!(press(&mut edit_area, Key::Down), (15, 5));
+
+        let rows = draw(&mut edit_area, Vec2::new(20, 3));
+        assert_eq!(rows[1..], ["2     ab", "3     c"]);
+
+        edit_area.set_tab_size(2);
+        assert_eq!(click(&mut edit_area, Vec2::new(6, 1)), 10);
+    }
+
+    #[test]
+    fn goto_counts_chars_and_shows_expanded_columns() {
+        let mut edit_area = with_content("x\n  \tc", 0);
+        edit_area.set_cursor_row_column(1, 3);
+        let cursor = edit_area.cursor();
+        assert_eq!(
+            (cursor.byte_offset, cursor.char_index, cursor.display_column),
+            (5, 3, 4)
+        );
+    }
+
+    #[test]
+    fn resizing_keeps_the_cursor_visible() {
+        let content = (0..100).map(|i| format!("{i}\n")).collect::<String>();
+        let mut edit_area = with_content(&content, 0);
+        edit_area.layout(Vec2::new(20, 50));
+        edit_area.set_cursor_row(80);
+        edit_area.layout(Vec2::new(20, 50));
+        let visible = |edit_area: &EditArea, height: usize| {
+            let y = edit_area.scroll().y;
+            (y..y + height).contains(&80)
+        };
+        assert!(visible(&edit_area, 50));
+
+        edit_area.layout(Vec2::new(20, 10));
+        assert!(visible(&edit_area, 10));
+
+        // growing again doesn't scroll behind the end
+        edit_area.layout(Vec2::new(20, 60));
+        assert!(visible(&edit_area, 60));
+        assert!(edit_area.scroll().y <= 100 - 60 + 1);
+
+        // without a resize the view can be scrolled away from the cursor
+        edit_area.set_scroll(Vec2::zero());
+        edit_area.layout(Vec2::new(20, 60));
+        assert_eq!(edit_area.scroll().y, 0);
+    }
+
+    #[test]
+    fn resizing_keeps_the_cursor_column_visible() {
+        let mut edit_area = with_content(&"x".repeat(100), 90);
+        edit_area.layout(Vec2::new(120, 5));
+        assert_eq!(edit_area.scroll().x, 0);
+        edit_area.layout(Vec2::new(30, 5));
+        let x = edit_area.scroll().x;
+        // the line numbers take two columns
+        assert!((x..x + 30 - 2).contains(&90), "{x}");
+    }
+
+    #[test]
+    fn transpose_chars() {
+        let mut edit_area = with_content("ab\ncd", 1);
+        edit_area.transpose_chars();
+        assert_eq!(edit_area.get_content(), "ba\ncd");
+        assert_eq!(edit_area.cursor().byte_offset, 2);
+
+        // at the end of the line the last two are swapped
+        let mut edit_area = with_content("ab\ncd", 5);
+        edit_area.transpose_chars();
+        assert_eq!(edit_area.get_content(), "ab\ndc");
+    }
+
+    #[test]
+    fn transpose_chars_on_short_lines() {
+        for (content, byte_offset) in [("", 0), ("a\n\nb", 2), ("a", 1), ("a", 0)] {
+            let mut edit_area = with_content(content, byte_offset);
+            edit_area.transpose_chars();
+            assert_eq!(edit_area.get_content(), content);
+        }
+    }
+
+    /// Returns an editor highlighting the content as Rust
+    fn highlighted(content: &str) -> EditArea {
+        let mut edit_area = with_content(content, 0);
+        edit_area.set_highlighting("rs", None);
+        edit_area
+    }
+
+    #[test]
+    fn highlighted_rows_are_reused_by_their_text() {
+        let first = "/* comment\n*/ fn main() {}\n";
+        let mut edit_area = highlighted(first);
+        let expected = edit_area.highlight_row(1);
+        edit_area.set_content("other");
+        edit_area.highlight_row(0);
+        edit_area.set_content(first);
+        assert!(edit_area.highlight_cache.borrow().by_text.len() >= 3);
+        assert_eq!(edit_area.highlight_row(1), expected);
+
+        // the same text inside of the comment is highlighted differently
+        edit_area.set_content("*/ fn main() {}\n");
+        assert_ne!(edit_area.highlight_row(0), expected);
+    }
+
+    /// Highlights all rows of the editor
+    fn highlight_all(edit_area: &EditArea) -> Vec<StyledString> {
+        (0..edit_area.rows.len())
+            .map(|row_id| edit_area.highlight_row(row_id))
+            .collect()
+    }
+
+    #[test]
+    fn incremental_highlighting_matches_a_full_one() {
+        let mut edit_area = highlighted("fn main() {\n    let a = 1;\n    let b = \"b\";\n}\n");
+        highlight_all(&edit_area);
+
+        let mut siv = Cursive::new();
+        // opening a comment changes the following rows, closing it changes them back
+        let edits: [(usize, &[Event]); 4] = [
+            (16, &[Event::Char('/'), Event::Char('*')]),
+            (31, &[Event::Char('*'), Event::Char('/')]),
+            (18, &[Event::Key(Key::Enter), Event::Char('"')]),
+            (
+                20,
+                &[Event::Key(Key::Backspace), Event::Key(Key::Backspace)],
+            ),
+        ];
+        for (byte_offset, events) in edits {
+            edit_area.set_cursor_byte_offset(byte_offset);
+            for event in events {
+                edit_area.on_event(event.clone()).process(&mut siv);
+                let expected = highlight_all(&highlighted(edit_area.get_content()));
+                assert_eq!(highlight_all(&edit_area), expected, "{event:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_highlighting_matches_a_full_one_after_many_edits() {
+        let mut edit_area = highlighted(&"fn f() {\n    let a = \"a\"; // b\n}\n".repeat(10));
+        let mut siv = Cursive::new();
+        let events = [
+            Event::Char('"'),
+            Event::Char('/'),
+            Event::Char('*'),
+            Event::Char('x'),
+            Event::Key(Key::Enter),
+            Event::Key(Key::Backspace),
+            Event::Key(Key::Del),
+        ];
+        for i in 0..200 {
+            let byte_offset = (i * 37) % edit_area.get_content().len();
+            let byte_offset = (0..=byte_offset)
+                .rev()
+                .find(|&i| edit_area.get_content().is_char_boundary(i))
+                .unwrap();
+            edit_area.set_cursor_byte_offset(byte_offset);
+            edit_area
+                .on_event(events[i % events.len()].clone())
+                .process(&mut siv);
+            // several edits are made before highlighting now and then
+            if i % 3 == 0 {
+                let expected = highlight_all(&highlighted(edit_area.get_content()));
+                assert_eq!(highlight_all(&edit_area), expected, "{i}");
+            }
+        }
+    }
+
+    #[test]
+    fn rows_behind_a_reconverged_state_are_kept() {
+        let mut edit_area = highlighted(&"let a = 1;\n".repeat(100));
+        highlight_all(&edit_area);
+        edit_area.set_cursor_byte_offset(4);
+        edit_area.insert('b');
+        edit_area.highlight_row(0);
+        let cache = edit_area.highlight_cache.borrow();
+        assert_eq!(cache.valid, 1);
+        assert!(cache.lines[1..].iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn columns_of_ascii_are_found_like_the_ones_of_graphemes() {
+        let edit_area = with_content("", 0);
+        // the accent is combined with the `e` in front of it, the tab ends at the 8th column
+        let text = "abce\u{301}漢\tx";
+        let offsets: Vec<usize> = (0..11)
+            .map(|column| edit_area.column_offset(text, column))
+            .collect();
+        assert_eq!(offsets, [0, 1, 2, 3, 6, 6, 9, 9, 10, 11, 11]);
+    }
+
+    /// Editing a single row of 1MB, like minified code, takes about as long as editing a file of
+    /// the same size with short rows, so nothing is done for the whole row on each keystroke
+    #[test]
+    fn editing_a_long_row_is_like_editing_short_ones() {
+        const EDITS: usize = 10;
+        let edit = |content: &str| {
+            let mut edit_area = highlighted(content);
+            let size = Vec2::new(80, 24);
+            edit_area.set_cursor_byte_offset(content.len() / 2);
+            draw(&mut edit_area, size);
+            let start = Instant::now();
+            for _ in 0..EDITS {
+                edit_area.insert('x');
+                draw(&mut edit_area, size);
+            }
+            start.elapsed()
+        };
+        let long = edit(&"let a = 1; ".repeat(100_000));
+        let short = edit(&"let a = 1;\n".repeat(100_000));
+        assert!(long < short * 4);
+    }
+
+    /// Highlighting the rows again after an edit is faster than highlighting all of them like
+    /// before
+    #[test]
+    fn highlighting_after_edits_is_incremental() {
+        const EDITS: usize = 10;
+        let content = "fn main() {\n    let a = \"text\";\n}\n".repeat(2_000);
+        let mut edit_area = highlighted(&content);
+        highlight_all(&edit_area);
+
+        let start = Instant::now();
+        for i in 0..EDITS {
+            edit_area.set_cursor_byte_offset(i * 30);
+            edit_area.insert('x');
+            highlight_all(&edit_area);
+        }
+        let incremental = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..EDITS {
+            let edit_area = highlighted(&content);
+            highlight_all(&edit_area);
+        }
+        let full = start.elapsed();
+
+        assert!(incremental < full);
+    }
+
+    #[test]
+    fn rows_far_down_are_highlighted_step_by_step() {
+        let content = "/* comment\n".to_string() + &"let a = 1;\n".repeat(3 * BLOCKING_ROWS);
+        let last = 3 * BLOCKING_ROWS;
+        let expected = highlighted(&content).highlight_row(last);
+
+        let siv = Cursive::new();
+        let mut edit_area = highlighted(&content);
+        edit_area.set_cb_sink(siv.cb_sink().clone());
+        assert_eq!(
+            edit_area.highlight_row(last),
+            StyledString::plain("let a = 1;")
+        );
+        while edit_area.highlight_cache.borrow().valid + BLOCKING_ROWS <= last {
+            edit_area.highlight_ahead(last);
+        }
+        assert_eq!(edit_area.highlight_row(last), expected);
+    }
+
+    #[test]
+    fn queries_follow_the_cursor() {
+        let content = "fn main() {\n    let a = 1;\n}";
+        let mut edit_area = with_content(content, 20);
+        assert_eq!(edit_area.get_content(), content);
+        assert_eq!(edit_area.cursor().row, 1);
+        assert_eq!(edit_area.current_line(), "    let a = 1;");
+        assert_eq!(edit_area.selection(), None);
+
+        // the last line is without its newline, in both directions
+        edit_area.on_event(Event::Ctrl(Key::Down));
+        assert_eq!(edit_area.selection(), Some("    let a = 1;\n}"));
+        edit_area.on_event(Event::Ctrl(Key::Up));
+        edit_area.on_event(Event::Ctrl(Key::Up));
+        assert_eq!(edit_area.selection(), Some("fn main() {\n    let a = 1;"));
+        assert_eq!(edit_area.current_line(), "    let a = 1;");
+
+        // the empty line behind the last newline
+        let mut edit_area = with_content("a\n", 2);
+        assert_eq!(edit_area.cursor().row, 1);
+        assert_eq!(edit_area.current_line(), "");
+        edit_area.on_event(Event::Ctrl(Key::Up));
+        assert_eq!(edit_area.selection(), Some("a\n"));
+    }
+
+    #[test]
+    fn numbers_are_incremented_in_place() {
+        let add = |line: &str, column, step| {
+            let (range, number) = add_to_number(line, column, step)?;
+            Some(format!(
+                "{}{number}{}",
+                &line[..range.start],
+                &line[range.end..]
+            ))
+        };
+        // the number at the cursor, otherwise the next one or the one right in front of it
+        assert_eq!(add("a = 9;", 0, 1).as_deref(), Some("a = 10;"));
+        assert_eq!(add("1 + 2", 2, 1).as_deref(), Some("1 + 3"));
+        assert_eq!(add("a = 5", 5, -1).as_deref(), Some("a = 4"));
+        assert_eq!(add("a = 5;", 6, 1), None);
+
+        // signs, leading zeros and hexadecimal numbers
+        assert_eq!(add("-1", 1, 1).as_deref(), Some("0"));
+        assert_eq!(add("x-1", 2, 1).as_deref(), Some("x-2"));
+        assert_eq!(add("007", 0, -8).as_deref(), Some("-001"));
+        assert_eq!(add("0xff", 0, 1).as_deref(), Some("0x100"));
+        assert_eq!(add("0x0A", 0, 5).as_deref(), Some("0x0F"));
+        assert_eq!(add("0x0a", 3, 5).as_deref(), Some("0x0f"));
+
+        // overflowing decimal numbers are left as they are
+        assert_eq!(add(&i64::MAX.to_string(), 0, 1), None);
+    }
+
+    #[test]
+    fn centering_and_paging_are_reported_as_scrolling() {
+        let scrolled = Rc::new(RefCell::new(Vec::new()));
+        let mut edit_area = with_content(&"line\n".repeat(100), 0);
+        let reported = scrolled.clone();
+        edit_area.set_on_scroll(move |_, offset, cursor| {
+            reported.borrow_mut().push((offset.y, cursor.byte_offset));
+        });
+        edit_area.set_scroll_off(3);
+        edit_area.layout(Vec2::new(20, 10));
+        edit_area.set_cursor_byte_offset(5 * 50);
+        let height = edit_area.page_height();
+
+        let mut siv = Cursive::new();
+        let mut press = |edit_area: &mut EditArea, event| {
+            if let EventResult::Consumed(Some(callback)) = edit_area.on_event(event) {
+                callback(&mut siv);
+            }
+        };
+        // centering again keeps the view where it is
+        for _ in 0..2 {
+            press(&mut edit_area, Event::CtrlChar('l'));
+            assert_eq!(edit_area.scroll().y, 50 - height / 2);
+            assert_eq!(scrolled.borrow().last(), Some(&(50 - height / 2, 5 * 50)));
+        }
+
+        press(&mut edit_area, Event::Key(Key::PageDown));
+        assert_eq!(edit_area.selected_row(), 50 + height);
+        assert_eq!(
+            scrolled.borrow().last(),
+            Some(&(edit_area.scroll().y, edit_area.cursor().byte_offset))
+        );
+    }
+}
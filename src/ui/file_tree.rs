@@ -1,42 +1,102 @@
 use cursive::{
+    event::{Event, EventResult, EventTrigger, Key, MouseButton, MouseEvent},
+    reexports::log::warn,
     view::{Nameable, Scrollable},
-    views::{NamedView, ScrollView},
-    Cursive,
+    views::{NamedView, OnEventView, ScrollView},
+    Cursive, View,
 };
 use cursive_tree_view::{Placement, TreeView};
-use std::{fmt, fs, io, path::PathBuf};
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
-use crate::error::ResultExt;
+use crate::{app::State, error::ResultExt, events};
 
-use super::open_file;
+use super::{notify, open_file};
 
 #[derive(Debug, Clone, Default)]
 pub struct TreeEntry {
     pub name: String,
     pub path: PathBuf,
     pub dir: Option<PathBuf>,
+    /// Target of a symlink, as it's written inside of the link
+    pub link: Option<PathBuf>,
+    /// Marked entries are deleted or moved together
+    pub marked: bool,
+    /// Why the directory at `path` couldn't be read, shown instead of its entries
+    pub error: Option<String>,
+}
+
+impl TreeEntry {
+    /// Creates the entry shown instead of the entries of a directory which couldn't be read
+    fn unreadable(dir: &Path, error: &io::Error) -> Self {
+        let error = match error.kind() {
+            io::ErrorKind::PermissionDenied => "Permission denied".to_string(),
+            _ => error.to_string(),
+        };
+        Self {
+            name: String::new(),
+            path: dir.to_path_buf(),
+            error: Some(error),
+            ..Default::default()
+        }
+    }
 }
 
 impl fmt::Display for TreeEntry {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.name)
+        if let Some(error) = &self.error {
+            return write!(f, "! {error}");
+        }
+        if self.marked {
+            write!(f, "● ")?;
+        }
+        match &self.link {
+            Some(link) => write!(f, "{} -> {}", self.name, link.to_string_lossy()),
+            None => write!(f, "{}", self.name),
+        }
     }
 }
 
-fn collect_entries(dir: &PathBuf, entries: &mut Vec<TreeEntry>) -> io::Result<()> {
+fn collect_entries(
+    dir: &PathBuf,
+    follow_symlinks: bool,
+    entries: &mut Vec<TreeEntry>,
+) -> io::Result<()> {
     if dir.is_dir() {
         for entry in fs::read_dir(dir)? {
-            let entry = entry?;
+            // entries which vanished or can't be read are skipped, not the whole directory
+            let Ok(entry) = entry else {
+                continue;
+            };
             let path = entry.path();
+            let link = fs::read_link(&path).ok();
 
-            if path.is_dir() {
+            // symlinked directories are only expanded if they don't lead back to an ancestor
+            let expandable = link.is_none() || follow_symlinks && !is_cycle(&path);
+            if path.is_dir() || link.is_some() {
                 entries.push(TreeEntry {
                     name: entry
                         .file_name()
                         .into_string()
                         .unwrap_or_else(|_| String::new()),
                     path: entry.path(),
-                    dir: Some(path),
+                    dir: (path.is_dir() && expandable).then_some(path),
+                    link,
+                    marked: false,
+                    error: None,
                 });
             } else if path.is_file() {
                 entries.push(TreeEntry {
@@ -46,6 +106,9 @@ fn collect_entries(dir: &PathBuf, entries: &mut Vec<TreeEntry>) -> io::Result<()
                         .unwrap_or_else(|_| String::new()),
                     path: entry.path(),
                     dir: None,
+                    link: None,
+                    marked: false,
+                    error: None,
                 });
             }
         }
@@ -53,54 +116,445 @@ fn collect_entries(dir: &PathBuf, entries: &mut Vec<TreeEntry>) -> io::Result<()
     Ok(())
 }
 
+/// Returns `true` if the symlink points to a directory containing the link itself
+///
+/// Expanding such a link would show the same directories over and over again.
+pub fn is_cycle(link: &Path) -> bool {
+    let (Some(parent), Ok(target)) = (link.parent(), link.canonicalize()) else {
+        return false;
+    };
+    parent
+        .canonicalize()
+        .is_ok_and(|parent| parent.starts_with(target))
+}
+
 pub fn expand_tree(
     tree: &mut TreeView<TreeEntry>,
     parent_row: usize,
     dir: &PathBuf,
     placement: Placement,
+    follow_symlinks: bool,
 ) {
     let mut entries = Vec::new();
-    if collect_entries(dir, &mut entries).is_ok() {
-        // sort entries
-        entries.sort_by(|a, b| {
-            b.dir
-                .is_some()
-                .cmp(&a.dir.is_some())
-                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
-        });
+    // an unreadable directory shows why instead of its entries
+    if let Err(e) = collect_entries(dir, follow_symlinks, &mut entries) {
+        entries = vec![TreeEntry::unreadable(dir, &e)];
+    }
+    // sort entries
+    entries.sort_by(|a, b| {
+        b.dir
+            .is_some()
+            .cmp(&a.dir.is_some())
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
 
-        // due to the nature of how the tree is being created, this has to be done
-        let placement = match placement {
-            Placement::LastChild => Placement::LastChild,
-            Placement::Before => {
-                entries.reverse();
-                Placement::Before
+    // due to the nature of how the tree is being created, this has to be done
+    let placement = match placement {
+        Placement::LastChild => Placement::LastChild,
+        Placement::Before => {
+            entries.reverse();
+            Placement::Before
+        }
+        _ => unimplemented!(),
+    };
+
+    for i in entries {
+        if i.dir.is_some() {
+            tree.insert_container_item(i.clone(), placement, parent_row);
+        } else {
+            tree.insert_item(i.clone(), placement, parent_row);
+        }
+    }
+}
+
+pub fn load_parent(tree: &mut TreeView<TreeEntry>, dir: &PathBuf, follow_symlinks: bool) {
+    tree.clear();
+    expand_tree(tree, 0, dir, Placement::Before, follow_symlinks);
+}
+
+/// Selects the entry of the path, expanding the directories leading to it
+///
+/// Directories which weren't expanded before are loaded like on expanding them by hand.
+pub fn reveal(tree: &mut TreeView<TreeEntry>, path: &Path, follow_symlinks: bool) {
+    let mut row = 0;
+    while let Some(item) = tree.borrow_item(row) {
+        if item.path == path {
+            tree.set_selected_row(row);
+            return;
+        }
+        if let Some(dir) = item.dir.clone().filter(|dir| path.starts_with(dir)) {
+            // entries loaded before are shown again, otherwise they're loaded
+            tree.expand_item(row);
+            if !is_expanded(tree, row) {
+                expand_tree(tree, row, &dir, Placement::LastChild, follow_symlinks);
             }
-            _ => unimplemented!(),
-        };
+        }
+        row += 1;
+    }
+}
 
-        for i in entries {
-            if i.dir.is_some() {
-                tree.insert_container_item(i.clone(), placement, parent_row);
-            } else {
-                tree.insert_item(i.clone(), placement, parent_row);
+/// Returns `true` for hidden files and build directories, which aren't searched or watched
+pub fn is_ignored(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with('.') || name == "target" || name == "node_modules"
+}
+
+/// Returns the number of shown rows
+///
+/// `len` of the tree also counts the entries inside of collapsed directories, which have no
+/// row.
+fn row_count(tree: &TreeView<TreeEntry>) -> usize {
+    let (mut low, mut high) = (0, tree.len());
+    while low < high {
+        let middle = (low + high) / 2;
+        if tree.borrow_item(middle).is_some() {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// Returns the directories which are expanded and show their entries
+fn expanded_dirs(tree: &TreeView<TreeEntry>) -> Vec<PathBuf> {
+    (0..row_count(tree).saturating_sub(1))
+        .filter(|&row| tree.item_parent(row + 1) == Some(row))
+        .filter_map(|row| tree.borrow_item(row)?.dir.clone())
+        .collect()
+}
+
+/// Returns the row of the entry with the path
+fn find_row(tree: &TreeView<TreeEntry>, path: &Path) -> Option<usize> {
+    (0..row_count(tree)).find(|&row| tree.borrow_item(row).is_some_and(|item| item.path == path))
+}
+
+/// Toggles the mark of the selected entry
+pub fn toggle_mark(tree: &mut TreeView<TreeEntry>) {
+    if let Some(item) = tree.row().and_then(|row| tree.borrow_item_mut(row)) {
+        if item.error.is_some() {
+            return;
+        }
+        item.marked = !item.marked;
+    }
+}
+
+/// Marks the selected entry and the one above or below it, moving the selection there
+pub fn extend_marks(tree: &mut TreeView<TreeEntry>, up: bool) {
+    fn mark_selected(tree: &mut TreeView<TreeEntry>) {
+        if let Some(item) = tree.row().and_then(|row| tree.borrow_item_mut(row)) {
+            item.marked = item.error.is_none();
+        }
+    }
+
+    mark_selected(tree);
+    if up {
+        tree.focus_up(1);
+    } else {
+        tree.focus_down(1);
+    }
+    mark_selected(tree);
+}
+
+/// Returns the paths of the shown marked entries
+///
+/// Entries inside of a marked directory are skipped, they're affected by it anyway.
+pub fn marked(tree: &TreeView<TreeEntry>) -> Vec<PathBuf> {
+    let paths = (0..row_count(tree))
+        .filter_map(|row| tree.borrow_item(row))
+        .filter(|item| item.marked)
+        .map(|item| item.path.clone())
+        .collect::<Vec<_>>();
+    paths
+        .iter()
+        .filter(|path| {
+            !paths
+                .iter()
+                .any(|other| other != *path && path.starts_with(other))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Reloads the tree, keeping the expanded directories, the marks and the selected entry
+pub fn refresh(tree: &mut TreeView<TreeEntry>, dir: &PathBuf, follow_symlinks: bool) {
+    let expanded = expanded_dirs(tree);
+    let marked = (0..row_count(tree))
+        .filter_map(|row| tree.borrow_item(row))
+        .filter(|item| item.marked)
+        .map(|item| item.path.clone())
+        .collect::<Vec<_>>();
+    let selected = tree
+        .row()
+        .and_then(|row| tree.borrow_item(row))
+        .map(|item| item.path.clone());
+
+    load_parent(tree, dir, follow_symlinks);
+    // parents come before their children, so they're already expanded
+    for dir in expanded {
+        if let Some(row) = find_row(tree, &dir) {
+            expand_tree(tree, row, &dir, Placement::LastChild, follow_symlinks);
+            tree.expand_item(row);
+        }
+    }
+    for path in marked {
+        if let Some(item) = find_row(tree, &path).and_then(|row| tree.borrow_item_mut(row)) {
+            item.marked = true;
+        }
+    }
+    if let Some(row) = selected.and_then(|selected| find_row(tree, &selected)) {
+        tree.set_selected_row(row);
+    }
+}
+
+/// Returns `true` if the entry is a directory whose entries are shown
+fn is_expanded(tree: &TreeView<TreeEntry>, row: usize) -> bool {
+    row + 1 < row_count(tree) && tree.item_parent(row + 1) == Some(row)
+}
+
+/// Selects the directory containing the selected entry
+pub fn select_parent(tree: &mut TreeView<TreeEntry>) {
+    if let Some(parent) = tree.row().and_then(|row| tree.item_parent(row)) {
+        tree.set_selected_row(parent);
+    }
+}
+
+/// Selects the first or last entry of the directory containing the selected entry
+pub fn select_sibling(tree: &mut TreeView<TreeEntry>, last: bool) {
+    let Some(row) = tree.row() else {
+        return;
+    };
+    let parent = tree.item_parent(row);
+    let mut siblings = (0..row_count(tree)).filter(|&other| tree.item_parent(other) == parent);
+    let sibling = if last {
+        siblings.next_back()
+    } else {
+        siblings.next()
+    };
+    if let Some(sibling) = sibling {
+        tree.set_selected_row(sibling);
+    }
+}
+
+/// Time after which typed letters start a new search inside of the tree
+const TYPE_SEARCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Letters typed while the tree is focused, selecting the entry starting with them
+#[derive(Debug, Default)]
+struct TypeSearch {
+    text: String,
+    last_typed: Option<Instant>,
+}
+
+/// Selects the next entry whose name starts with the typed letters, like inside of a file
+/// manager
+///
+/// Typing the same letter again moves on to the next entry starting with it. The search
+/// wraps around at the end of the tree.
+fn type_search(tree: &mut TreeView<TreeEntry>, search: &mut TypeSearch, ch: char) {
+    if search
+        .last_typed
+        .map_or(true, |last| last.elapsed() > TYPE_SEARCH_TIMEOUT)
+    {
+        search.text.clear();
+    }
+    search.last_typed = Some(Instant::now());
+    search.text.extend(ch.to_lowercase());
+
+    let selected = tree.row().unwrap_or_default();
+    let len = row_count(tree);
+    let find = |tree: &TreeView<TreeEntry>, text: &str, start: usize| {
+        (0..len).map(|i| (start + i) % len).find(|&row| {
+            tree.borrow_item(row)
+                .is_some_and(|item| item.name.to_lowercase().starts_with(text))
+        })
+    };
+    // a longer prefix may still match the selected entry, a single letter moves on
+    let start = if search.text.chars().count() > 1 {
+        selected
+    } else {
+        selected + 1
+    };
+    let mut found = find(tree, &search.text, start);
+    let mut letters = search.text.chars();
+    if let (None, Some(first)) = (found, letters.next()) {
+        if letters.all(|letter| letter == first) {
+            found = find(tree, &first.to_string(), selected + 1);
+        }
+    }
+    if let Some(row) = found {
+        tree.set_selected_row(row);
+    }
+}
+
+/// Time the changes of the shown directories are collected for, before they're checked
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The watcher of the tree and the entries of the shown directories when they were checked
+/// the last time, only set while the tree is watched
+static WATCHED: Mutex<Option<Watched>> = Mutex::new(None);
+
+struct Watched {
+    watcher: Debouncer<RecommendedWatcher>,
+    /// Directories which are watched, also the one of the current file
+    dirs: HashSet<PathBuf>,
+    listings: HashMap<PathBuf, Vec<OsString>>,
+    /// Current file which was found deleted on disk, so it's only reported once
+    deleted: Option<PathBuf>,
+}
+
+impl Watched {
+    /// Watches the directories instead of the ones before
+    ///
+    /// A directory which can't be watched, like when the limit of the system is reached, is
+    /// skipped, changes inside of it are noticed along with the ones of other directories.
+    fn watch_dirs(&mut self, dirs: HashSet<PathBuf>) {
+        for dir in self.dirs.difference(&dirs) {
+            // removed directories aren't watched anymore anyway
+            let _ = self.watcher.watcher().unwatch(dir);
+        }
+        for dir in dirs.difference(&self.dirs) {
+            if let Err(e) = self
+                .watcher
+                .watcher()
+                .watch(dir, RecursiveMode::NonRecursive)
+            {
+                warn!("{} couldn't be watched: {e}", dir.to_string_lossy());
             }
         }
+        self.dirs = dirs;
     }
 }
 
-pub fn load_parent(tree: &mut TreeView<TreeEntry>, dir: &PathBuf) {
-    tree.clear();
-    expand_tree(tree, 0, dir, Placement::Before);
+/// Refreshes the tree when files are added, removed or renamed outside of the editor
+///
+/// Only the project directory and the expanded directories are watched, except for hidden
+/// and build directories. The changes are collected until they settled, so bulk changes like
+/// a checkout don't reload the tree over and over again. A current file which was deleted on
+/// disk is reported, asking how to continue with its buffer.
+pub fn watch(siv: &mut Cursive) {
+    let cb_sink = siv.cb_sink().clone();
+    let watcher = new_debouncer(WATCH_DEBOUNCE, move |events: DebounceEventResult| {
+        if let Err(e) = events {
+            warn!("The tree couldn't be watched: {e}");
+        }
+        // the app may have been quit in the meantime
+        let _ = cb_sink.send(Box::new(update_watched));
+    });
+    match watcher {
+        Ok(watcher) => {
+            *WATCHED.lock().unwrap() = Some(Watched {
+                watcher,
+                dirs: HashSet::new(),
+                listings: HashMap::new(),
+                deleted: None,
+            });
+            update_watched(siv);
+        }
+        Err(e) => warn!("The tree couldn't be watched: {e}"),
+    }
+}
+
+/// Compares the entries of the shown directories with the last check, refreshing the tree if
+/// they changed, and watches the ones which are shown now
+///
+/// Needs to be called when other directories are shown or another file is opened, which is
+/// done by the tree and when opening files. Does nothing while the tree isn't watched.
+pub fn update_watched(siv: &mut Cursive) {
+    if WATCHED.lock().unwrap().is_none() {
+        return;
+    }
+    let Some((project_path, follow_symlinks, current_file)) =
+        siv.with_user_data(|state: &mut State| {
+            (
+                state.project_path.clone(),
+                state.config.follow_symlinks,
+                state.current_file.clone(),
+            )
+        })
+    else {
+        return;
+    };
+    let Some(mut dirs) =
+        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| expanded_dirs(tree))
+    else {
+        return;
+    };
+    dirs.retain(|dir| !dir.ancestors().any(is_ignored));
+    dirs.push(project_path.clone());
+
+    let listings = dirs
+        .iter()
+        .filter_map(|dir| {
+            let mut names = fs::read_dir(dir)
+                .ok()?
+                .filter_map(|entry| entry.ok().map(|entry| entry.file_name()))
+                .collect::<Vec<_>>();
+            names.sort();
+            Some((dir.clone(), names))
+        })
+        .collect::<HashMap<_, _>>();
+    let deleted = current_file.as_ref().filter(|file| !file.exists()).cloned();
+    // the directory of the current file is watched as well, so deleting it is noticed
+    let mut dirs = dirs.into_iter().collect::<HashSet<_>>();
+    dirs.extend(
+        current_file
+            .as_ref()
+            .and_then(|file| file.parent())
+            .map(Path::to_path_buf),
+    );
+
+    let (changed, newly_deleted) = {
+        let mut watched = WATCHED.lock().unwrap();
+        let Some(watched) = watched.as_mut() else {
+            return;
+        };
+        watched.watch_dirs(dirs);
+        // newly expanded directories aren't a change
+        let changed = listings.iter().any(|(dir, names)| {
+            watched
+                .listings
+                .get(dir)
+                .is_some_and(|old_names| old_names != names)
+        });
+        watched.listings = listings;
+        let newly_deleted = deleted
+            .clone()
+            .filter(|path| watched.deleted.as_ref() != Some(path));
+        watched.deleted = deleted;
+        (changed, newly_deleted)
+    };
+
+    if let Some(path) = newly_deleted {
+        events::confirm_deleted(siv, &path);
+    }
+    if changed {
+        siv.call_on_name("tree", |tree: &mut TreeView<TreeEntry>| {
+            refresh(tree, &project_path, follow_symlinks);
+        });
+    }
+}
+
+/// Scrolls the tree to the selected entry after it was selected by a key
+fn show_selected(view: &mut ScrollView<NamedView<TreeView<TreeEntry>>>) -> EventResult {
+    view.scroll_to_important_area();
+    // the key was handled, even if the tree didn't scroll
+    EventResult::consumed()
 }
 
-pub fn new(parent: &PathBuf) -> ScrollView<NamedView<TreeView<TreeEntry>>> {
+pub fn new(
+    parent: &PathBuf,
+    follow_symlinks: bool,
+) -> OnEventView<ScrollView<NamedView<TreeView<TreeEntry>>>> {
     let mut tree = TreeView::<TreeEntry>::new();
 
-    load_parent(&mut tree, parent);
+    load_parent(&mut tree, parent, follow_symlinks);
 
     // Stuff that should happen when interacted with a collapse
     tree.set_on_collapse(|siv: &mut Cursive, row, is_collapsed, children| {
+        let follow_symlinks = siv
+            .with_user_data(|state: &mut State| state.config.follow_symlinks)
+            .unwrap_or_default();
         siv.call_on_name("tree", move |tree: &mut TreeView<TreeEntry>| {
             // Lazily insert directory listings for sub nodes if there weren't already opened
             if !is_collapsed && children == 0 {
@@ -110,21 +564,186 @@ pub fn new(parent: &PathBuf) -> ScrollView<NamedView<TreeView<TreeEntry>>> {
                     .dir
                     .clone()
                 {
-                    expand_tree(tree, row, &dir, Placement::LastChild);
+                    expand_tree(tree, row, &dir, Placement::LastChild, follow_symlinks);
                 }
             }
         });
+        update_watched(siv);
     });
 
     tree.set_on_submit(move |siv: &mut Cursive, row| {
         if let Some(tree) = siv.find_name::<TreeView<TreeEntry>>("tree") {
             if let Some(item) = tree.borrow_item(row) {
-                if item.dir.is_none() {
+                if let Some(error) = &item.error {
+                    let message = format!("Couldn't read {}: {error}", item.path.to_string_lossy());
+                    notify(siv, message);
+                } else if item.dir.is_none() && item.path.is_dir() {
+                    let message = if is_cycle(&item.path) {
+                        format!("{} links to a directory containing itself", item.name)
+                    } else {
+                        "Following linked directories is disabled".to_string()
+                    };
+                    notify(siv, message);
+                } else if item.dir.is_none() {
                     open_file(siv, &item.path).handle(siv);
                 }
             }
         }
     });
 
-    tree.with_name("tree").scrollable()
+    // right-clicking selects the entry and opens its context menu
+    let right_click = EventTrigger::from_fn(|event| {
+        matches!(
+            event,
+            Event::Mouse {
+                event: MouseEvent::Press(MouseButton::Right),
+                ..
+            }
+        )
+    });
+    // letters and digits jump to the entries starting with them, `Space` marks entries
+    let typed =
+        EventTrigger::from_fn(|event| matches!(event, Event::Char(ch) if !ch.is_whitespace()));
+    let search = Rc::new(RefCell::new(TypeSearch::default()));
+    OnEventView::new(tree.with_name("tree").scrollable())
+        .on_event_inner(Key::Left, |view, _| {
+            let expanded = {
+                let mut tree = view.get_inner_mut().get_mut();
+                let expanded = tree.row().is_some_and(|row| is_expanded(&tree, row));
+                if !expanded {
+                    select_parent(&mut tree);
+                }
+                expanded
+            };
+            // collapsing is done like by `Enter`
+            if expanded {
+                Some(view.on_event(Event::Key(Key::Enter)))
+            } else {
+                Some(show_selected(view))
+            }
+        })
+        .on_event_inner(Key::Right, |view, _| {
+            let (is_dir, expanded) = {
+                let tree = view.get_inner_mut().get_mut();
+                let row = tree.row()?;
+                let is_dir = tree.borrow_item(row)?.dir.is_some();
+                (is_dir, is_expanded(&tree, row))
+            };
+            if !is_dir {
+                return None;
+            }
+            // expanding is done like by `Enter`, which loads the entries if needed
+            if expanded {
+                view.get_inner_mut().get_mut().focus_down(1);
+                Some(show_selected(view))
+            } else {
+                Some(view.on_event(Event::Key(Key::Enter)))
+            }
+        })
+        .on_event_inner(Key::Backspace, |view, _| {
+            select_parent(&mut view.get_inner_mut().get_mut());
+            Some(show_selected(view))
+        })
+        .on_event_inner(Event::Ctrl(Key::Up), |view, _| {
+            select_sibling(&mut view.get_inner_mut().get_mut(), false);
+            Some(show_selected(view))
+        })
+        .on_event_inner(Event::Ctrl(Key::Down), |view, _| {
+            select_sibling(&mut view.get_inner_mut().get_mut(), true);
+            Some(show_selected(view))
+        })
+        .on_event_inner(typed, move |view, event| {
+            let Event::Char(ch) = *event else {
+                return None;
+            };
+            type_search(
+                &mut view.get_inner_mut().get_mut(),
+                &mut search.borrow_mut(),
+                ch,
+            );
+            Some(show_selected(view))
+        })
+        .on_pre_event_inner(right_click, |view, event| {
+            let Event::Mouse {
+                offset, position, ..
+            } = *event
+            else {
+                return None;
+            };
+            let row = position.checked_sub(offset)?.y + view.content_viewport().top();
+            let result = view.on_event(event.clone());
+            if row >= row_count(&view.get_inner_mut().get_mut()) {
+                return Some(result);
+            }
+            Some(result.and(EventResult::with_cb(move |siv| {
+                events::tree_menu(siv, Some(position));
+            })))
+        })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+    use crate::testing::temp_dir;
+
+    /// Returns the entries of the directory by their name
+    fn entries(dir: &PathBuf, follow_symlinks: bool) -> HashMap<String, TreeEntry> {
+        let mut entries = Vec::new();
+        collect_entries(dir, follow_symlinks, &mut entries).unwrap();
+        entries
+            .into_iter()
+            .map(|entry| (entry.name.clone(), entry))
+            .collect()
+    }
+
+    #[test]
+    fn links_to_ancestors_are_cycles() {
+        let dir = temp_dir("cycles");
+        let outside = temp_dir("cycles-outside");
+        fs::create_dir(dir.join("sub")).unwrap();
+        symlink(&dir, dir.join("sub/parent")).unwrap();
+        symlink(".", dir.join("itself")).unwrap();
+        symlink(&outside, dir.join("outside")).unwrap();
+        symlink(dir.join("sub"), dir.join("sibling")).unwrap();
+
+        assert!(is_cycle(&dir.join("sub/parent")));
+        assert!(is_cycle(&dir.join("itself")));
+        assert!(!is_cycle(&dir.join("outside")));
+        assert!(!is_cycle(&dir.join("sibling")));
+        // a link to itself can't be resolved at all
+        symlink("self", dir.join("self")).unwrap();
+        assert!(!is_cycle(&dir.join("self")));
+
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(outside).unwrap();
+    }
+
+    #[test]
+    fn links_are_shown_with_their_target() {
+        let dir = temp_dir("tree-links");
+        let outside = temp_dir("links-outside");
+        fs::write(dir.join("file.txt"), "").unwrap();
+        symlink("file.txt", dir.join("link.txt")).unwrap();
+        symlink(".", dir.join("itself")).unwrap();
+        symlink(&outside, dir.join("outside")).unwrap();
+        symlink("missing", dir.join("broken")).unwrap();
+
+        let shown = entries(&dir, true);
+        assert_eq!(shown["link.txt"].to_string(), "link.txt -> file.txt");
+        assert_eq!(shown["link.txt"].dir, None);
+        assert_eq!(shown["file.txt"].link, None);
+        // cycles aren't expanded, other linked directories are, also outside of the project
+        assert_eq!(shown["itself"].dir, None);
+        assert_eq!(shown["outside"].dir, Some(dir.join("outside")));
+        // broken links are shown, but can't be expanded
+        assert_eq!(shown["broken"].dir, None);
+
+        // without following links no linked directory is expanded
+        assert_eq!(entries(&dir, false)["outside"].dir, None);
+
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(outside).unwrap();
+    }
 }
@@ -1,48 +1,143 @@
 pub mod edit_area;
 pub mod file_tree;
+pub mod output;
 pub mod path_input;
 
 // Here are some general functions of updating the ui
 
-use std::{fs, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Read,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
 
-use cursive::{Cursive, Vec2};
+use cursive::{
+    reexports::log::warn,
+    theme::{BaseColor, Color, Effect, PaletteColor, Style},
+    utils::markup::StyledString,
+    views::{LinearLayout, TextView},
+    Cursive, Vec2,
+};
+use ropey::Rope;
 
 use crate::{
-    app::{EditorPanel, FileData, State},
-    error::Result,
+    app::{content_hash, EditorBorder, EditorPanel, FileData, State, TreeBorder, TreePanel},
+    encoding::{self, Format},
+    error::{Result, ResultExt},
+    events,
+    hooks::{self, Hook},
+    positions, recent,
 };
 
 use self::edit_area::{Cursor, EditArea};
 
+/// How a file without a buffer is read when it's opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenMode {
+    /// Files larger than the `large_file_warning` ask first, before they're read
+    Checked,
+    /// The whole file is read, regardless of its size
+    Full,
+    /// Only the beginning of a large file is read, it can't be edited or saved
+    ReadOnly,
+}
+
 /// Open a file, reading from fs if needed, updating title and edit_area content/highlighting, updating state, ...
+///
+/// The file is opened as a buffer, the previously opened buffers stay inside of `State::files`.
+/// If the file already has a buffer, its content, cursor and scroll are restored from it.
 pub fn open_file(siv: &mut Cursive, file_to_open: &Path) -> Result<()> {
+    open_file_with(siv, file_to_open, OpenMode::Checked)
+}
+
+/// Opens a file like [`open_file`], reading a file without a buffer according to the mode
+pub fn open_file_with(siv: &mut Cursive, file_to_open: &Path, mode: OpenMode) -> Result<()> {
+    let file_to_open = file_to_open.canonicalize()?;
+    store_view(siv);
+    save_outgoing(siv, &file_to_open);
     let mut state = siv
         .with_user_data(|state: &mut State| state.clone())
         .unwrap_or_default();
-    let file_to_open = file_to_open.canonicalize().unwrap_or_default();
     let extension = file_to_open
         .extension()
         .unwrap_or_default()
         .to_string_lossy();
+    let rulers = state.config.rulers_for(&extension);
+    let syntax_name = state.config.syntax_for(&extension).map(String::from);
+    let highlight_overflow = state.config.highlight_overflow;
+    // remember the position inside of the previous file, in case it isn't reopened this session
+    let remember_positions = state.config.remember_positions;
+    if let Some(current_file) = state
+        .current_file
+        .as_ref()
+        .filter(|f| remember_positions && **f != file_to_open)
+    {
+        if let Some(data) = state.get_file(current_file) {
+            if let Err(e) = positions::set([(current_file.as_path(), data.position())]) {
+                warn!("The cursor position couldn't be saved: {e}");
+            }
+        }
+    }
+    let mut syntax_found = true;
     if state.get_file(&file_to_open).is_none() {
-        let content = fs::read_to_string(file_to_open.clone())?;
-        siv.call_on_name("editor", |edit_area: &mut EditArea| {
-            edit_area.set_highlighting(&extension);
-            edit_area.set_content(content.clone());
-            edit_area.set_cursor(Cursor::default());
-            edit_area.set_scroll(Vec2::zero());
-            edit_area.enable();
-        })
-        .unwrap();
+        let limit = state.config.large_file_limit();
+        if let Some(limit) = limit.filter(|_| mode == OpenMode::Checked) {
+            let size = fs::metadata(&file_to_open)?.len();
+            if size > limit {
+                events::confirm_large_file(siv, &file_to_open, size);
+                return Ok(());
+            }
+        }
+        let read_only = mode == OpenMode::ReadOnly;
+        let Some((content, format)) = read_text(&file_to_open, limit.filter(|_| read_only))? else {
+            events::open_unsupported(siv, &file_to_open);
+            return Ok(());
+        };
+        let position = remember_positions
+            .then(|| positions::get(&file_to_open))
+            .flatten()
+            .unwrap_or_default();
+        let highlighted = state.config.highlights(&content);
+        let (cursor, scroll_offset) = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                syntax_found = edit_area.set_highlighting(&extension, syntax_name.as_deref());
+                edit_area.set_pairs(state.config.pairs_for(edit_area.syntax_name()));
+                edit_area.set_word_chars(state.config.word_chars_for(edit_area.syntax_name()));
+                edit_area.set_indent_rules(state.config.indent_rules_for(edit_area.syntax_name()));
+                edit_area.set_snippets(
+                    state.config.snippets_for(edit_area.syntax_name()),
+                    state.config.date_format.clone(),
+                );
+                edit_area.set_rulers(rulers, highlight_overflow);
+                edit_area.set_highlighted(highlighted);
+                edit_area.set_content(content.clone());
+                edit_area.set_notes(BTreeSet::new());
+                edit_area.set_cursor_byte_offset(position.byte_offset);
+                edit_area.set_scroll(position.scroll.into());
+                edit_area.enable();
+                (edit_area.cursor(), edit_area.scroll())
+            })
+            .unwrap();
 
         siv.set_user_data(state.open_new_file(
             file_to_open.clone(),
             FileData {
-                str: content,
-                ..Default::default()
+                saved_hash: content_hash(&content),
+                saved_len: content.len(),
+                text: Rope::from_str(&content),
+                scroll_offset,
+                cursor,
+                read_only,
+                highlighted,
+                notes: BTreeMap::new(),
+                format,
             },
         ));
+        hooks::run(siv, Hook::Open, &file_to_open);
     } else {
         state = State {
             current_file: Some(file_to_open.clone()),
@@ -50,8 +145,26 @@ pub fn open_file(siv: &mut Cursive, file_to_open: &Path) -> Result<()> {
         };
 
         siv.call_on_name("editor", |edit_area: &mut EditArea| {
-            edit_area.set_highlighting(&extension);
-            edit_area.set_content(&state.get_current_file().unwrap().str);
+            syntax_found = edit_area.set_highlighting(&extension, syntax_name.as_deref());
+            edit_area.set_pairs(state.config.pairs_for(edit_area.syntax_name()));
+            edit_area.set_word_chars(state.config.word_chars_for(edit_area.syntax_name()));
+            edit_area.set_indent_rules(state.config.indent_rules_for(edit_area.syntax_name()));
+            edit_area.set_snippets(
+                state.config.snippets_for(edit_area.syntax_name()),
+                state.config.date_format.clone(),
+            );
+            edit_area.set_rulers(rulers, highlight_overflow);
+            edit_area.set_highlighted(state.get_current_file().unwrap().highlighted);
+            edit_area.set_content(state.get_current_file().unwrap().str());
+            edit_area.set_notes(
+                state
+                    .get_current_file()
+                    .unwrap()
+                    .notes
+                    .keys()
+                    .copied()
+                    .collect(),
+            );
             edit_area.set_cursor(state.get_current_file().unwrap().cursor);
             edit_area.set_scroll(state.get_current_file().unwrap().scroll_offset);
             edit_area.enable();
@@ -61,32 +174,532 @@ pub fn open_file(siv: &mut Cursive, file_to_open: &Path) -> Result<()> {
         siv.set_user_data(state.clone());
     }
 
+    if let Some(syntax_name) = syntax_name.filter(|_| !syntax_found) {
+        warn!("Unknown syntax {syntax_name} configured for .{extension}");
+        notify(
+            siv,
+            format!("Unknown syntax {syntax_name} for .{extension}, it's detected instead"),
+        );
+    }
+
+    // only the followed file and partially loaded ones are read-only
+    let read_only = state.following.as_ref() == Some(&file_to_open)
+        || siv
+            .with_user_data(|state: &mut State| {
+                state
+                    .get_file(&file_to_open)
+                    .is_some_and(|file| file.read_only)
+            })
+            .unwrap_or_default();
+    siv.call_on_name("editor", |edit_area: &mut EditArea| {
+        edit_area.set_read_only(read_only);
+    });
+
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("welcome") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    if let Err(e) = recent::add(&file_to_open) {
+        warn!("The recent files couldn't be saved: {e}");
+    }
+
     // check if file has been added && update title accordingly
-    update_title(siv, Some(&state), &file_to_open);
+    update_title(siv, state.is_file_edited(&file_to_open), &file_to_open);
+    let cursor = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| edit_area.cursor())
+        .unwrap();
+    update_status(siv, Some(cursor));
+    file_tree::update_watched(siv);
 
     Ok(())
 }
 
+/// Creates a scratch buffer with the name and content and shows it, like the text piped in
+/// via `-`
+///
+/// An existing scratch buffer with the same name is replaced.
+pub fn open_scratch(siv: &mut Cursive, name: String, content: String) {
+    siv.with_user_data(|state: &mut State| {
+        state.scratches.retain(|(other, _)| other != &name);
+        state.scratches.push((
+            name.clone(),
+            FileData {
+                saved_hash: content_hash(&content),
+                saved_len: content.len(),
+                text: Rope::from_str(&content),
+                scroll_offset: Vec2::zero(),
+                cursor: Cursor::default(),
+                read_only: false,
+                highlighted: true,
+                notes: BTreeMap::new(),
+                format: Format::default(),
+            },
+        ));
+    });
+    show_scratch(siv, &name);
+}
+
+/// Shows a scratch buffer inside of the editor, it isn't highlighted as it has no extension
+pub fn show_scratch(siv: &mut Cursive, name: &str) {
+    store_view(siv);
+    let state = siv
+        .with_user_data(|state: &mut State| {
+            if state.get_scratch(name).is_some() {
+                state.current_file = None;
+                state.current_scratch = Some(name.to_string());
+            }
+            state.clone()
+        })
+        .unwrap();
+    let Some(scratch) = state.get_scratch(name) else {
+        return;
+    };
+
+    let cursor = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.set_highlighting("", None);
+            edit_area.set_pairs(state.config.pairs_for(edit_area.syntax_name()));
+            edit_area.set_word_chars(state.config.word_chars_for(edit_area.syntax_name()));
+            edit_area.set_indent_rules(state.config.indent_rules_for(edit_area.syntax_name()));
+            edit_area.set_snippets(
+                state.config.snippets_for(edit_area.syntax_name()),
+                state.config.date_format.clone(),
+            );
+            edit_area.set_rulers(state.config.rulers_for(""), state.config.highlight_overflow);
+            edit_area.set_highlighted(scratch.highlighted);
+            edit_area.set_content(scratch.str());
+            edit_area.set_notes(scratch.notes.keys().copied().collect());
+            edit_area.set_cursor(scratch.cursor);
+            edit_area.set_scroll(scratch.scroll_offset);
+            edit_area.set_read_only(false);
+            edit_area.enable();
+            edit_area.cursor()
+        })
+        .unwrap();
+
+    if let Some(pos) = siv.screen_mut().find_layer_from_name("welcome") {
+        siv.screen_mut().remove_layer(pos);
+    }
+    update_title(siv, state.is_scratch_edited(name), Path::new(""));
+    update_status(siv, Some(cursor));
+}
+
+/// Stores the cursor and scroll of the editor inside of the buffer of the current file
+///
+/// The callbacks of the editor already report them, but switching files must not depend on
+/// them having run, otherwise the view of the outgoing file could be reset.
+fn store_view(siv: &mut Cursive) {
+    let Some((hash, cursor, scroll_offset)) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            edit_area.is_enabled().then(|| {
+                (
+                    content_hash(edit_area.get_content()),
+                    edit_area.cursor(),
+                    edit_area.scroll(),
+                )
+            })
+        })
+        .flatten()
+    else {
+        return;
+    };
+    siv.with_user_data(|state: &mut State| {
+        // the editor may still show another file, like while a new one is loaded
+        let file = state.current_buffer_mut();
+        if let Some(file) = file.filter(|file| file.hash() == hash) {
+            file.cursor = cursor;
+            file.scroll_offset = scroll_offset;
+        }
+    });
+}
+
+/// Saves the edited current file before switching to another one, if `autosave_on_switch`
+/// is enabled
+fn save_outgoing(siv: &mut Cursive, next_file: &Path) {
+    let outgoing = siv
+        .with_user_data(|state: &mut State| {
+            let current_file = state
+                .current_file
+                .clone()
+                .filter(|file| file != next_file)?;
+            if !state.config.autosave_on_switch || !state.is_file_edited(&current_file) {
+                return None;
+            }
+            let content = state.get_file(&current_file)?.str();
+            Some((current_file, content))
+        })
+        .flatten();
+    if let Some((path, content)) = outgoing {
+        events::save(siv, Some((&path, &content))).handle(siv);
+    }
+}
+
+/// Reads the content of a text file and how it's stored, `None` if it's binary like an image
+///
+/// Files which can't be decoded or contain a null byte are seen as binary, see
+/// [`encoding::decode`]. With a limit only the beginning of the file up to it is read.
+pub fn read_text(path: &Path, limit: Option<u64>) -> Result<Option<(String, Format)>> {
+    let mut bytes = Vec::new();
+    fs::File::open(path)?
+        .take(limit.unwrap_or(u64::MAX))
+        .read_to_end(&mut bytes)?;
+    // the limit may cut a char in half
+    Ok(encoding::decode(&bytes, limit.is_some()))
+}
+
+/// Returns the length of the bytes without an incomplete char at the end
+///
+/// The missing bytes of the char may not have been written yet. Invalid bytes are kept.
+pub fn valid_utf8_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => bytes.len(),
+    }
+}
+
+/// Text shown inside of the disabled editor while no file is open
+///
+/// Lists the bindings for getting started and the recently opened files.
+pub fn empty_state() -> String {
+    let mut text = "No file is open\n\n".to_string();
+    for (binding, description) in [
+        ("Ctrl + o", "Opening a File/Project"),
+        ("Ctrl + n", "Creating a new File/Directory"),
+        ("Esc     ", "Showing all Bindings"),
+    ] {
+        text.push_str(&format!("{binding}  {description}\n"));
+    }
+
+    let recent_files = recent::load();
+    if !recent_files.is_empty() {
+        text.push_str("\nRecent Files\n");
+        for file in recent_files {
+            text.push_str(&format!("{}\n", file.to_string_lossy()));
+        }
+    }
+    text
+}
+
+/// Duration a notification is shown
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(4);
+/// Maximal number of notifications kept in the history
+const MAX_NOTIFICATIONS: usize = 100;
+/// Id of the latest notification, so older ones don't remove it when they expire
+static NOTIFICATION_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Shows a message inside of the status bar, which disappears after a few seconds
+///
+/// The message is also added to the history of notifications.
+pub fn notify(siv: &mut Cursive, message: impl Into<String>) {
+    let message = message.into();
+    siv.with_user_data(|state: &mut State| {
+        state.notifications.push_front(message.clone());
+        state.notifications.truncate(MAX_NOTIFICATIONS);
+    });
+    siv.call_on_name("notification", |view: &mut TextView| {
+        view.set_content(message);
+    });
+
+    let id = NOTIFICATION_ID.fetch_add(1, Ordering::Relaxed) + 1;
+    let cb_sink = siv.cb_sink().clone();
+    thread::spawn(move || {
+        thread::sleep(NOTIFICATION_DURATION);
+        // the app may have been quit in the meantime
+        let _ = cb_sink.send(Box::new(move |siv| {
+            if NOTIFICATION_ID.load(Ordering::Relaxed) == id {
+                siv.call_on_name("notification", |view: &mut TextView| {
+                    view.set_content("");
+                });
+            }
+        }));
+    });
+}
+
 /// Update the title of the editor panel including the current editing state via adding `*`
-pub fn update_title(siv: &mut Cursive, state: Option<&State>, path: &Path) {
-    let file_name = path
+pub fn update_title(siv: &mut Cursive, edited: bool, path: &Path) {
+    let file_name = if path.as_os_str().is_empty() {
+        // without a file the current scratch buffer is shown, if there is one
+        siv.with_user_data(|state: &mut State| state.current_scratch.clone())
+            .flatten()
+            .unwrap_or_default()
+    } else {
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    };
+
+    let mut title = if edited { file_name + " *" } else { file_name };
+    if !path.as_os_str().is_empty() && !path.exists() {
+        title += " (deleted on disk)";
+    }
+
+    let focused = is_editor_focused(siv);
+    let title = styled_title(siv, title, focused);
+    siv.call_on_name("editor_title", |view: &mut EditorPanel| {
+        view.set_title(title);
+    })
+    .unwrap();
+}
+
+/// Update the status bar to the position of the cursor, an empty status bar is shown without one
+///
+/// The column is the visual column, so tabs are expanded to the tab size. With column cursors
+/// the number of their lines is shown as well.
+pub fn update_status(siv: &mut Cursive, cursor: Option<Cursor>) {
+    let (selected, highlighted) = siv
+        .call_on_name("editor", |edit_area: &mut EditArea| {
+            let selected = edit_area
+                .selection()
+                .map(|selection| selection.split('\n').count());
+            (selected, edit_area.is_highlighted())
+        })
+        .unwrap_or((None, true));
+    let mut status = cursor.map_or(String::new(), |cursor| match selected {
+        Some(lines) => format!(
+            "Ln {}, Col {} ({lines} lines) ",
+            cursor.row + 1,
+            cursor.display_column + 1
+        ),
+        None => format!("Ln {}, Col {} ", cursor.row + 1, cursor.display_column + 1),
+    });
+    if cursor.is_some() && !highlighted {
+        status.insert_str(0, "Plain Text, ");
+    }
+    siv.call_on_name("status", |view: &mut TextView| view.set_content(status))
+        .unwrap();
+}
+
+/// Update the title of the tree panel to the name of the project directory
+pub fn update_tree_title(siv: &mut Cursive, project_path: &Path) {
+    let dir_name = project_path
         .file_name()
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
 
-    let title = if let Some(state) = state {
-        if state.is_file_edited(&path.to_path_buf()) {
-            file_name + " *"
-        } else {
-            file_name
+    let focused = !is_editor_focused(siv);
+    let title = styled_title(siv, dir_name, focused);
+    siv.call_on_name("tree_title", |view: &mut TreePanel| {
+        view.get_inner_mut().set_title(title);
+    })
+    .unwrap();
+}
+
+/// Re-styles both panel titles and borders so that the focused panel is highlighted
+pub fn update_focus(siv: &mut Cursive) {
+    let (edited, current_file, project_path) = siv
+        .with_user_data(|state: &mut State| {
+            (
+                state.is_current_file_edited(),
+                state.current_file.clone().unwrap_or_default(),
+                state.project_path.clone(),
+            )
+        })
+        .unwrap_or_default();
+
+    let editor_focused = is_editor_focused(siv);
+    let editor_theme = border_theme(siv, editor_focused);
+    let tree_theme = border_theme(siv, !editor_focused);
+    siv.call_on_name("editor_border", |view: &mut EditorBorder| {
+        view.set_theme(editor_theme);
+    });
+    siv.call_on_name("tree_border", |view: &mut TreeBorder| {
+        view.set_theme(tree_theme);
+    });
+
+    update_title(siv, edited, &current_file);
+    update_tree_title(siv, &project_path);
+}
+
+/// Returns `true` if the editor panel has the focus, otherwise the tree panel has it
+pub fn is_editor_focused(siv: &mut Cursive) -> bool {
+    siv.call_on_name("layout", |layout: &mut LinearLayout| {
+        layout.get_focus_index() == 1
+    })
+    .unwrap_or_default()
+}
+
+/// Colors of the panel borders, titles and the tree selection
+///
+/// Colors which aren't configured are derived from the theme.
+struct ChromeColors {
+    border: Color,
+    title: Color,
+    inactive_title: Option<Color>,
+    selection: Color,
+}
+
+/// Returns the configured colors of the panels, invalid ones are logged and replaced by the default
+fn chrome_colors(siv: &mut Cursive) -> ChromeColors {
+    let colors = siv
+        .with_user_data(|state: &mut State| {
+            [
+                state.config.border_color.clone(),
+                state.config.title_color.clone(),
+                state.config.inactive_title_color.clone(),
+                state.config.selection_color.clone(),
+            ]
+        })
+        .unwrap_or_default();
+    let [border, title, inactive_title, selection] = colors.map(|color| {
+        let color = color?;
+        let parsed = Color::parse(&color);
+        if parsed.is_none() {
+            warn!("Invalid color: {color}");
         }
-    } else {
-        file_name
-    };
+        parsed
+    });
 
-    siv.call_on_name("editor_title", |view: &mut EditorPanel| {
-        view.set_title(title);
+    let highlight = siv.current_theme().palette[PaletteColor::Highlight];
+    ChromeColors {
+        border: border.unwrap_or(highlight),
+        title: title.unwrap_or(highlight),
+        inactive_title,
+        selection: selection.unwrap_or(highlight),
+    }
+}
+
+/// Applies the selection color to the tree, needs to be called again if the theme changes
+pub fn update_tree_theme(siv: &mut Cursive) {
+    let mut theme = siv.current_theme().clone();
+    theme.palette[PaletteColor::Highlight] = chrome_colors(siv).selection;
+    siv.call_on_name("tree_title", |view: &mut TreePanel| {
+        view.get_inner_mut().get_inner_mut().set_theme(theme);
     })
     .unwrap();
 }
+
+/// Theme for drawing the panel borders, highlighted if focused and dimmed otherwise
+fn border_theme(siv: &mut Cursive, focused: bool) -> cursive::theme::Theme {
+    let border = chrome_colors(siv).border;
+    let mut theme = siv.current_theme().clone();
+    theme.palette[PaletteColor::Primary] = if focused {
+        border
+    } else {
+        Color::Light(BaseColor::Black)
+    };
+    theme
+}
+
+/// Highlights the title of the focused panel
+fn styled_title(siv: &mut Cursive, title: String, focused: bool) -> StyledString {
+    let colors = chrome_colors(siv);
+    if focused {
+        StyledString::styled(title, Style::from(colors.title).combine(Effect::Bold))
+    } else if let Some(color) = colors.inactive_title {
+        StyledString::styled(title, color)
+    } else {
+        StyledString::plain(title)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use cursive::{
+        event::{Event, Key},
+        view::{Nameable, Resizable},
+        views::{Panel, ThemedView},
+        Vec2, View,
+    };
+    use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
+
+    use super::*;
+
+    /// Returns the app with an editor showing the content inside of its panel and a status bar
+    fn app(content: &str) -> Cursive {
+        let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+        let mut edit_area = EditArea::new(Arc::new(SyntaxSet::load_defaults_newlines()), theme);
+        edit_area.set_content(content);
+        let mut siv = Cursive::new();
+        let editor_panel = Panel::new(ThemedView::new(
+            siv.current_theme().clone(),
+            edit_area.with_name("editor").full_screen(),
+        ))
+        .with_name("editor_title");
+        siv.add_layer(
+            LinearLayout::vertical()
+                .child(editor_panel)
+                .child(TextView::new("").with_name("status")),
+        );
+        siv
+    }
+
+    /// Returns the text of the status bar
+    fn status(siv: &mut Cursive) -> String {
+        siv.call_on_name("status", |view: &mut TextView| {
+            view.get_content().source().to_string()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn status_shows_the_visual_column() {
+        let mut siv = app("\tab");
+        let cursor = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                edit_area.set_cursor_byte_offset(2);
+                edit_area.cursor()
+            })
+            .unwrap();
+        update_status(&mut siv, Some(cursor));
+        assert_eq!(status(&mut siv), "Ln 1, Col 6 ");
+
+        update_status(&mut siv, None);
+        assert_eq!(status(&mut siv), "");
+    }
+
+    #[test]
+    fn status_follows_vertical_moves() {
+        let mut siv = app("first\nsecond\nthird");
+        let cursor = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                edit_area.on_event(Event::Key(Key::Down));
+                edit_area.on_event(Event::Key(Key::Down));
+                edit_area.on_event(Event::Key(Key::Up));
+                edit_area.cursor()
+            })
+            .unwrap();
+        update_status(&mut siv, Some(cursor));
+        assert_eq!(status(&mut siv), "Ln 2, Col 1 ");
+    }
+
+    #[test]
+    fn switching_buffers_keeps_their_view() {
+        let mut siv = app("");
+        siv.set_user_data(State::default());
+        let content = "line\n".repeat(100);
+        open_scratch(&mut siv, "a".into(), content.clone());
+
+        // moved without the callbacks of the editor, which would store the view as well
+        let (cursor, scroll) = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                edit_area.layout(Vec2::new(20, 10));
+                edit_area.set_cursor_byte_offset(5 * 80 + 2);
+                (edit_area.cursor(), edit_area.scroll())
+            })
+            .unwrap();
+        assert_eq!(cursor.row, 80);
+        assert!(scroll.y > 0);
+
+        open_scratch(&mut siv, "b".into(), content);
+        let other = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| edit_area.cursor())
+            .unwrap();
+        assert_eq!(other.byte_offset, 0);
+
+        show_scratch(&mut siv, "a");
+        let (shown, shown_scroll) = siv
+            .call_on_name("editor", |edit_area: &mut EditArea| {
+                (edit_area.cursor(), edit_area.scroll())
+            })
+            .unwrap();
+        assert_eq!(
+            (shown.row, shown.byte_offset),
+            (cursor.row, cursor.byte_offset)
+        );
+        assert_eq!(shown_scroll, scroll);
+        assert_eq!(status(&mut siv), "Ln 81, Col 3 ");
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use cursive::{
+    event::{Event, Key},
+    theme::Effect,
+    utils::markup::StyledString,
+    view::{Nameable, Resizable, ScrollStrategy, Scrollable},
+    views::{HideableView, NamedView, OnEventView, Panel, ResizedView, ScrollView, TextView},
+    Cursive,
+};
+
+use crate::{
+    ansi,
+    app::State,
+    error::ResultExt,
+    events,
+    search::Search,
+    ui::{edit_area::restyle, update_focus},
+};
+
+/// Maximal number of lines kept inside of the output, the oldest ones are dropped
+const MAX_LINES: usize = 10_000;
+
+/// Height of the output panel
+const HEIGHT: usize = 12;
+
+/// The output panel below the editor, which can be hidden
+pub type OutputPanel =
+    HideableView<OnEventView<ResizedView<Panel<NamedView<ScrollView<NamedView<TextView>>>>>>>;
+
+/// Creates the hidden output panel, which shows the output of all commands run via `Shift` + `F9`
+///
+/// While it's focused, `Del` clears the output, `Ctrl` + `f` searches it and `Esc` clears
+/// the highlighted matches.
+pub fn new() -> NamedView<OutputPanel> {
+    let text = TextView::new("")
+        .no_wrap()
+        .with_name("output")
+        .scrollable()
+        .scroll_x(true)
+        .scroll_strategy(ScrollStrategy::StickToBottom)
+        .with_name("output_scroll");
+    let panel = OnEventView::new(Panel::new(text).title("Output").fixed_height(HEIGHT))
+        .on_event(Key::Del, clear)
+        .on_event(Event::CtrlChar('f'), |s| events::search_output(s).handle(s))
+        .on_event(Key::Esc, |s| {
+            let searched = s
+                .with_user_data(|state: &mut State| state.output_search.take().is_some())
+                .unwrap();
+            if searched {
+                render(s);
+            } else {
+                events::info(s).handle(s);
+            }
+        });
+    HideableView::new(panel).hidden().with_name("output_panel")
+}
+
+/// Appends a line to the output, the line may contain ANSI colors
+pub fn append(siv: &mut Cursive, line: String) {
+    let (search, first, trimmed) = siv
+        .with_user_data(|state: &mut State| {
+            let output = Arc::make_mut(&mut state.output);
+            output.push_back(line.clone());
+            // trimmed in batches, as the whole output has to be shown again
+            let trimmed = output.len() > MAX_LINES + MAX_LINES / 10;
+            if trimmed {
+                output.drain(..output.len() - MAX_LINES);
+            }
+            (state.output_search.clone(), output.len() == 1, trimmed)
+        })
+        .unwrap();
+    if trimmed {
+        return render(siv);
+    }
+
+    let mut styled = StyledString::new();
+    if !first {
+        styled.append_plain("\n");
+    }
+    styled.append(styled_line(&line, search.as_ref()));
+    siv.call_on_name("output", |view: &mut TextView| view.append(styled));
+}
+
+/// Shows the whole output again, like after the search changed
+pub fn render(siv: &mut Cursive) {
+    let (output, search) = siv
+        .with_user_data(|state: &mut State| (state.output.clone(), state.output_search.clone()))
+        .unwrap();
+    let mut styled = StyledString::new();
+    for (i, line) in output.iter().enumerate() {
+        if i > 0 {
+            styled.append_plain("\n");
+        }
+        styled.append(styled_line(line, search.as_ref()));
+    }
+    siv.call_on_name("output", |view: &mut TextView| view.set_content(styled));
+}
+
+/// Converts the ANSI colors of a line and highlights the matches of the search
+fn styled_line(line: &str, search: Option<&Search>) -> StyledString {
+    let styled = ansi::parse(line);
+    let Some(search) = search else {
+        return styled;
+    };
+    let text = styled.source().to_string();
+    let found = search.matches(&text).collect::<Vec<_>>();
+    restyle(&text, &styled, &found, |style| {
+        style.combine(Effect::Reverse)
+    })
+}
+
+/// Removes all lines of the output
+pub fn clear(siv: &mut Cursive) {
+    siv.with_user_data(|state: &mut State| state.output = Arc::default());
+    siv.call_on_name("output", |view: &mut TextView| view.set_content(""));
+}
+
+/// Shows the output panel without focusing it, new output is scrolled to
+pub fn show(siv: &mut Cursive) {
+    siv.call_on_name("output_panel", |view: &mut OutputPanel| view.unhide());
+    siv.call_on_name(
+        "output_scroll",
+        |view: &mut ScrollView<NamedView<TextView>>| {
+            view.set_scroll_strategy(ScrollStrategy::StickToBottom)
+        },
+    );
+}
+
+/// Shows and focuses the output panel, or hides it if it's already shown
+pub fn toggle(siv: &mut Cursive) {
+    let visible = siv
+        .call_on_name("output_panel", |view: &mut OutputPanel| {
+            view.set_visible(!view.is_visible());
+            view.is_visible()
+        })
+        .unwrap();
+    if visible {
+        if let Ok(result) = siv.focus_name("output") {
+            result.process(siv);
+        }
+    } else if let Ok(result) = siv.focus_name("editor").or_else(|_| siv.focus_name("tree")) {
+        result.process(siv);
+    }
+    update_focus(siv);
+}
+
+/// Scrolls the output to the given line
+pub fn scroll_to_line(siv: &mut Cursive, line: usize) {
+    siv.call_on_name(
+        "output_scroll",
+        |view: &mut ScrollView<NamedView<TextView>>| {
+            let height = view.content_viewport().height();
+            view.set_offset((0, line.saturating_sub(height / 2)));
+        },
+    );
+}
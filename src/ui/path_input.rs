@@ -1,51 +1,48 @@
 use std::{
-    fs,
+    env, fs,
     path::{Path, PathBuf},
 };
 
 use cursive::{
+    event::Key,
     view::Nameable,
-    views::{EditView, LinearLayout, ScrollView, SelectView},
+    views::{EditView, LinearLayout, OnEventView, ScrollView, SelectView, TextView},
+    Cursive,
 };
 
 use crate::error::Result;
 
 /// Creates a filepath input view
 ///
-/// The name for the EditView is `name` + `"_edit"`, for the SelectView `name` + `"_select"`
+/// The name for the EditView is `name` + `"_edit"`, for the SelectView `name` + `"_select"`.
+/// Pressing `Tab` inside the EditView completes the common prefix of the suggested paths,
+/// pressing it again cycles through them.
 pub fn new(path: &Path, name: String, files: bool) -> Result<LinearLayout> {
     let view_name = name.clone() + "_edit";
     let select_name = name.clone() + "_select";
+    let info_name = name.clone() + "_info";
 
     let mut select = SelectView::new();
 
     let view_name_clone = view_name.clone();
-    let select_name_clone = select_name.clone();
+    let name_clone = name.clone();
     select.set_on_submit(move |siv, new_path: &String| {
         siv.call_on_name(&view_name_clone, |edit_view: &mut EditView| {
             edit_view.set_content(new_path);
         })
         .unwrap();
-        siv.call_on_name(&select_name_clone, |select_view: &mut SelectView| {
-            select_view.clear();
-            select_view
-                .add_all_str(&get_paths(&PathBuf::from(new_path), files).unwrap_or_default());
-        })
-        .unwrap();
+        update_suggestions(siv, &name_clone, new_path, files);
     });
 
-    select.add_all_str(&get_paths(path, files).unwrap_or_default());
+    let paths = get_paths(path, files).unwrap_or_default();
+    let info = TextView::new(no_matches_info(&paths));
+    select.add_all_str(paths);
 
     let mut edit_view = EditView::new().content(path.to_string_lossy());
 
-    let select_name_clone = select_name.clone();
+    let name_clone = name.clone();
     edit_view.set_on_edit(move |siv, new_path, _| {
-        let new_path = PathBuf::from(&new_path);
-        siv.call_on_name(&select_name_clone, |view: &mut SelectView| {
-            view.clear();
-            view.add_all_str(&get_paths(&new_path, files).unwrap_or_default());
-        })
-        .unwrap();
+        update_suggestions(siv, &name_clone, new_path, files);
     });
 
     let view_name_clone = view_name.clone();
@@ -65,11 +62,107 @@ pub fn new(path: &Path, name: String, files: bool) -> Result<LinearLayout> {
         }
     });
 
+    let edit_view = OnEventView::new(edit_view.with_name(view_name))
+        .on_pre_event(Key::Tab, move |siv| complete(siv, &name, files));
+
     Ok(LinearLayout::vertical()
-        .child(edit_view.with_name(view_name))
+        .child(edit_view)
+        .child(info.with_name(info_name))
         .child(ScrollView::new(select.with_name(select_name))))
 }
 
+/// Expands a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"));
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            PathBuf::from(home).join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Replaces the suggested paths with the ones for the input `path`
+fn update_suggestions(siv: &mut Cursive, name: &str, path: &str, files: bool) {
+    let paths = get_paths(&expand_home(path), files).unwrap_or_default();
+    siv.call_on_name(&format!("{name}_info"), |view: &mut TextView| {
+        view.set_content(no_matches_info(&paths));
+    })
+    .unwrap();
+    siv.call_on_name(&format!("{name}_select"), |view: &mut SelectView| {
+        view.clear();
+        view.add_all_str(paths);
+    })
+    .unwrap();
+}
+
+/// Completes the input to the common prefix of the suggested paths
+///
+/// If there is nothing left to complete, the suggested paths are cycled through.
+fn complete(siv: &mut Cursive, name: &str, files: bool) {
+    let input = siv
+        .call_on_name(&format!("{name}_edit"), |view: &mut EditView| {
+            view.get_content().to_string()
+        })
+        .unwrap();
+
+    let next = siv
+        .call_on_name(&format!("{name}_select"), |view: &mut SelectView| {
+            let paths = view.iter().map(|(path, _)| path).collect::<Vec<_>>();
+            let prefix = common_prefix(&paths);
+            let cycling = view.selection().is_some_and(|path| *path == input);
+            if !cycling && prefix.len() > expand_home(&input).to_string_lossy().len() {
+                return Some((prefix, true));
+            }
+
+            if cycling {
+                view.select_down(1);
+                if view.selection().is_some_and(|path| *path == input) {
+                    view.set_selection(0);
+                }
+            }
+            view.selection().map(|path| (path.to_string(), false))
+        })
+        .unwrap();
+
+    if let Some((next, update)) = next {
+        siv.call_on_name(&format!("{name}_edit"), |view: &mut EditView| {
+            view.set_content(next.clone());
+        })
+        .unwrap();
+        if update {
+            update_suggestions(siv, name, &next, files);
+        }
+    }
+}
+
+/// Returns the longest common prefix of the paths, on char boundaries
+fn common_prefix(paths: &[&str]) -> String {
+    let Some((first, rest)) = paths.split_first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for path in rest {
+        len = first
+            .char_indices()
+            .zip(path.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(len);
+    }
+    first[..len].to_string()
+}
+
+/// Text shown below the input if no paths are suggested
+fn no_matches_info(paths: &[String]) -> &'static str {
+    if paths.is_empty() {
+        "No matches"
+    } else {
+        ""
+    }
+}
+
 /// Getting all paths by a path with search functionality for incomplete paths.
 fn get_paths(path: &Path, include_files: bool) -> Result<Vec<String>> {
     let entries_result = fs::read_dir(path);